@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use crate::{Edge, PlacedItem};
+
+/// Two placed items which share part of an edge, see `Bin::adjacency`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Adjacency {
+    /// Index of the first item, in `Bin::solution`
+    pub first: usize,
+    /// Index of the second item, in `Bin::solution`. Always greater than 'first'.
+    pub second: usize,
+    /// The edge of the first item which touches the second item
+    pub edge: Edge,
+    /// The length of the shared part of the edge
+    pub length: usize,
+}
+
+/// Find all pairs of items sharing an edge over a length of at least one cell.
+/// Items touching only at a corner are not adjacent.
+pub(crate) fn adjacency<I:Clone>(items: &[PlacedItem<I>]) -> Vec<Adjacency> {
+    let mut result = vec![];
+    // For each coordinate, the items ending and starting there, along one axis
+    let mut columns: HashMap<usize, (Vec<usize>, Vec<usize>)> = HashMap::new();
+    let mut rows: HashMap<usize, (Vec<usize>, Vec<usize>)> = HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        columns.entry(item.x1).or_default().0.push(index);
+        columns.entry(item.x0).or_default().1.push(index);
+        rows.entry(item.y1).or_default().0.push(index);
+        rows.entry(item.y0).or_default().1.push(index);
+    }
+    let overlap = |a0: usize, a1: usize, b0: usize, b1: usize| a1.min(b1).saturating_sub(a0.max(b0));
+    for (ending, starting) in columns.values() {
+        for &a in ending {
+            for &b in starting {
+                let length = overlap(items[a].y0, items[a].y1, items[b].y0, items[b].y1);
+                if length > 0 {
+                    result.push(pair(a, b, Edge::Right, Edge::Left, length));
+                }
+            }
+        }
+    }
+    for (ending, starting) in rows.values() {
+        for &a in ending {
+            for &b in starting {
+                let length = overlap(items[a].x0, items[a].x1, items[b].x0, items[b].x1);
+                if length > 0 {
+                    result.push(pair(a, b, Edge::Bottom, Edge::Top, length));
+                }
+            }
+        }
+    }
+    result.sort_unstable_by_key(|x|(x.first, x.second));
+    result
+}
+
+/// The adjacency of 'a' touching 'b' with its edge 'edge', where 'b' touches 'a'
+/// with its edge 'opposite'.
+fn pair(a: usize, b: usize, edge: Edge, opposite: Edge, length: usize) -> Adjacency {
+    if a < b {
+        Adjacency { first: a, second: b, edge, length }
+    } else {
+        Adjacency { first: b, second: a, edge: opposite, length }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bin, Item};
+
+    #[test]
+    fn test_adjacency() {
+        let mut bin = Bin::new(4, 4);
+        bin.set_default_allow_rotate(false);
+        assert!(bin.pack([Item::new(2, 4, 'A'), Item::new(2, 2, 'B'), Item::new(1, 1, 'C')]));
+        let adjacency = bin.adjacency();
+        let named: Vec<_> = adjacency.iter().map(|x|(bin.solution()[x.first].id, bin.solution()[x.second].id, x.edge, x.length)).collect();
+        assert_eq!(named, vec![('A', 'B', Edge::Right, 2), ('A', 'C', Edge::Right, 1), ('B', 'C', Edge::Bottom, 1)]);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{Bin, PlacedItem, Rectangle};
+
+/// Which edges of an item need edge banding, see [`Bin::edge_banding`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct BandingRules {
+    /// Whether each edge of the item is banded: left, right, top, bottom. Like 'Item::margins',
+    /// this is in the orientation of the item before rotation.
+    pub edges: [bool; 4],
+    /// Whether the parts of edges along the edges of the bin are banded. These are often
+    /// factory edges, which need no banding.
+    pub bin_edges: bool,
+    /// Whether the parts of edges along reserved areas (see `Bin::reserved_areas`), such as
+    /// trimmed margins or defects, are banded
+    pub reserved_edges: bool,
+}
+
+impl Default for BandingRules {
+    /// All edges are banded, except along the edges of the bin and along reserved areas
+    fn default() -> Self {
+        BandingRules { edges: [true; 4], bin_edges: false, reserved_edges: false }
+    }
+}
+
+/// The length of edge banding needed for a placed item, see [`Bin::edge_banding`].
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq,Hash)]
+pub struct EdgeBanding {
+    /// The banded length of each edge: left, right, top, bottom. Unlike 'BandingRules::edges',
+    /// this is in the placed orientation, like 'PlacedItem::margins'.
+    pub lengths: [usize; 4],
+}
+
+impl EdgeBanding {
+    /// The total banded length of all edges
+    pub fn total(&self) -> usize {
+        self.lengths.iter().sum()
+    }
+}
+
+/// See [`Bin::edge_banding`]
+pub(crate) fn edge_banding<I:Clone>(bin: &Bin<I>, rules: impl Fn(&PlacedItem<I>) -> BandingRules) -> Vec<EdgeBanding> {
+    let (width, height) = (bin.width(), bin.height());
+    let reserved = bin.reserved_areas();
+    bin.items.iter().map(|item| {
+        let rules = rules(item);
+        let edges = item.rotation.rotate_sides(rules.edges);
+        // The strip of cells just outside each edge, if the edge is not on the edge of the bin
+        let strips = [
+            (item.x0 > 0).then(||Rectangle { x0: item.x0 - 1, y0: item.y0, x1: item.x0, y1: item.y1 }),
+            (item.x1 < width).then(||Rectangle { x0: item.x1, y0: item.y0, x1: item.x1 + 1, y1: item.y1 }),
+            (item.y0 > 0).then(||Rectangle { x0: item.x0, y0: item.y0 - 1, x1: item.x1, y1: item.y0 }),
+            (item.y1 < height).then(||Rectangle { x0: item.x0, y0: item.y1, x1: item.x1, y1: item.y1 + 1 }),
+        ];
+        let mut lengths = [0; 4];
+        for (side, strip) in strips.into_iter().enumerate() {
+            if !edges[side] {
+                continue;
+            }
+            let length = if side < 2 { item.height() } else { item.width() };
+            lengths[side] = match strip {
+                None if rules.bin_edges => length,
+                None => 0,
+                Some(_) if rules.reserved_edges => length,
+                Some(strip) => length - covered_length(&strip, &reserved, side < 2),
+            };
+        }
+        EdgeBanding { lengths }
+    }).collect()
+}
+
+/// The number of cells of the one cell thick 'strip' which lie in any of 'rects'. The strip
+/// is vertical if 'vertical' is true.
+fn covered_length(strip: &Rectangle, rects: &[Rectangle], vertical: bool) -> usize {
+    let mut ranges: Vec<(usize, usize)> = rects.iter().filter(|x|x.intersects(strip)).map(|x| {
+        if vertical { (x.y0.max(strip.y0), x.y1.min(strip.y1)) } else { (x.x0.max(strip.x0), x.x1.min(strip.x1)) }
+    }).collect();
+    ranges.sort();
+    let (mut covered, mut end) = (0, 0);
+    for (start, stop) in ranges {
+        covered += stop.saturating_sub(start.max(end));
+        end = end.max(stop);
+    }
+    covered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Item, PlaceOptions, Reservation};
+
+    #[test]
+    fn test_edge_banding() {
+        let mut bin = Bin::new(10, 10);
+        bin.reserve(Reservation::Cols(0..2));
+        bin.set_default_allow_rotate(false);
+        assert!(bin.place_all([Item::new(4, 10, 'A'), Item::new(3, 6, 'B')].into_iter(), PlaceOptions::default()));
+        assert_eq!((bin.placement_of(&'A').map(|x|x.x0), bin.placement_of(&'B').map(|x|x.x0)), (Some(2), Some(6)));
+        // 'A' lies between the reserved columns and 'B', and spans the height of the bin
+        let banding = bin.edge_banding(|_|BandingRules::default());
+        assert_eq!(banding[0].lengths, [0, 10, 0, 0]);
+        assert_eq!(banding[1].lengths, [6, 6, 0, 3]);
+        assert_eq!(banding[1].total(), 15);
+        let all = BandingRules { edges: [true; 4], bin_edges: true, reserved_edges: true };
+        assert_eq!(bin.edge_banding(|_|all)[0].total(), 28);
+        // Only the long edges of 'B'
+        let long = bin.edge_banding(|x|BandingRules { edges: [x.id == 'B', x.id == 'B', false, false], ..BandingRules::default() });
+        assert_eq!((long[0].total(), long[1].total()), (0, 12));
+    }
+}
@@ -0,0 +1,416 @@
+use std::cell::RefCell;
+
+use crate::spans::FreeSpans;
+use crate::{Rectangle, Run};
+
+/// Bitmaps with more cells than this are stored as tiles
+const TILED_THRESHOLD: usize = 1 << 26;
+/// Width and height of a tile, in cells. Each row of a tile is a u64.
+const TILE: usize = 64;
+
+#[derive(Clone)]
+enum Tile {
+    Empty,
+    Full,
+    Mixed(Box<[u64; TILE]>),
+}
+
+/// How a bin keeps track of which parts of it are occupied.
+/// See [`crate::Bin::with_backend`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub enum Backend {
+    /// 'Bitmap' for normal bins, 'Tiled' for very large ones (more than 2^26 cells)
+    #[default]
+    Auto,
+    /// One bit per cell
+    Bitmap,
+    /// One bit per cell, in tiles of 64x64 cells. Tiles which are completely free or
+    /// completely occupied use no memory for the bits.
+    Tiled,
+    /// Lists of the occupied rectangles and of the maximal free rectangles, as in the
+    /// 'MaxRects' algorithm. Memory use does not depend on the size of the bin, and only
+    /// positions in the corners of free rectangles are evaluated, instead of every cell.
+    /// This makes it practical to use a fine resolution, such as micrometers, for
+    /// near-exact coordinates. Checking a single cell takes time proportional to the
+    /// number of placed items.
+    FreeRects,
+}
+
+/// A fixed number of bits, stored in 64 bit words
+#[derive(Clone)]
+struct Bits {
+    words: Vec<u64>,
+}
+
+impl Bits {
+    /// 'len' bits, all set to 'value'
+    fn from_elem(len: usize, value: bool) -> Bits {
+        Bits { words: vec![if value { u64::MAX } else { 0 }; len.div_ceil(64)] }
+    }
+    fn get(&self, index: usize) -> bool {
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+    /// Set the bits 'start' to 'end' (exclusive) to 'value'
+    fn set_range(&mut self, start: usize, end: usize, value: bool) {
+        let mut index = start;
+        while index < end {
+            let bits = (end - index).min(64 - index % 64);
+            let mask = row_mask(index % 64, index % 64 + bits);
+            let word = &mut self.words[index / 64];
+            if value { *word |= mask } else { *word &= !mask }
+            index += bits;
+        }
+    }
+    /// The number of set bits from 'start' to 'end' (exclusive)
+    fn count_range(&self, start: usize, end: usize) -> usize {
+        let mut index = start;
+        let mut count = 0;
+        while index < end {
+            let bits = (end - index).min(64 - index % 64);
+            count += (self.words[index / 64] & row_mask(index % 64, index % 64 + bits)).count_ones() as usize;
+            index += bits;
+        }
+        count
+    }
+    /// The number of bits which can be stored without reallocating
+    fn capacity(&self) -> usize {
+        self.words.capacity() * 64
+    }
+}
+
+#[derive(Clone)]
+enum Storage {
+    Dense(Bits),
+    /// Tiles in row major order. Uniform tiles take no extra memory, which makes very
+    /// large, mostly empty (or mostly full) bitmaps cheap.
+    Tiled(Vec<Tile>),
+    /// Disjoint occupied rectangles, and all maximal free rectangles
+    Rects {
+        occupied: Vec<Rectangle>,
+        free: Vec<Rectangle>,
+    },
+}
+
+/// The parts of 'rect' outside 'hole', as disjoint rectangles
+fn subtract(rect: Rectangle, hole: &Rectangle, out: &mut Vec<Rectangle>) {
+    if !rect.intersects(hole) {
+        out.push(rect);
+        return;
+    }
+    let (y0, y1) = (rect.y0.max(hole.y0), rect.y1.min(hole.y1));
+    let parts = [
+        Rectangle { x0: rect.x0, y0: rect.y0, x1: rect.x1, y1: hole.y0 },
+        Rectangle { x0: rect.x0, y0: hole.y1, x1: rect.x1, y1: rect.y1 },
+        Rectangle { x0: rect.x0, y0, x1: hole.x0, y1 },
+        Rectangle { x0: hole.x1, y0, x1: rect.x1, y1 },
+    ];
+    out.extend(parts.into_iter().filter(|x|x.x0 < x.x1 && x.y0 < x.y1));
+}
+
+/// Update the maximal free rectangles 'free', after 'used' has become occupied
+fn split_free(free: &mut Vec<Rectangle>, used: &Rectangle) {
+    let mut added = vec![];
+    free.retain(|rect| {
+        if !rect.intersects(used) {
+            return true;
+        }
+        let parts = [
+            Rectangle { x1: used.x0, ..*rect },
+            Rectangle { x0: used.x1, ..*rect },
+            Rectangle { y1: used.y0, ..*rect },
+            Rectangle { y0: used.y1, ..*rect },
+        ];
+        added.extend(parts.into_iter().filter(|x|x.x0 < x.x1 && x.y0 < x.y1));
+        false
+    });
+    // Each new rectangle is maximal, unless it is contained in another one
+    for (index, rect) in added.iter().enumerate() {
+        let redundant = free.iter().any(|x|x.contains(rect)) ||
+            added.iter().enumerate().any(|(other, x)|other != index && x.contains(rect) && (x != rect || other < index));
+        if !redundant {
+            free.push(*rect);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Bitmap2d {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    storage: Storage,
+    spans: RefCell<FreeSpans>,
+}
+
+/// The bits 'x0' to 'x1' (exclusive) of a tile row
+fn row_mask(x0: usize, x1: usize) -> u64 {
+    let bits = x1 - x0;
+    if bits == TILE { u64::MAX } else { ((1u64 << bits) - 1) << x0 }
+}
+
+impl Bitmap2d {
+    pub(crate) fn with_backend(width: usize, height: usize, backend: Backend) -> Bitmap2d {
+        if width < 1 || height < 1 {
+            panic!("Width and height must both be > 0");
+        }
+        let tiles = || width.div_ceil(TILE).checked_mul(height.div_ceil(TILE)).expect("Bin is too large for the 'Tiled' backend, use 'FreeRects'");
+        let storage = match backend {
+            Backend::Auto if Bitmap2d::tiled_for(width, height) => Storage::Tiled(vec![Tile::Empty; tiles()]),
+            Backend::Tiled => Storage::Tiled(vec![Tile::Empty; tiles()]),
+            Backend::Auto | Backend::Bitmap => Storage::Dense(Bits::from_elem(width.checked_mul(height).expect("Bin is too large for the 'Bitmap' backend"), false)),
+            Backend::FreeRects => Storage::Rects {
+                occupied: vec![],
+                free: vec![Rectangle { x0: 0, y0: 0, x1: width, y1: height }],
+            },
+        };
+        Bitmap2d {
+            width,
+            height,
+            storage,
+            spans: RefCell::default(),
+        }
+    }
+    /// True if a bitmap of the given size is stored as tiles
+    pub(crate) fn tiled_for(width: usize, height: usize) -> bool {
+        width.saturating_mul(height) > TILED_THRESHOLD
+    }
+    /// The heap memory used by an empty bitmap of the given size
+    pub(crate) fn initial_bytes(width: usize, height: usize) -> usize {
+        if Bitmap2d::tiled_for(width, height) {
+            width.div_ceil(TILE).saturating_mul(height.div_ceil(TILE)).saturating_mul(std::mem::size_of::<Tile>())
+        } else {
+            width.saturating_mul(height) / 8
+        }
+    }
+    /// The backend of this bitmap. Never 'Auto'.
+    pub(crate) fn backend(&self) -> Backend {
+        match self.storage {
+            Storage::Dense(_) => Backend::Bitmap,
+            Storage::Tiled(_) => Backend::Tiled,
+            Storage::Rects { .. } => Backend::FreeRects,
+        }
+    }
+    /// True if every cell has its own bit. Checking many cells one at a time is cheapest then.
+    pub(crate) fn is_dense(&self) -> bool {
+        matches!(self.storage, Storage::Dense(_))
+    }
+    /// The maximal free rectangles, if they are kept track of
+    pub(crate) fn free_list(&self) -> Option<&[Rectangle]> {
+        match &self.storage {
+            Storage::Rects { free, .. } => Some(free),
+            _ => None,
+        }
+    }
+    /// The approximate number of bytes of heap memory used
+    pub(crate) fn memory_bytes(&self) -> usize {
+        match &self.storage {
+            Storage::Dense(bits) => bits.capacity() / 8,
+            Storage::Tiled(tiles) => tiles.capacity() * std::mem::size_of::<Tile>() +
+                tiles.iter().filter(|x|matches!(x, Tile::Mixed(_))).count() * TILE * 8,
+            Storage::Rects { occupied, free } => (occupied.capacity() + free.capacity()) * std::mem::size_of::<Rectangle>(),
+        }
+    }
+    /// A copy of this bitmap with the given size, which must not be smaller. New cells are free.
+    pub(crate) fn resized(&self, width: usize, height: usize) -> Bitmap2d {
+        let mut result = Bitmap2d::with_backend(width, height, self.backend());
+        if let Storage::Rects { occupied, .. } = &self.storage {
+            for rect in occupied {
+                result.fill(rect.x0, rect.y0, rect.width(), rect.height(), true);
+            }
+            return result;
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) {
+                    result.set(x, y, true);
+                }
+            }
+        }
+        result
+    }
+    fn tile_index(&self, x: usize, y: usize) -> usize {
+        (y / TILE) * self.width.div_ceil(TILE) + x / TILE
+    }
+    pub(crate) fn get(&self, x: usize, y: usize) -> bool {
+        match &self.storage {
+            Storage::Dense(bits) => bits.get(y*self.width + x),
+            Storage::Tiled(tiles) => match &tiles[self.tile_index(x, y)] {
+                Tile::Empty => false,
+                Tile::Full => true,
+                Tile::Mixed(rows) => rows[y % TILE] & (1 << (x % TILE)) != 0,
+            },
+            Storage::Rects { occupied, .. } => occupied.iter().any(|r|r.x0 <= x && x < r.x1 && r.y0 <= y && y < r.y1),
+        }
+    }
+    pub(crate) fn set(&mut self, x: usize, y: usize, value: bool) {
+        self.fill(x, y, 1, 1, value)
+    }
+    pub(crate) fn fill(&mut self, x0: usize, y0: usize, w: usize, h: usize, value: bool) {
+        self.spans.get_mut().fill(x0, y0, w, h, value);
+        let (width, height) = (self.width, self.height);
+        let tiles_x = width.div_ceil(TILE);
+        let tiles = match &mut self.storage {
+            Storage::Dense(bits) => {
+                for y in y0..y0+h {
+                    bits.set_range(y*width + x0, y*width + x0 + w, value);
+                }
+                return;
+            }
+            Storage::Tiled(tiles) => tiles,
+            Storage::Rects { occupied, free } => {
+                if w == 0 || h == 0 {
+                    return;
+                }
+                let rect = Rectangle { x0, y0, x1: x0 + w, y1: y0 + h };
+                let mut parts = vec![];
+                if value {
+                    // Only add the part which isn't occupied already, so that the
+                    // occupied rectangles stay disjoint.
+                    let mut new_parts = vec![rect];
+                    for existing in occupied.iter().filter(|x|x.intersects(&rect)) {
+                        for part in new_parts.drain(..) {
+                            subtract(part, existing, &mut parts);
+                        }
+                        std::mem::swap(&mut parts, &mut new_parts);
+                    }
+                    occupied.extend(new_parts);
+                    split_free(free, &rect);
+                } else {
+                    for existing in occupied.drain(..) {
+                        subtract(existing, &rect, &mut parts);
+                    }
+                    *occupied = parts;
+                    *free = vec![Rectangle { x0: 0, y0: 0, x1: width, y1: height }];
+                    for rect in occupied.iter() {
+                        split_free(free, rect);
+                    }
+                }
+                return;
+            }
+        };
+        if w == 0 || h == 0 {
+            return;
+        }
+        for ty in y0 / TILE..=(y0 + h - 1) / TILE {
+            for tx in x0 / TILE..=(x0 + w - 1) / TILE {
+                // The part of the tile within the bitmap, and the part to fill, in tile coordinates
+                let (tile_x0, tile_y0) = (tx * TILE, ty * TILE);
+                let (tile_w, tile_h) = ((width - tile_x0).min(TILE), (height - tile_y0).min(TILE));
+                let (fx0, fy0) = (x0.max(tile_x0) - tile_x0, y0.max(tile_y0) - tile_y0);
+                let (fx1, fy1) = ((x0 + w).min(tile_x0 + tile_w) - tile_x0, (y0 + h).min(tile_y0 + tile_h) - tile_y0);
+                let tile = &mut tiles[ty * tiles_x + tx];
+                if fx0 == 0 && fy0 == 0 && fx1 == tile_w && fy1 == tile_h {
+                    *tile = if value { Tile::Full } else { Tile::Empty };
+                    continue;
+                }
+                let rows = match tile {
+                    Tile::Empty if !value => continue,
+                    Tile::Full if value => continue,
+                    Tile::Empty => { *tile = Tile::Mixed(Box::new([0; TILE])); tile }
+                    Tile::Full => { *tile = Tile::Mixed(Box::new([u64::MAX; TILE])); tile }
+                    Tile::Mixed(_) => tile,
+                };
+                let Tile::Mixed(rows) = rows else { unreachable!() };
+                let mask = row_mask(fx0, fx1);
+                for row in &mut rows[fy0..fy1] {
+                    if value { *row |= mask } else { *row &= !mask }
+                }
+            }
+        }
+    }
+    pub(crate) fn count_occupied(&self, x0: usize, y0: usize, w: usize, h: usize) -> usize {
+        let tiles = match &self.storage {
+            Storage::Dense(bits) => return (y0..y0+h).map(|y|bits.count_range(y*self.width + x0, y*self.width + x0 + w)).sum(),
+            Storage::Tiled(tiles) => tiles,
+            Storage::Rects { occupied, .. } => {
+                let rect = Rectangle { x0, y0, x1: x0 + w, y1: y0 + h };
+                return occupied.iter().map(|x|x.intersection_area(&rect)).fold(0, usize::saturating_add);
+            }
+        };
+        if w == 0 || h == 0 {
+            return 0;
+        }
+        let tiles_x = self.width.div_ceil(TILE);
+        let mut count = 0;
+        for ty in y0 / TILE..=(y0 + h - 1) / TILE {
+            for tx in x0 / TILE..=(x0 + w - 1) / TILE {
+                let (tile_x0, tile_y0) = (tx * TILE, ty * TILE);
+                let (fx0, fy0) = (x0.max(tile_x0) - tile_x0, y0.max(tile_y0) - tile_y0);
+                let (fx1, fy1) = ((x0 + w).min(tile_x0 + TILE) - tile_x0, (y0 + h).min(tile_y0 + TILE) - tile_y0);
+                count += match &tiles[ty * tiles_x + tx] {
+                    Tile::Empty => 0,
+                    Tile::Full => (fx1 - fx0) * (fy1 - fy0),
+                    Tile::Mixed(rows) => {
+                        let mask = row_mask(fx0, fx1);
+                        rows[fy0..fy1].iter().map(|row|(row & mask).count_ones() as usize).sum()
+                    }
+                };
+            }
+        }
+        count
+    }
+    /// The widest interval `x0..x1` of free cells in row 'y', the leftmost if there are several
+    pub(crate) fn widest_free_span(&self, y: usize) -> Option<(usize, usize)> {
+        let mut spans = self.spans.borrow_mut();
+        let row = spans.row(y, || match &self.storage {
+            Storage::Rects { free, .. } => {
+                let mut covering: Vec<(usize, usize)> = free.iter().filter(|x|x.y0 <= y && y < x.y1).map(|x|(x.x0, x.x1)).collect();
+                covering.sort_unstable();
+                let mut merged: Vec<(usize, usize)> = vec![];
+                for (x0, x1) in covering {
+                    match merged.last_mut() {
+                        Some(last) if x0 <= last.1 => last.1 = last.1.max(x1),
+                        _ => merged.push((x0, x1)),
+                    }
+                }
+                merged
+            }
+            _ => self.row_runs(y).into_iter().filter(|x|!x.occupied).map(|x|(x.x0, x.x1)).collect(),
+        });
+        row.iter().copied().rev().max_by_key(|(x0, x1)|x1 - x0)
+    }
+    pub(crate) fn row_runs(&self, y: usize) -> Vec<Run> {
+        let mut runs: Vec<Run> = vec![];
+        for x in 0..self.width {
+            let occupied = self.get(x, y);
+            match runs.last_mut() {
+                Some(run) if run.occupied == occupied => run.x1 = x + 1,
+                _ => runs.push(Run { x0: x, x1: x + 1, occupied }),
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends_match_dense() {
+        let (width, height) = (150, 70);
+        let mut dense = Bitmap2d::with_backend(width, height, Backend::Bitmap);
+        let mut others = [Bitmap2d::with_backend(width, height, Backend::Tiled), Bitmap2d::with_backend(width, height, Backend::FreeRects)];
+        let fills = [(0, 0, 150, 70, true), (3, 5, 100, 60, false), (60, 60, 10, 10, true), (64, 0, 64, 64, true), (127, 63, 2, 2, false), (20, 20, 5, 5, true)];
+        for (x0, y0, w, h, value) in fills {
+            dense.fill(x0, y0, w, h, value);
+            for other in &mut others {
+                other.fill(x0, y0, w, h, value);
+            }
+        }
+        for other in &others {
+            for y in 0..height {
+                for x in 0..width {
+                    assert_eq!(dense.get(x, y), other.get(x, y), "{} {}", x, y);
+                }
+            }
+            for (x0, y0, w, h) in [(0, 0, 150, 70), (50, 30, 90, 35), (63, 63, 2, 2)] {
+                assert_eq!(dense.count_occupied(x0, y0, w, h), other.count_occupied(x0, y0, w, h));
+            }
+        }
+        let mut expected = crate::free_rects::maximal_free_rects(&dense);
+        let mut free = others[1].free_list().unwrap().to_vec();
+        expected.sort_by_key(|x|(x.x0, x.y0, x.x1, x.y1));
+        free.sort_by_key(|x|(x.x0, x.y0, x.x1, x.y1));
+        assert_eq!(free, expected);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::Rectangle;
+
+/// A repeating pattern of blocked cells, such as the holes of a vacuum table or locating
+/// pins. See [`crate::Bin::block_pattern`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum BlockPattern {
+    /// Blocks of 'w' x 'h' cells, the first of which has its top left corner at 'offset',
+    /// repeated every 'pitch_x' columns and every 'pitch_y' rows until the edges of the bin.
+    /// Blocks which extend past the edges of the bin are cut off.
+    Grid {
+        /// The distance between the left sides of horizontally adjacent blocks
+        pitch_x: usize,
+        /// The distance between the tops of vertically adjacent blocks
+        pitch_y: usize,
+        /// The width of each block
+        w: usize,
+        /// The height of each block
+        h: usize,
+        /// The x and y coordinates of the first block
+        offset: (usize, usize),
+    },
+}
+
+impl BlockPattern {
+    /// The blocks of the pattern in a bin of the given size, row by row
+    pub(crate) fn rects(&self, width: usize, height: usize) -> Vec<Rectangle> {
+        match *self {
+            BlockPattern::Grid { pitch_x, pitch_y, w, h, offset: (x, y) } => {
+                if w == 0 || h == 0 {
+                    return vec![];
+                }
+                let mut rects = vec![];
+                for y0 in (y..height).step_by(pitch_y) {
+                    for x0 in (x..width).step_by(pitch_x) {
+                        rects.push(Rectangle { x0, y0, x1: x0.saturating_add(w).min(width), y1: y0.saturating_add(h).min(height) });
+                    }
+                }
+                rects
+            }
+        }
+    }
+
+    /// Panics unless the pattern repeats at a positive distance
+    pub(crate) fn check(&self) {
+        match self {
+            BlockPattern::Grid { pitch_x, pitch_y, .. } => if *pitch_x == 0 || *pitch_y == 0 {
+                panic!("The pitch of a block pattern must be > 0");
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bin, Item, PlaceOptions};
+
+    #[test]
+    fn test_block_pattern() {
+        let mut bin = Bin::new(10, 10);
+        bin.block_pattern(BlockPattern::Grid { pitch_x: 4, pitch_y: 5, w: 2, h: 1, offset: (1, 2) });
+        let blocks = bin.reserved_areas();
+        assert_eq!(blocks.len(), 6);
+        assert_eq!(blocks[2], Rectangle { x0: 9, y0: 2, x1: 10, y1: 3 });
+        assert_eq!(blocks[5].y0, 7);
+        // Items go around the blocks
+        let items = [Item::new(10, 2, 'A'), Item::new(10, 2, 'B'), Item::new(10, 2, 'C'), Item::new(1, 10, 'D')];
+        assert!(bin.place_all(items.into_iter(), PlaceOptions::default()));
+        for item in bin.solution() {
+            assert!(blocks.iter().all(|x|!x.intersects(&item.rect())));
+        }
+    }
+}
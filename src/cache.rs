@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::{Bin, Item, PlaceOptions, PlacedItem};
+
+/// A solution stored by a [`PackerCache`].
+#[derive(Debug,Clone)]
+pub struct CachedSolution<I:Clone> {
+    /// The return value of `Bin::place_all`
+    pub all_fit: bool,
+    /// The contents of the bin after packing
+    pub items: Vec<PlacedItem<I>>,
+}
+
+/// Storage used by a [`PackerCache`]. Implement this to keep solutions
+/// somewhere other than in memory, or to bound the number of entries kept.
+pub trait CacheStore<I:Clone> {
+    /// The solution stored for 'key', if any
+    fn get(&mut self, key: u64) -> Option<CachedSolution<I>>;
+    /// Store 'solution' for 'key', replacing any previous solution
+    fn put(&mut self, key: u64, solution: CachedSolution<I>);
+}
+
+/// A [`CacheStore`] which keeps all solutions in memory, without limit.
+#[derive(Debug,Clone)]
+pub struct MemoryStore<I:Clone> {
+    solutions: HashMap<u64, CachedSolution<I>>,
+}
+
+impl<I:Clone> Default for MemoryStore<I> {
+    fn default() -> Self {
+        MemoryStore {
+            solutions: HashMap::new(),
+        }
+    }
+}
+
+impl<I:Clone> MemoryStore<I> {
+    /// The number of stored solutions
+    pub fn len(&self) -> usize {
+        self.solutions.len()
+    }
+
+    /// True if no solutions are stored
+    pub fn is_empty(&self) -> bool {
+        self.solutions.is_empty()
+    }
+}
+
+impl<I:Clone> CacheStore<I> for MemoryStore<I> {
+    fn get(&mut self, key: u64) -> Option<CachedSolution<I>> {
+        self.solutions.get(&key).cloned()
+    }
+    fn put(&mut self, key: u64, solution: CachedSolution<I>) {
+        self.solutions.insert(key, solution);
+    }
+}
+
+/// Caches the results of [`Bin::place_all`].
+///
+/// Solutions are keyed by a hash of the bin (its size, contents and settings), the
+/// options and the items. The order of the items is not part of the key, since they are
+/// sorted before packing anyway (a hit may however place equally sized items in each other's
+/// positions). Packing the same instance again restores the stored solution instead of packing.
+///
+/// The cancel callback and deadline are not part of the key, and runs which were
+/// cancelled are not stored. Bins with an unloading order or item order (see
+/// `Bin::set_unloading_order` and `Bin::set_item_order`), and options with a random number
+/// generator, are never cached, since these can't be compared.
+///
+/// The key is the 64 bit FNV-1a hash of the instance, with all integers hashed as 64 bit little
+/// endian values. Unlike the hasher of the standard library, this does not change between
+/// platforms and releases, so the solutions can be kept in a persistent store, as long as the
+/// 'Hash' implementation of the ids does not change. Updates of this crate may however add
+/// settings to the key. Note that two different instances may, with very low probability,
+/// get the same key.
+pub struct PackerCache<I:Clone, S:CacheStore<I> = MemoryStore<I>> {
+    store: S,
+    hits: u64,
+    misses: u64,
+    _marker: std::marker::PhantomData<I>,
+}
+
+impl<I:Clone> Default for PackerCache<I> {
+    fn default() -> Self {
+        PackerCache::new(MemoryStore::default())
+    }
+}
+
+impl<I:Clone, S:CacheStore<I>> PackerCache<I, S> {
+    /// Create a cache using the given store
+    pub fn new(store: S) -> PackerCache<I, S> {
+        PackerCache {
+            store,
+            hits: 0,
+            misses: 0,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// The underlying store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// The underlying store, mutably
+    pub fn store_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// The number of calls to 'place_all' which returned a stored solution
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of calls to 'place_all' which had to pack
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Like [`Bin::place_all`], but returns a stored solution if the same instance has been
+    /// packed before. On a hit, the bin statistics are reset, apart from 'zone_utilization',
+    /// 'weighted_utilization' and 'kerf_area'.
+    pub fn place_all(&mut self, bin: &mut Bin<I>, input: impl Iterator<Item=Item<I>>, options: PlaceOptions) -> bool where I: Hash {
+        if bin.unloading.is_some() || bin.item_order.is_some() || options.has_rng() {
+            self.misses += 1;
+            return bin.place_all(input, options);
+        }
+        let items: Vec<Item<I>> = input.collect();
+        let key = cache_key(bin, &items, &options);
+        if let Some(solution) = self.store.get(key) {
+            self.hits += 1;
+            bin.restore(solution.items);
+            bin.stats.zone_utilization = bin.zone_utilization();
+            bin.stats.weighted_utilization = bin.weighted_utilization();
+            bin.stats.kerf_area = bin.kerf_area(options.spacing());
+            bin.stats.shared_cut_length = bin.shared_cut_length(options.spacing());
+            return solution.all_fit;
+        }
+        self.misses += 1;
+        let all_fit = bin.place_all(items.into_iter(), options);
+        if !bin.stats().cancelled {
+            self.store.put(key, CachedSolution {
+                all_fit,
+                items: bin.solution().to_vec(),
+            });
+        }
+        all_fit
+    }
+}
+
+fn cache_key<I:Clone+Hash>(bin: &Bin<I>, items: &[Item<I>], options: &PlaceOptions) -> u64 {
+    let mut item_hashes: Vec<u64> = items.iter().map(|item| {
+        let mut hasher = StableHasher::default();
+        (item.w, item.h, item.allow_rotate, item.margins, item.tags, item.optional, &item.x_band, &item.y_band, &item.id).hash(&mut hasher);
+        hasher.finish()
+    }).collect();
+    item_hashes.sort_unstable();
+    let mut hasher = StableHasher::default();
+    bin.hash_config(&mut hasher);
+    options.hash_config(&mut hasher);
+    item_hashes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 64 bit FNV-1a, see [`PackerCache`]
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+    // The signed integers are hashed as the unsigned ones
+    fn write_u16(&mut self, value: u16) {
+        self.write_u64(value as u64);
+    }
+    fn write_u32(&mut self, value: u32) {
+        self.write_u64(value as u64);
+    }
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let items = || (0..4).map(|id| Item::new(2 + id, 3, id));
+        let mut cache = PackerCache::default();
+        let mut first = Bin::new(10, 10);
+        assert!(cache.place_all(&mut first, items(), PlaceOptions::default()));
+        let mut second = Bin::new(10, 10);
+        assert!(cache.place_all(&mut second, items().rev(), PlaceOptions::default()));
+        assert_eq!(format!("{:?}", first.solution()), format!("{:?}", second.solution()));
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(format!("{:?}", second.get_largest_hole()), format!("{:?}", first.get_largest_hole()));
+
+        let mut third = Bin::new(10, 10);
+        cache.place_all(&mut third, items(), PlaceOptions::default().with_spacing(1));
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+        assert_eq!(cache.store().len(), 2);
+
+        // Regions are part of the key
+        let mut restricted = Bin::new(10, 10);
+        restricted.restrict_to_regions(0, [crate::Rectangle { x0: 5, y0: 5, x1: 10, y1: 10 }]);
+        assert!(cache.place_all(&mut restricted, items(), PlaceOptions::default()));
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+        assert!(restricted.verify_regions().is_ok());
+
+        // So are the bands and the backend
+        assert!(cache.place_all(&mut Bin::new(10, 10), items().take(2), PlaceOptions::default()));
+        let mut banded = Bin::new(10, 10);
+        assert!(cache.place_all(&mut banded, items().take(2).map(|x|Item { y_band: Some(6..7), ..x }), PlaceOptions::default()));
+        assert!(banded.solution().iter().all(|x|x.y0 == 6));
+        let mut free_rects = Bin::with_backend(10, 10, crate::Backend::FreeRects);
+        cache.place_all(&mut free_rects, items(), PlaceOptions::default());
+        assert_eq!((cache.hits(), cache.misses()), (1, 6));
+
+        // Bins with an item order are packed every time
+        let mut ordered = Bin::new(10, 10);
+        ordered.set_item_order(|a: &Item<usize>, b: &Item<usize>|a.id.cmp(&b.id));
+        cache.place_all(&mut ordered.clone(), items(), PlaceOptions::default());
+        cache.place_all(&mut ordered, items(), PlaceOptions::default());
+        assert_eq!((cache.hits(), cache.misses(), cache.store().len()), (1, 8, 6));
+    }
+
+    #[test]
+    fn test_stable_key() {
+        // Keys must not change between platforms and releases, since they may be persisted
+        let key = cache_key(&Bin::new(10, 10), &[Item::new(2, 3, 0u32)], &PlaceOptions::default());
+        assert_eq!(key, 12233489596769406569);
+    }
+}
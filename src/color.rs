@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+
+/// An RGB color, as used when rendering layouts. Displays as `#rrggbb`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Color {
+    /// Red component
+    pub r: u8,
+    /// Green component
+    pub g: u8,
+    /// Blue component
+    pub b: u8,
+}
+
+impl Color {
+    /// The color number 'index' of a sequence of visually distinct colors. The hue
+    /// is stepped by the golden ratio, so that consecutive colors are far apart,
+    /// and no two colors early in the sequence are close.
+    pub fn from_index(index: usize) -> Color {
+        const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+        let hue = (index as f64 * GOLDEN_RATIO_CONJUGATE).fract();
+        Color::from_hsl(hue, 0.65, 0.6)
+    }
+
+    /// Convert from hue, saturation and lightness, all in the range 0 to 1
+    fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let channel = |offset: f64| {
+            let k = (offset + hue * 12.0) % 12.0;
+            let value = lightness - chroma / 2.0 * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0);
+            (value * 255.0).round() as u8
+        };
+        Color { r: channel(0.0), g: channel(8.0), b: channel(4.0) }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Assigns colors to item ids, so that all renderings of a layout use the same color
+/// for the same item. Ids are given colors from [`Color::from_index`] in the order they
+/// are first seen, so the assignment is deterministic for a given order of ids.
+#[derive(Debug,Clone)]
+pub struct ColorMap<I> {
+    assigned: HashMap<I, Color>,
+}
+
+impl<I> Default for ColorMap<I> {
+    fn default() -> Self {
+        ColorMap {
+            assigned: HashMap::new(),
+        }
+    }
+}
+
+impl<I:Clone+Eq+Hash> ColorMap<I> {
+    /// Create a map without any assigned colors
+    pub fn new() -> ColorMap<I> {
+        ColorMap::default()
+    }
+
+    /// Create a map assigning colors to 'ids' in order
+    pub fn with_ids<'a>(ids: impl IntoIterator<Item=&'a I>) -> ColorMap<I> where I: 'a {
+        let mut map = ColorMap::new();
+        for id in ids {
+            map.color(id);
+        }
+        map
+    }
+
+    /// The color of 'id', assigning the next unused color if it has none yet
+    pub fn color(&mut self, id: &I) -> Color {
+        let next = self.assigned.len();
+        *self.assigned.entry(id.clone()).or_insert_with(|| Color::from_index(next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.5).to_string(), "#ff0000");
+        assert_eq!(Color::from_hsl(1.0 / 3.0, 1.0, 0.5).to_string(), "#00ff00");
+        let mut map = ColorMap::with_ids(&['a', 'b']);
+        assert_eq!(map.color(&'b'), Color::from_index(1));
+        assert_eq!(map.color(&'c'), Color::from_index(2));
+        assert_eq!(map.color(&'a'), Color::from_index(0));
+        assert_ne!(Color::from_index(0), Color::from_index(1));
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+use crate::{Bin, Item, PlaceOptions, SpacingPolicy, Strategy, Tags};
+
+/// The final search of 'place_all', see `PlaceOptions::with_final_search`. 'placed' tells
+/// whether each of 'items' was placed, in packing order. Returns true if all items could be
+/// placed, in which case the bin is updated.
+pub(crate) fn complete<I:Clone>(bin: &mut Bin<I>, items: &[Item<I>], placed: &[bool], max_items: usize, budget: Duration, options: &mut PlaceOptions) -> bool {
+    let start = Instant::now();
+    if options.should_degrade() {
+        bin.stats.degraded = true;
+        return false;
+    }
+    let deadline = options.soft_deadline().map_or(start + budget, |soft|soft.min(start + budget));
+    let is_placed = |index: usize|placed.get(index).copied().unwrap_or(false);
+    let mut search_items: Vec<&Item<I>> = items.iter().enumerate().filter(|(index, _)|!is_placed(*index)).map(|(_, x)|x).collect();
+    let unplaced = search_items.len();
+    if unplaced > max_items {
+        return false;
+    }
+    // Also move the items placed last, since they took the space the others needed
+    let tail: Vec<&Item<I>> = items.iter().enumerate().filter(|(index, _)|is_placed(*index)).map(|(_, x)|x).rev().take(max_items - unplaced).collect();
+    let mut base = bin.clone();
+    base.autosave = None;
+    let kept = base.items[..base.items.len() - tail.len()].to_vec();
+    base.restore(kept);
+    search_items.extend(tail);
+    let needed = search_items.iter().map(|x|x.w.saturating_mul(x.h)).fold(0, usize::saturating_add);
+    let available = base.width().saturating_mul(base.height()) - base.placed_quality().1;
+    let found = if needed <= available { search(&base, &mut search_items, deadline, options) } else { None };
+    bin.stats.duration += start.elapsed();
+    bin.stats.cancelled = options.was_cancelled();
+    let Some(found) = found else {
+        return false;
+    };
+    #[cfg(feature = "log")]
+    log::debug!("Final search placed the remaining {} items in {:?}", unplaced, start.elapsed());
+    bin.items = found.items;
+    bin.bitmap = found.bitmap;
+    bin.items_changed();
+    bin.update_holes(false);
+    bin.rejections.clear();
+    bin.stats.items_placed += unplaced;
+    bin.stats.items_rejected = 0;
+    bin.stats.zone_utilization = bin.zone_utilization();
+    bin.stats.weighted_utilization = bin.weighted_utilization();
+    bin.stats.kerf_area = bin.kerf_area(options.spacing());
+    bin.stats.shared_cut_length = bin.shared_cut_length(options.spacing());
+    bin.run_autosave();
+    true
+}
+
+/// Try every order and orientation of 'items', returning the bin with all of them placed.
+/// None if they don't fit, or the deadline has passed.
+fn search<I:Clone>(bin: &Bin<I>, items: &mut Vec<&Item<I>>, deadline: Instant, options: &mut PlaceOptions) -> Option<Bin<I>> {
+    if items.is_empty() {
+        return Some(bin.clone());
+    }
+    // Identical items give the same layouts, so only the first of them is tried first
+    let mut tried = vec![];
+    for index in 0..items.len() {
+        let item = items[index];
+        let tags = if let SpacingPolicy::SharedEdgeTagged(_) = options.spacing_policy() { item.tags } else { Tags::NONE };
+        let class = (item.w, item.h, bin.allows_rotation(item), item.margins, item.x_band.clone(), item.y_band.clone(), tags);
+        if tried.contains(&class) {
+            continue;
+        }
+        tried.push(class);
+        let strategies: &[Strategy] = if bin.allows_rotation(item) && !item.is_symmetric() {
+            &[Strategy::DoNotRotate, Strategy::Rotate]
+        } else {
+            &[Strategy::DoNotRotate]
+        };
+        for &strategy in strategies {
+            if Instant::now() >= deadline || options.is_cancelled() {
+                return None;
+            }
+            let mut attempt = bin.clone();
+            if !attempt.add_to_best_fit(item, strategy, options) {
+                continue;
+            }
+            items.remove(index);
+            let found = search(&attempt, items, deadline, options);
+            items.insert(index, item);
+            if found.is_some() {
+                return found;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_final_search() {
+        let items = || [
+            Item::rotatable(5, 4, 0),
+            Item::new(2, 4, 1).with_allow_rotate(false),
+            Item::rotatable(3, 1, 2),
+        ].into_iter();
+        let mut bin = Bin::new(6, 6);
+        assert!(!bin.place_all(items(), PlaceOptions::default()));
+        let mut bin = Bin::new(6, 6);
+        assert!(bin.place_all(items(), PlaceOptions::default().with_final_search(3, Duration::from_secs(10))));
+        assert_eq!(bin.solution().len(), 3);
+        assert_eq!(bin.stats().items_placed, 3);
+        assert!(bin.rejections().is_empty());
+        // Too many items left for the search
+        let mut bin = Bin::new(6, 6);
+        assert!(!bin.place_all(items(), PlaceOptions::default().with_final_search(0, Duration::from_secs(10))));
+    }
+
+    #[test]
+    fn test_search_bands() {
+        // Placing A first takes the only position of B, so B must be tried first
+        let (a, b) = (Item::new(2, 2, 'A').with_x_band(0..3), Item::new(2, 2, 'B').with_x_band(0..1));
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let found = search(&Bin::new(4, 2), &mut vec![&a, &b], deadline, &mut PlaceOptions::default()).unwrap();
+        let placed: Vec<_> = found.solution().iter().map(|x|(x.id, x.x0)).collect();
+        assert_eq!(placed, vec![('B', 0), ('A', 2)]);
+    }
+}
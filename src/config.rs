@@ -0,0 +1,276 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::{BinSelection, ContactWeights, Corner, Heuristic, OverlapTolerance, PlaceOptions, ScanOrder, SliverPolicy, SpacingPolicy, Strategy, Tags, TieBreak};
+
+/// A named set of all the options which affect the result of packing, which can be stored
+/// together with a solution (see [`crate::write_solution_with_config`]) so that the result can
+/// be reproduced exactly later. Unlike [`PlaceOptions`], a configuration is plain data: it has
+/// no cancellation, deadlines or random number generator.
+///
+/// ```
+/// use bin_packing_2d::{Bin, Heuristic, Item, PackConfig};
+/// let config = PackConfig::new("labels").with_options(|x|x.with_spacing(1).with_heuristic(Heuristic::FirstFit).with_seed(7));
+/// let mut bin = Bin::new(10, 10);
+/// assert!(bin.place_all([Item::new(4, 4, 'A'), Item::new(4, 4, 'B')].into_iter(), config.options()));
+/// ```
+#[derive(Debug,Clone,PartialEq)]
+pub struct PackConfig {
+    /// A name for the configuration, for instance the profile it was chosen from. The name is
+    /// not compared when checking that a solution was packed using a configuration.
+    pub name: String,
+    /// See `PlaceOptions::with_strategies`
+    pub strategies: Vec<Strategy>,
+    /// See `PlaceOptions::with_spacing`
+    pub spacing: usize,
+    /// See `PlaceOptions::with_spacing_policy`
+    pub spacing_policy: SpacingPolicy,
+    /// See `PlaceOptions::with_heuristic`
+    pub heuristic: Heuristic,
+    /// See `PlaceOptions::with_seed`
+    pub seed: Option<u64>,
+    /// See `PlaceOptions::with_deduplication`
+    pub deduplicate: bool,
+    /// See `PlaceOptions::with_scan_order`
+    pub scan_order: ScanOrder,
+    /// See `PlaceOptions::with_sliver_policy`
+    pub sliver_policy: SliverPolicy,
+    /// See `PlaceOptions::with_min_offcut`
+    pub min_offcut: Option<usize>,
+    /// See `PlaceOptions::with_overlap_tolerance`
+    pub overlap_tolerance: OverlapTolerance,
+    /// See `PlaceOptions::with_tie_break`
+    pub tie_break: TieBreak,
+    /// See `PlaceOptions::with_bin_selection`
+    pub bin_selection: BinSelection,
+    /// See `PlaceOptions::with_shared_cut_weight`
+    pub shared_cut_weight: usize,
+    /// See `PlaceOptions::with_final_search`
+    pub final_search: Option<(usize, Duration)>,
+    /// See `PlaceOptions::with_utilization_target`
+    pub utilization_target: Option<f64>,
+}
+
+impl PackConfig {
+    /// A configuration with the default options
+    pub fn new(name: impl Into<String>) -> PackConfig {
+        PackConfig::from_options(name, &PlaceOptions::default())
+    }
+
+    /// The configuration of 'options'
+    pub fn from_options(name: impl Into<String>, options: &PlaceOptions) -> PackConfig {
+        PackConfig {
+            name: name.into(),
+            strategies: options.strategies().to_vec(),
+            spacing: options.spacing(),
+            spacing_policy: options.spacing_policy(),
+            heuristic: options.heuristic(),
+            seed: options.seed(),
+            deduplicate: options.deduplicate(),
+            scan_order: options.scan_order(),
+            sliver_policy: options.sliver_policy(),
+            min_offcut: options.min_offcut(),
+            overlap_tolerance: options.overlap_tolerance(),
+            tie_break: options.tie_break(),
+            bin_selection: options.bin_selection(),
+            shared_cut_weight: options.shared_cut_weight(),
+            final_search: options.final_search(),
+            utilization_target: options.utilization_target(),
+        }
+    }
+
+    /// Change the configuration using the builder methods of [`PlaceOptions`]
+    pub fn with_options(self, change: impl FnOnce(PlaceOptions<'static>) -> PlaceOptions<'static>) -> PackConfig {
+        PackConfig::from_options(self.name.clone(), &change(self.options()))
+    }
+
+    /// Options for packing using this configuration
+    pub fn options(&self) -> PlaceOptions<'static> {
+        let mut options = PlaceOptions::default()
+            .with_strategies(self.strategies.iter().copied())
+            .with_spacing(self.spacing)
+            .with_spacing_policy(self.spacing_policy)
+            .with_heuristic(self.heuristic)
+            .with_deduplication(self.deduplicate)
+            .with_scan_order(self.scan_order)
+            .with_sliver_policy(self.sliver_policy)
+            .with_overlap_tolerance(self.overlap_tolerance)
+            .with_tie_break(self.tie_break)
+            .with_bin_selection(self.bin_selection)
+            .with_shared_cut_weight(self.shared_cut_weight);
+        if let Some(seed) = self.seed {
+            options = options.with_seed(seed);
+        }
+        if let Some(min_offcut) = self.min_offcut {
+            options = options.with_min_offcut(min_offcut);
+        }
+        if let Some((max_items, budget)) = self.final_search {
+            options = options.with_final_search(max_items, budget);
+        }
+        if let Some(target) = self.utilization_target {
+            options = options.with_utilization_target(target);
+        }
+        options
+    }
+
+    /// The name of the first option which differs from 'other', ignoring the names of the
+    /// configurations
+    pub fn difference(&self, other: &PackConfig) -> Option<&'static str> {
+        self.fields().into_iter().zip(other.fields()).skip(1).find(|(a, b)|a != b).map(|(a, _)|a.0)
+    }
+
+    /// The options as keys and values, in the order they are written
+    pub(crate) fn fields(&self) -> Vec<(&'static str, String)> {
+        let optional = |value: Option<String>|value.unwrap_or_else(||"none".to_string());
+        vec![
+            ("name", self.name.clone()),
+            ("strategies", self.strategies.iter().map(|x|format!("{:?}", x)).collect::<Vec<_>>().join(",")),
+            ("spacing", self.spacing.to_string()),
+            ("spacing_policy", match self.spacing_policy {
+                SpacingPolicy::SharedEdgeTagged(tags) => format!("SharedEdgeTagged {}", tags.0),
+                policy => format!("{:?}", policy),
+            }),
+            ("heuristic", match self.heuristic {
+                Heuristic::WeightedContact(x) => format!("WeightedContact {} {} {}", x.free_cell, x.open_side, x.no_corner),
+                heuristic => format!("{:?}", heuristic),
+            }),
+            ("seed", optional(self.seed.map(|x|x.to_string()))),
+            ("deduplicate", self.deduplicate.to_string()),
+            ("scan_order", match self.scan_order {
+                ScanOrder::Rows(corner) => format!("Rows {:?}", corner),
+                ScanOrder::Columns(corner) => format!("Columns {:?}", corner),
+                ScanOrder::SpiralFromCenter => "SpiralFromCenter".to_string(),
+            }),
+            ("sliver_policy", match self.sliver_policy {
+                SliverPolicy::Allow => "Allow".to_string(),
+                SliverPolicy::Penalize { min_width, penalty } => format!("Penalize {} {}", min_width, penalty),
+                SliverPolicy::Forbid { min_width } => format!("Forbid {}", min_width),
+            }),
+            ("min_offcut", optional(self.min_offcut.map(|x|x.to_string()))),
+            ("overlap_tolerance", match self.overlap_tolerance {
+                OverlapTolerance::Cells(cells) => format!("Cells {}", cells),
+                OverlapTolerance::Fraction(fraction) => format!("Fraction {}", fraction),
+            }),
+            ("tie_break", format!("{:?}", self.tie_break)),
+            ("bin_selection", format!("{:?}", self.bin_selection)),
+            ("shared_cut_weight", self.shared_cut_weight.to_string()),
+            ("final_search", optional(self.final_search.map(|(max_items, budget)|format!("{} {}", max_items, budget.as_nanos())))),
+            ("utilization_target", optional(self.utilization_target.map(|x|x.to_string()))),
+        ]
+    }
+
+    /// Set the option 'key' from its written 'value'. Returns None if the key is unknown or
+    /// the value invalid.
+    pub(crate) fn set(&mut self, key: &str, value: &str) -> Option<()> {
+        let words: Vec<&str> = value.split(' ').collect();
+        let number = |index: usize| -> Option<usize> { words.get(index)?.parse().ok() };
+        // The value of an optional option, which is "none" if it is not set
+        let given = (value != "none").then_some(value);
+        let corner = |name: &str| -> Option<Corner> {
+            [Corner::TopLeft, Corner::TopRight, Corner::BottomLeft, Corner::BottomRight].into_iter().find(|x|format!("{:?}", x) == name)
+        };
+        match key {
+            "name" => self.name = value.to_string(),
+            "strategies" => {
+                let all = [Strategy::Rotate, Strategy::DoNotRotate, Strategy::RotateIfSuitable];
+                self.strategies = value.split(',').filter(|x|!x.is_empty())
+                    .map(|name|all.into_iter().find(|x|format!("{:?}", x) == name))
+                    .collect::<Option<_>>()?;
+            },
+            "spacing" => self.spacing = value.parse().ok()?,
+            "spacing_policy" => self.spacing_policy = match words[0] {
+                "Uniform" => SpacingPolicy::Uniform,
+                "SharedEdgeAllowed" => SpacingPolicy::SharedEdgeAllowed,
+                "SharedEdgeTagged" => SpacingPolicy::SharedEdgeTagged(Tags(words.get(1)?.parse().ok()?)),
+                _ => return None,
+            },
+            "heuristic" => self.heuristic = match words[0] {
+                "MaxContact" => Heuristic::MaxContact,
+                "FirstFit" => Heuristic::FirstFit,
+                "WeightedContact" => Heuristic::WeightedContact(ContactWeights { free_cell: number(1)?, open_side: number(2)?, no_corner: number(3)? }),
+                _ => return None,
+            },
+            "seed" => self.seed = given.map(|x|x.parse()).transpose().ok()?,
+            "deduplicate" => self.deduplicate = value.parse().ok()?,
+            "scan_order" => self.scan_order = match words[0] {
+                "Rows" => ScanOrder::Rows(corner(words.get(1)?)?),
+                "Columns" => ScanOrder::Columns(corner(words.get(1)?)?),
+                "SpiralFromCenter" => ScanOrder::SpiralFromCenter,
+                _ => return None,
+            },
+            "sliver_policy" => self.sliver_policy = match words[0] {
+                "Allow" => SliverPolicy::Allow,
+                "Penalize" => SliverPolicy::Penalize { min_width: number(1)?, penalty: number(2)? },
+                "Forbid" => SliverPolicy::Forbid { min_width: number(1)? },
+                _ => return None,
+            },
+            "min_offcut" => self.min_offcut = given.map(|x|x.parse()).transpose().ok()?,
+            "overlap_tolerance" => self.overlap_tolerance = match words[0] {
+                "Cells" => OverlapTolerance::Cells(number(1)?),
+                "Fraction" => OverlapTolerance::Fraction(words.get(1)?.parse().ok()?),
+                _ => return None,
+            },
+            "tie_break" => self.tie_break = match value {
+                "ScanOrder" => TieBreak::ScanOrder,
+                "SmallestBoundingBox" => TieBreak::SmallestBoundingBox,
+                _ => return None,
+            },
+            "bin_selection" => self.bin_selection = match value {
+                "FirstFit" => BinSelection::FirstFit,
+                "BestFit" => BinSelection::BestFit,
+                "WorstFit" => BinSelection::WorstFit,
+                _ => return None,
+            },
+            "shared_cut_weight" => self.shared_cut_weight = value.parse().ok()?,
+            "final_search" => self.final_search = match given {
+                None => None,
+                Some(_) => Some((number(0)?, Duration::from_nanos(words.get(1)?.parse().ok()?))),
+            },
+            "utilization_target" => self.utilization_target = given.map(|x|x.parse()).transpose().ok()?,
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+impl fmt::Display for PackConfig {
+    /// One line per option, as `key: value`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in self.fields() {
+            writeln!(f, "{}: {}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_roundtrip() {
+        let config = PackConfig::new("custom").with_options(|x|x
+            .with_strategies([Strategy::Rotate])
+            .with_spacing(2)
+            .with_spacing_policy(SpacingPolicy::SharedEdgeTagged(Tags(6)))
+            .with_heuristic(Heuristic::WeightedContact(ContactWeights { free_cell: 1, open_side: 5, no_corner: 100 }))
+            .with_seed(42)
+            .with_scan_order(ScanOrder::Columns(Corner::BottomRight))
+            .with_sliver_policy(SliverPolicy::Penalize { min_width: 3, penalty: 10 })
+            .with_overlap_tolerance(OverlapTolerance::Fraction(0.1))
+            .with_tie_break(TieBreak::SmallestBoundingBox)
+            .with_shared_cut_weight(2)
+            .with_final_search(4, Duration::from_millis(250))
+            .with_utilization_target(0.85));
+        let mut read = PackConfig::new("");
+        for (key, value) in config.fields() {
+            assert_eq!(read.set(key, &value), Some(()), "{}", key);
+        }
+        assert_eq!(read, config);
+        assert_eq!(config.difference(&read), None);
+        assert_eq!(PackConfig::new("other").difference(&PackConfig::new("default")), None);
+        assert_eq!(PackConfig::new("default").difference(&config), Some("strategies"));
+        assert_eq!(read.set("spacing", "wide"), None);
+        assert_eq!(read.set("unknown", "1"), None);
+    }
+}
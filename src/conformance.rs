@@ -0,0 +1,87 @@
+use crate::{Backend, Bin, Item, PlaceOptions};
+
+/// A packing instance with a known outcome, for checking that backends (see [`Backend`])
+/// behave the same. See [`check_conformance`].
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct ConformanceInstance {
+    /// A name for the instance, used in error messages
+    pub name: String,
+    /// The width of the bin
+    pub width: usize,
+    /// The height of the bin
+    pub height: usize,
+    /// The items to pack, with their index as id
+    pub items: Vec<Item<usize>>,
+    /// Whether all items fit, using the default options
+    pub feasible: bool,
+}
+
+impl ConformanceInstance {
+    /// An instance packing items of the given sizes, where rotation is allowed
+    pub fn new(name: impl Into<String>, width: usize, height: usize, sizes: &[(usize, usize)], feasible: bool) -> ConformanceInstance {
+        let items = sizes.iter().enumerate().map(|(id, &(w, h))|Item::new(w, h, id)).collect();
+        ConformanceInstance { name: name.into(), width, height, items, feasible }
+    }
+}
+
+/// The instances the backends are checked against. Applications can add their own
+/// instances before calling [`check_conformance`].
+pub fn conformance_corpus() -> Vec<ConformanceInstance> {
+    vec![
+        ConformanceInstance::new("single", 10, 10, &[(10, 10)], true),
+        ConformanceInstance::new("quarters", 10, 10, &[(5, 5); 4], true),
+        ConformanceInstance::new("strips", 12, 6, &[(4, 6); 3], true),
+        ConformanceInstance::new("rotated", 10, 4, &[(4, 10)], true),
+        ConformanceInstance::new("tiling", 20, 10, &[(5, 5), (10, 10), (5, 10), (5, 5)], true),
+        ConformanceInstance::new("sparse", 30, 20, &[(7, 3), (2, 9), (6, 6), (1, 1), (4, 2)], true),
+        ConformanceInstance::new("too-large", 5, 5, &[(6, 1)], false),
+        ConformanceInstance::new("too-much-area", 6, 6, &[(3, 3); 5], false),
+    ]
+}
+
+/// Pack each of 'instances' using each of 'backends', with the default options, and check
+/// that every layout lies within the bin, that items don't overlap and have the size of the
+/// corresponding item, and that whether all items fit is as expected. Returns a description
+/// of the first difference found.
+pub fn check_conformance(instances: &[ConformanceInstance], backends: &[Backend]) -> Result<(), String> {
+    for instance in instances {
+        for &backend in backends {
+            let error = |message: String|format!("Instance '{}' with backend {:?}: {}", instance.name, backend, message);
+            let mut bin = Bin::with_backend(instance.width, instance.height, backend);
+            let all_fit = bin.place_all(instance.items.iter().cloned(), PlaceOptions::default());
+            if all_fit != instance.feasible {
+                return Err(error(format!("all items fit: {}, expected {}", all_fit, instance.feasible)));
+            }
+            let solution = bin.solution();
+            for (index, placed) in solution.iter().enumerate() {
+                if placed.x1 > instance.width || placed.y1 > instance.height {
+                    return Err(error(format!("item {} lies outside the bin", placed.id)));
+                }
+                let item = instance.items.get(placed.id).ok_or_else(||error(format!("unknown item {}", placed.id)))?;
+                let size = (placed.width(), placed.height());
+                if size != (item.w, item.h) && size != (item.h, item.w) {
+                    return Err(error(format!("item {} has size {:?}", placed.id, size)));
+                }
+                if let Some(other) = solution[..index].iter().find(|x|x.rect().intersects(&placed.rect())) {
+                    return Err(error(format!("items {} and {} overlap", other.id, placed.id)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conformance() {
+        let backends = [Backend::Bitmap, Backend::Tiled, Backend::FreeRects];
+        assert_eq!(check_conformance(&conformance_corpus(), &backends), Ok(()));
+        let mut corpus = conformance_corpus();
+        corpus.push(ConformanceInstance::new("wrong", 4, 4, &[(2, 2); 5], true));
+        let error = check_conformance(&corpus, &backends).unwrap_err();
+        assert!(error.starts_with("Instance 'wrong' with backend Bitmap"), "{}", error);
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::{Item, PlacedItem};
+
+/// Write the placed items of one or more bins as CSV, with the header
+/// `id,x,y,width,height,rotated,bin_index`. Each element of 'bins' is the solution
+/// of one bin (see `Bin::solution`), and 'bin_index' is its position in 'bins'.
+/// 'rotated' is written as `true` or `false`.
+///
+/// Fields are quoted as described in RFC 4180 when needed, so ids may contain commas
+/// and quotes, but not line breaks.
+pub fn write_csv<'a, I:Clone+Display+'a>(mut writer: impl Write, bins: impl IntoIterator<Item=&'a [PlacedItem<I>]>) -> io::Result<()> {
+    writeln!(writer, "id,x,y,width,height,rotated,bin_index")?;
+    for (bin_index, items) in bins.into_iter().enumerate() {
+        for item in items {
+            writeln!(writer, "{},{},{},{},{},{},{}", quote(&item.id.to_string()), item.x0, item.y0,
+                item.width(), item.height(), item.rotated(), bin_index)?;
+        }
+    }
+    writer.flush()
+}
+
+/// Read items to be packed from CSV. The first line must be a header naming the
+/// columns, which may be in any order:
+/// * `id`, `width` and `height` are required.
+/// * `rotatable` is optional, and may be `true`, `false`, `1`, `0`, or empty to use the
+///   default of the bin.
+/// * `quantity` is optional, and repeats the item (with the same id) that many times.
+///
+/// Other columns are ignored, and column names are not case sensitive.
+pub fn read_items_csv<I:Clone+FromStr>(reader: impl BufRead) -> io::Result<Vec<Item<I>>> {
+    let invalid = |line: usize, what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("invalid CSV on line {}: {}", line + 1, what));
+    let mut lines = reader.lines().enumerate();
+    let header = match lines.next() {
+        Some((_, line)) => split(&line?).ok_or_else(|| invalid(0, "unterminated quote"))?,
+        None => return Ok(vec![]),
+    };
+    let column = |name: &str| header.iter().position(|x|x.trim().eq_ignore_ascii_case(name));
+    let required = |name: &str| column(name).ok_or_else(|| invalid(0, &format!("missing column '{}'", name)));
+    let (id_column, width_column, height_column) = (required("id")?, required("width")?, required("height")?);
+    let (rotatable_column, quantity_column) = (column("rotatable"), column("quantity"));
+    let mut items = vec![];
+    for (line_number, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split(&line).ok_or_else(|| invalid(line_number, "unterminated quote"))?;
+        let field = |column: usize| fields.get(column).map(|x|x.trim()).unwrap_or("");
+        let number = |column: usize, name: &str| -> io::Result<usize> {
+            field(column).parse().map_err(|_| invalid(line_number, &format!("invalid {}", name)))
+        };
+        let id: I = fields.get(id_column).and_then(|x|x.parse().ok()).ok_or_else(|| invalid(line_number, "invalid id"))?;
+        let (w, h) = (number(width_column, "width")?, number(height_column, "height")?);
+        let allow_rotate = match rotatable_column.map(field) {
+            None | Some("") => None,
+            Some(x) if x.eq_ignore_ascii_case("true") || x == "1" => Some(true),
+            Some(x) if x.eq_ignore_ascii_case("false") || x == "0" => Some(false),
+            Some(_) => return Err(invalid(line_number, "invalid rotatable")),
+        };
+        let quantity = match quantity_column {
+            Some(column) if !field(column).is_empty() => number(column, "quantity")?,
+            _ => 1,
+        };
+        for _ in 0..quantity {
+            items.push(Item { allow_rotate, ..Item::new(w, h, id.clone()) });
+        }
+    }
+    Ok(items)
+}
+
+/// Quote a field, if it contains characters with special meaning in CSV
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Split a line of CSV into fields, removing quotes. Returns None if a quote is not terminated.
+fn split(line: &str) -> Option<Vec<String>> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tags;
+    use crate::Rotation;
+
+    #[test]
+    fn test_csv() {
+        let placed = [PlacedItem { x0: 1, y0: 2, x1: 4, y1: 6, rotation: Rotation::Cw90, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: "door, left".to_string() }];
+        let mut buffer = vec![];
+        write_csv(&mut buffer, [&[][..], &placed[..]]).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "id,x,y,width,height,rotated,bin_index\n\"door, left\",1,2,3,4,true,1\n");
+
+        let input = "Height,id,Width,quantity,rotatable\n2,\"a \"\"b\"\"\",3,2,false\n5,c,4,,\n";
+        let items: Vec<Item<String>> = read_items_csv(input.as_bytes()).unwrap();
+        let read: Vec<_> = items.iter().map(|x|(x.id.as_str(), x.w, x.h, x.allow_rotate)).collect();
+        assert_eq!(read, vec![("a \"b\"", 3, 2, Some(false)), ("a \"b\"", 3, 2, Some(false)), ("c", 4, 5, None)]);
+        assert!(read_items_csv::<String>("id,width\na,1\n".as_bytes()).is_err());
+        assert!(read_items_csv::<String>("id,width,height\na,1,x\n".as_bytes()).is_err());
+    }
+}
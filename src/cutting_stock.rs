@@ -0,0 +1,193 @@
+use crate::stepwise::{sort_items, PackIterator};
+use crate::{Bin, Item, PlaceOptions, PlacedItem};
+
+/// An item to be cut, with the number of copies needed. See [`Bin::cutting_plan`].
+#[derive(Debug,Clone)]
+pub struct Demand<I> {
+    /// The item
+    pub item: Item<I>,
+    /// The number of copies needed
+    pub quantity: usize,
+    /// The number of copies which may be cut in addition to 'quantity', to use space on
+    /// the sheets which would otherwise be wasted. Default is 0.
+    pub max_overrun: usize,
+}
+
+impl<I> Demand<I> {
+    /// 'quantity' copies of 'item', without overrun
+    pub fn new(item: Item<I>, quantity: usize) -> Demand<I> {
+        Demand { item, quantity, max_overrun: 0 }
+    }
+
+    /// Allow cutting up to 'max_overrun' more copies than needed
+    pub fn with_max_overrun(mut self, max_overrun: usize) -> Demand<I> {
+        self.max_overrun = max_overrun;
+        self
+    }
+}
+
+impl<I> From<(Item<I>, usize)> for Demand<I> {
+    fn from((item, quantity): (Item<I>, usize)) -> Self {
+        Demand::new(item, quantity)
+    }
+}
+
+/// A layout which is cut from a number of identical sheets, see [`Bin::cutting_plan`].
+#[derive(Debug,Clone)]
+pub struct Pattern<I:Clone> {
+    /// The items cut from each sheet, not including any items which were already in the bin
+    pub solution: Vec<PlacedItem<I>>,
+    /// The number of sheets cut using this pattern
+    pub sheets: usize,
+}
+
+/// The result of [`Bin::cutting_plan`].
+#[derive(Debug,Clone)]
+pub struct CuttingPlan<I:Clone> {
+    /// The patterns, in the order they were chosen
+    pub patterns: Vec<Pattern<I>>,
+    /// The items which could not be placed on an empty sheet, or which were left when
+    /// packing was cancelled, with the quantity still needed
+    pub unplaced: Vec<(Item<I>, usize)>,
+    /// Each demand, with the number of copies produced by the patterns. This is more than
+    /// the quantity demanded if overrun was used.
+    pub production: Vec<(Demand<I>, usize)>,
+}
+
+impl<I:Clone> CuttingPlan<I> {
+    /// The total number of sheets used
+    pub fn sheets(&self) -> usize {
+        self.patterns.iter().map(|x|x.sheets).sum()
+    }
+}
+
+/// A pattern considered in one round of 'cutting_plan'
+struct CandidatePattern<I:Clone> {
+    placed_area: usize,
+    // The number of sheets the pattern can be used on without cutting too many items
+    repetitions: usize,
+    bin: Bin<I>,
+    // The number of items of each kind in the pattern
+    counts: Vec<usize>,
+}
+
+/// The most kinds of items tried at the front of the packing order, when looking for a pattern
+const MAX_LEADING_KINDS: usize = 8;
+
+/// Greedily choose patterns until the demand is met. In each round, candidate patterns are
+/// packed from the remaining demand, the one which uses the most of the sheet is chosen, and
+/// it is repeated as many times as possible without cutting more items than demanded. The
+/// space left in the pattern is then filled with extra copies, within the overrun limits.
+pub(crate) fn cutting_plan<'o, I:Clone>(bin: &Bin<I>, demand: Vec<Demand<I>>, options: &dyn Fn() -> PlaceOptions<'o>) -> CuttingPlan<I> {
+    let area = bin.width().saturating_mul(bin.height());
+    let kinds: Vec<Item<I>> = demand.iter().map(|x|x.item.clone()).collect();
+    let mut produced = vec![0; demand.len()];
+    let compare = bin.item_order.clone().map(|compare|{ let kinds = &kinds; move |a: &Item<usize>, b: &Item<usize>|compare(&kinds[a.id], &kinds[b.id]) });
+    let compare = compare.as_ref().map(|x|x as _);
+    let mut patterns = vec![];
+    loop {
+        let remaining: Vec<usize> = demand.iter().zip(&produced).map(|(x, &produced)|x.quantity.saturating_sub(produced)).collect();
+        if remaining.iter().all(|&x|x == 0) {
+            break;
+        }
+        // One copy of each item which might fit on a sheet, identified by kind
+        let mut base: Vec<Item<usize>> = vec![];
+        for (kind, item) in kinds.iter().enumerate() {
+            let capacity = area / item.w.saturating_mul(item.h).max(1);
+            let copy = item.with_id(kind);
+            base.extend(std::iter::repeat_n(copy, remaining[kind].min(capacity)));
+        }
+        sort_items(&mut base, &mut options(), compare);
+        let mut leading: Vec<usize> = (0..kinds.len()).filter(|&x|remaining[x] > 0).collect();
+        leading.sort_by_key(|&x|std::cmp::Reverse(remaining[x]));
+        leading.truncate(MAX_LEADING_KINDS);
+        let orders = std::iter::once(base.clone()).chain(leading.into_iter().map(|kind| {
+            let (mut order, rest): (Vec<_>, Vec<_>) = base.iter().cloned().partition(|x|x.id == kind);
+            order.extend(rest);
+            order
+        }));
+        let mut best: Option<CandidatePattern<I>> = None;
+        for order in orders {
+            let mut attempt = bin.clone();
+            attempt.autosave = None;
+            let initial_count = attempt.items.len();
+            let mut packer = PackIterator::new(&mut attempt, order.iter().map(|x|kinds[x.id].clone()), options(), false);
+            packer.by_ref().for_each(drop);
+            let placed = packer.placed().to_vec();
+            if attempt.stats().cancelled {
+                return plan(patterns, demand, produced);
+            }
+            let solution = &attempt.items[initial_count..];
+            let mut counts = vec![0; kinds.len()];
+            for (item, _) in order.iter().zip(placed).filter(|(_, placed)|*placed) {
+                counts[item.id] += 1;
+            }
+            let repetitions = counts.iter().zip(&remaining).filter(|(&count, _)|count > 0).map(|(count, remaining)|remaining / count).min().unwrap_or(0);
+            let placed_area = solution.iter().map(|x|x.rect().area()).fold(0, usize::saturating_add);
+            if best.as_ref().is_none_or(|best|(placed_area, repetitions) > (best.placed_area, best.repetitions)) {
+                best = Some(CandidatePattern { placed_area, repetitions, bin: attempt, counts });
+            }
+        }
+        let Some(mut best) = best.filter(|x|x.repetitions > 0) else {
+            break; //None of the remaining items fit on a sheet
+        };
+        let repetitions = best.repetitions;
+        // Extra copies which may be added to each sheet, within the demand and overrun
+        let mut extra: Vec<Item<usize>> = vec![];
+        for (kind, item) in kinds.iter().enumerate() {
+            let limit = demand[kind].quantity.saturating_add(demand[kind].max_overrun);
+            let spare = limit.saturating_sub(produced[kind] + best.counts[kind] * repetitions) / repetitions;
+            let copy = item.with_id(kind);
+            extra.extend(std::iter::repeat_n(copy, spare.min(area / item.w.saturating_mul(item.h).max(1))));
+        }
+        sort_items(&mut extra, &mut options(), compare);
+        let mut packer = PackIterator::new(&mut best.bin, extra.iter().map(|x|kinds[x.id].clone()), options(), false);
+        packer.by_ref().for_each(drop);
+        for (item, _) in extra.iter().zip(packer.placed().to_vec()).filter(|(_, placed)|*placed) {
+            best.counts[item.id] += 1;
+        }
+        for (produced, count) in produced.iter_mut().zip(&best.counts) {
+            *produced += count * repetitions;
+        }
+        let solution = best.bin.items.split_off(bin.items.len());
+        patterns.push(Pattern { solution, sheets: repetitions });
+    }
+    plan(patterns, demand, produced)
+}
+
+fn plan<I:Clone>(patterns: Vec<Pattern<I>>, demand: Vec<Demand<I>>, produced: Vec<usize>) -> CuttingPlan<I> {
+    let unplaced = demand.iter().zip(&produced)
+        .filter(|(x, &produced)|produced < x.quantity)
+        .map(|(x, &produced)|(x.item.clone(), x.quantity - produced))
+        .collect();
+    CuttingPlan { patterns, unplaced, production: demand.into_iter().zip(produced).collect() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cutting_plan() {
+        let square = Item::new(5, 5, 'S').with_allow_rotate(false);
+        let strip = Item::rotatable(10, 5, 'L');
+        let huge = Item::rotatable(20, 5, 'H');
+        let plan = Bin::new(10, 10).cutting_plan([(square, 10), (strip, 3), (huge, 1)], PlaceOptions::default);
+        assert_eq!(plan.sheets(), 4);
+        let cut: Vec<char> = plan.patterns.iter().flat_map(|x|x.solution.iter().flat_map(move |placed|std::iter::repeat_n(placed.id, x.sheets))).collect();
+        assert_eq!(cut.iter().filter(|&&x|x == 'S').count(), 10);
+        assert_eq!(cut.iter().filter(|&&x|x == 'L').count(), 3);
+        assert_eq!(plan.unplaced.len(), 1);
+        assert_eq!(plan.unplaced[0].0.id, 'H');
+
+        // The sheet with the third strip has room for two squares, which are cut as overrun
+        let square = Item::new(5, 5, 'S').with_allow_rotate(false);
+        let strip = Item::new(10, 5, 'L').with_allow_rotate(false);
+        let demand = [Demand::new(strip, 3), Demand::new(square, 0).with_max_overrun(5)];
+        let plan = Bin::new(10, 10).cutting_plan(demand, PlaceOptions::default);
+        assert_eq!(plan.sheets(), 2);
+        let produced: Vec<_> = plan.production.iter().map(|(x, produced)|(x.item.id, *produced)).collect();
+        assert_eq!(produced, vec![('L', 3), ('S', 2)]);
+        assert!(plan.unplaced.is_empty());
+    }
+}
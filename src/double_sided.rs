@@ -0,0 +1,160 @@
+use crate::stepwise::PackIterator;
+use crate::{Bin, Item, PlaceOptions, PlacedItem, Rotation};
+
+/// A side of a [`DoubleSidedBin`]
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Side {
+    /// The side facing up when the sheet is loaded
+    Front,
+    /// The side facing down, which is worked on after flipping the sheet around its vertical axis
+    Back,
+}
+
+/// An item placed on one of the sides of a [`DoubleSidedBin`].
+#[derive(Debug,Clone)]
+pub struct SidedItem<I:Clone> {
+    /// The side the item was placed on
+    pub side: Side,
+    /// Where the item was placed. For the back, the placement is mirrored in x, so that it
+    /// is in the coordinates of the flipped sheet.
+    pub placed: PlacedItem<I>,
+}
+
+/// A sheet with two surfaces which are worked on separately, such as a board which is cut
+/// or engraved from both sides. The sides are packed like two bins of the same size, and
+/// each item can be placed on either side, or be restricted to one of them.
+///
+/// Since the sheet is flipped around its vertical axis to work on the back, items on the
+/// back are reported mirrored in x (see 'solution'). The bins of the sides themselves use
+/// the coordinates of the front, so that for instance a defect can be reserved at the same
+/// position on both sides.
+///
+/// ```
+/// use bin_packing_2d::{DoubleSidedBin, Item, PlaceOptions, Side};
+/// let mut sheet = DoubleSidedBin::new(10, 5);
+/// let items = [(Item::new(10, 5, 'A'), None), (Item::new(4, 5, 'B'), Some(Side::Back))];
+/// assert!(sheet.place_all(items, PlaceOptions::default()).is_empty());
+/// let solution = sheet.solution();
+/// let b = solution.iter().find(|x|x.placed.id == 'B').unwrap();
+/// assert_eq!((b.side, b.placed.x0, b.placed.x1), (Side::Back, 6, 10));
+/// ```
+#[derive(Clone)]
+pub struct DoubleSidedBin<I:Clone> {
+    front: Bin<I>,
+    back: Bin<I>,
+}
+
+impl<I:Clone> DoubleSidedBin<I> {
+    /// An empty sheet of the given size
+    pub fn new(width: usize, height: usize) -> DoubleSidedBin<I> {
+        DoubleSidedBin { front: Bin::new(width, height), back: Bin::new(width, height) }
+    }
+
+    /// The bin of the given side
+    pub fn side(&self, side: Side) -> &Bin<I> {
+        match side {
+            Side::Front => &self.front,
+            Side::Back => &self.back,
+        }
+    }
+
+    /// The bin of the given side, for instance to reserve areas of it before packing
+    pub fn side_mut(&mut self, side: Side) -> &mut Bin<I> {
+        match side {
+            Side::Front => &mut self.front,
+            Side::Back => &mut self.back,
+        }
+    }
+
+    /// Pack items, each with the side it is restricted to, if any. Items restricted to a side
+    /// are packed first, followed by the other items, which are packed on the front if they
+    /// fit, and on the back otherwise. Each step is packed like with [`Bin::place_all`].
+    ///
+    /// Returns the items which did not fit. If packing is cancelled, the remaining steps are
+    /// not packed.
+    pub fn place_all(&mut self, input: impl IntoIterator<Item=(Item<I>, Option<Side>)>, options: PlaceOptions) -> Vec<Item<I>> {
+        let (mut front, mut back, mut either) = (vec![], vec![], vec![]);
+        for (item, side) in input {
+            match side {
+                Some(Side::Front) => front.push(item),
+                Some(Side::Back) => back.push(item),
+                None => either.push(item),
+            }
+        }
+        let mut options = options;
+        let mut left = vec![];
+        for (side, items) in [(Side::Front, front), (Side::Back, back)] {
+            let (returned_options, unplaced) = pack(self.side_mut(side), items, options);
+            options = returned_options;
+            left.extend(unplaced);
+        }
+        for side in [Side::Front, Side::Back] {
+            let (returned_options, unplaced) = pack(self.side_mut(side), either, options);
+            options = returned_options;
+            either = unplaced;
+        }
+        left.extend(either);
+        left
+    }
+
+    /// All placed items, with the side they were placed on. Items on the back are mirrored
+    /// in x: the left edge of an item at 'x0' on the back is at `width - x1` on the flipped
+    /// sheet, its left and right margins are swapped, and quarter turns are reversed.
+    pub fn solution(&self) -> Vec<SidedItem<I>> {
+        let width = self.front.width();
+        let front = self.front.solution().iter().map(|placed|SidedItem { side: Side::Front, placed: placed.clone() });
+        let back = self.back.solution().iter().map(|placed| {
+            let [left, right, top, bottom] = placed.margins;
+            let rotation = match placed.rotation {
+                Rotation::Cw90 => Rotation::Cw270,
+                Rotation::Cw270 => Rotation::Cw90,
+                rotation => rotation,
+            };
+            let placed = PlacedItem { x0: width - placed.x1, x1: width - placed.x0, margins: [right, left, top, bottom], rotation, ..placed.clone() };
+            SidedItem { side: Side::Back, placed }
+        });
+        front.chain(back).collect()
+    }
+}
+
+/// Pack 'items' into 'bin', returning the options and the items which did not fit
+fn pack<'o, I:Clone>(bin: &mut Bin<I>, items: Vec<Item<I>>, mut options: PlaceOptions<'o>) -> (PlaceOptions<'o>, Vec<Item<I>>) {
+    if items.is_empty() || options.is_cancelled() {
+        return (options, items);
+    }
+    let count = items.len();
+    let mut packer = PackIterator::new(bin, items, options, true);
+    packer.by_ref().for_each(drop);
+    // Whether each item was placed, in the pass whose layout was kept
+    let mut placed = packer.placed().to_vec();
+    placed.resize(count, false);
+    let (options, items) = packer.into_parts();
+    (options, items.into_iter().zip(placed).filter(|(_, placed)|!placed).map(|(item, _)|item).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_sided() {
+        let mut sheet = DoubleSidedBin::new(10, 4);
+        sheet.side_mut(Side::Front).set_default_allow_rotate(false);
+        let items = [
+            (Item::new(6, 4, 'A'), Some(Side::Front)),
+            (Item { margins: [1, 0, 0, 0], ..Item::new(3, 4, 'B') }, Some(Side::Back)),
+            (Item::new(4, 4, 'C'), None),
+            (Item::new(5, 4, 'D'), None),
+            (Item::new(12, 1, 'E'), None),
+        ];
+        let left = sheet.place_all(items, PlaceOptions::default());
+        assert_eq!(left.iter().map(|x|x.id).collect::<Vec<_>>(), vec!['E']);
+        let solution = sheet.solution();
+        let find = |id: char|solution.iter().find(|x|x.placed.id == id).unwrap();
+        assert_eq!((find('A').side, find('C').side, find('D').side), (Side::Front, Side::Front, Side::Back));
+        // 'B' is at the left edge of the back, which is the right edge of the flipped sheet
+        let b = &find('B').placed;
+        assert_eq!(sheet.side(Side::Back).placement_of(&'B').map(|x|x.x0), Some(0));
+        assert_eq!((b.x0, b.x1, b.margins), (7, 10, [0, 1, 0, 0]));
+    }
+}
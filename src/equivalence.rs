@@ -0,0 +1,70 @@
+use crate::PlacedItem;
+
+/// True if the two solutions are the same layout, up to translation, which of several
+/// identically sized items is where, and differences in position of at most 'tolerance'
+/// cells in each direction. Ids are not compared, so items of the same placed width and
+/// height are interchangeable.
+///
+/// This is meant for comparing solutions to expected ones in tests, where the exact
+/// solution may change because of harmless changes in how ties are broken.
+pub fn solutions_equivalent<I:Clone>(a: &[PlacedItem<I>], b: &[PlacedItem<I>], tolerance: usize) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let (a, b) = (canonical(a), canonical(b));
+    if tolerance == 0 {
+        return a == b;
+    }
+    // Match the items of 'a' to those of 'b' using augmenting paths (Kuhn's algorithm),
+    // since matching greedily may fail even if a complete matching exists.
+    let close = |i: usize, j: usize| {
+        let (x, y) = (a[i], b[j]);
+        x[2] - x[0] == y[2] - y[0] && x[3] - x[1] == y[3] - y[1] &&
+            x[0].abs_diff(y[0]) <= tolerance && x[1].abs_diff(y[1]) <= tolerance
+    };
+    let mut matched: Vec<Option<usize>> = vec![None; b.len()];
+    fn augment(i: usize, close: &dyn Fn(usize, usize) -> bool, visited: &mut [bool], matched: &mut [Option<usize>]) -> bool {
+        for j in 0..matched.len() {
+            if visited[j] || !close(i, j) {
+                continue;
+            }
+            visited[j] = true;
+            if matched[j].is_none_or(|other|augment(other, close, visited, matched)) {
+                matched[j] = Some(i);
+                return true;
+            }
+        }
+        false
+    }
+    (0..a.len()).all(|i| augment(i, &close, &mut vec![false; b.len()], &mut matched))
+}
+
+/// The rectangles `[x0, y0, x1, y1]` of the items, translated so that the layout starts
+/// at 0, 0, in sorted order.
+fn canonical<I:Clone>(items: &[PlacedItem<I>]) -> Vec<[usize; 4]> {
+    let min_x = items.iter().map(|x|x.x0).min().unwrap_or(0);
+    let min_y = items.iter().map(|x|x.y0).min().unwrap_or(0);
+    let mut rects: Vec<_> = items.iter().map(|x|[x.x0 - min_x, x.y0 - min_y, x.x1 - min_x, x.y1 - min_y]).collect();
+    rects.sort_unstable_by_key(|x|(x[1], x[0], x[3], x[2]));
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rotation, Tags};
+
+    #[test]
+    fn test_solutions_equivalent() {
+        let item = |x0: usize, y0: usize, w: usize, h: usize, id: char| PlacedItem { x0, y0, x1: x0 + w, y1: y0 + h, rotation: Rotation::None, overlap: 0, margins: [0; 4], tags: Tags::NONE, id };
+        let a = [item(0, 0, 2, 2, 'A'), item(2, 0, 2, 2, 'B'), item(0, 2, 4, 1, 'C')];
+        let swapped = [item(5, 5, 2, 2, 'B'), item(7, 5, 2, 2, 'A'), item(5, 7, 4, 1, 'C')];
+        assert!(solutions_equivalent(&a, &swapped, 0));
+        let shifted = [item(0, 0, 2, 2, 'A'), item(3, 0, 2, 2, 'B'), item(0, 2, 4, 1, 'C')];
+        assert!(!solutions_equivalent(&a, &shifted, 0));
+        assert!(solutions_equivalent(&a, &shifted, 1));
+        let resized = [item(0, 0, 2, 2, 'A'), item(2, 0, 2, 2, 'B'), item(0, 2, 1, 4, 'C')];
+        assert!(!solutions_equivalent(&a, &resized, 5));
+        assert!(!solutions_equivalent(&a, &a[1..], 5));
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt::{Display, Write};
+
+use crate::{Bin, Item, PlaceOptions};
+
+/// The canonical fingerprint of 'bin', see [`Bin::fingerprint`]
+pub(crate) fn fingerprint<I:Clone+Display>(bin: &Bin<I>) -> String {
+    let stats = bin.stats();
+    let mut text = String::new();
+    // Writing to a String can not fail
+    let _ = writeln!(text, "bin {}x{}", bin.width(), bin.height());
+    let _ = writeln!(text, "placed {}", bin.solution().len());
+    let _ = writeln!(text, "rejected {}", bin.rejections().len());
+    let _ = writeln!(text, "passes {}", stats.passes);
+    let _ = writeln!(text, "utilization {:.4}", bin.utilization());
+    let _ = writeln!(text, "grid {:016x}", grid_hash(bin));
+    let mut items: Vec<_> = bin.solution().iter()
+        .map(|x|(x.y0, x.x0, x.y1, x.x1, if x.rotated() { 'r' } else { '-' }, x.id.to_string()))
+        .collect();
+    items.sort();
+    for (y0, x0, y1, x1, rotated, id) in items {
+        let _ = writeln!(text, "item {} {} {} {} {} {}", x0, y0, x1, y1, rotated, id);
+    }
+    text
+}
+
+/// FNV-1a hash of the run-length encoded occupancy. Unlike `std::hash`, this is stable
+/// between platforms and releases, so it can be committed.
+fn grid_hash<I:Clone>(bin: &Bin<I>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut add = |value: u64| {
+        for byte in value.to_le_bytes() {
+            hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    };
+    for row in bin.occupancy_rle() {
+        for run in row {
+            add(run.x0 as u64);
+            add(run.x1 as u64);
+            add(run.occupied as u64);
+        }
+        add(u64::MAX); //End of row
+    }
+    hash
+}
+
+/// Pack 'items' into an empty bin of the given size with 'options', and return the
+/// fingerprint of the result (see [`Bin::fingerprint`]). This is meant for generating
+/// fixtures for regression tests, which are then checked using [`compare_fingerprints`].
+pub fn fingerprint_instance<I:Clone+Display>(width: usize, height: usize, items: impl Iterator<Item=Item<I>>, options: PlaceOptions) -> String {
+    let mut bin = Bin::new(width, height);
+    bin.place_all(items, options);
+    bin.fingerprint()
+}
+
+/// Compare a fingerprint to the expected one, for instance one committed as a fixture.
+/// Line endings and trailing whitespace are ignored. If they differ, the error lists the
+/// lines which were removed, prefixed by '-', and added, prefixed by '+', in the order
+/// of the fingerprints.
+pub fn compare_fingerprints(expected: &str, actual: &str) -> Result<(), String> {
+    let expected: Vec<&str> = expected.lines().map(str::trim_end).filter(|x|!x.is_empty()).collect();
+    let actual: Vec<&str> = actual.lines().map(str::trim_end).filter(|x|!x.is_empty()).collect();
+    if expected == actual {
+        return Ok(());
+    }
+    // Longest common subsequence of the lines, so that a moved item only shows up as the
+    // lines which changed
+    let (n, m) = (expected.len(), actual.len());
+    let mut common = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && expected[i] == actual[j] {
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && common[i + 1][j] >= common[i][j + 1]) {
+            let _ = writeln!(diff, "-{}", expected[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(diff, "+{}", actual[j]);
+            j += 1;
+        }
+    }
+    Err(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        let items = || [Item::new(5, 4, 'A'), Item::new(5, 4, 'B'), Item::new(11, 1, 'C')].into_iter();
+        let fingerprint = fingerprint_instance(10, 10, items(), PlaceOptions::default());
+        assert!(fingerprint.starts_with("bin 10x10\nplaced 2\nrejected 1\n"));
+        assert_eq!(fingerprint.lines().filter(|x|x.starts_with("item ")).count(), 2);
+        // The same instance gives the same fingerprint, which compares equal to itself
+        assert_eq!(compare_fingerprints(&fingerprint, &fingerprint_instance(10, 10, items(), PlaceOptions::default())), Ok(()));
+        assert_eq!(compare_fingerprints(&fingerprint.replace('\n', "\r\n"), &fingerprint), Ok(()));
+
+        let changed = fingerprint.replace("placed 2", "placed 3");
+        assert_eq!(compare_fingerprints(&fingerprint, &changed), Err("-placed 2\n+placed 3\n".to_string()));
+        let mut bin = Bin::new(10, 10);
+        bin.place_all([Item::new(5, 4, 'A')].into_iter(), PlaceOptions::default());
+        let diff = compare_fingerprints(&fingerprint, &bin.fingerprint()).unwrap_err();
+        assert!(diff.lines().any(|x|x == "-placed 2") && diff.lines().any(|x|x == "+placed 1"));
+        assert!(diff.lines().any(|x|x.starts_with("-item ") && x.ends_with(" B")));
+        assert!(!diff.lines().any(|x|x.ends_with(" A")));
+    }
+}
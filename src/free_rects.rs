@@ -0,0 +1,84 @@
+use crate::bitmap::Bitmap2d;
+use crate::Rectangle;
+
+/// Find all maximal free rectangles, i.e. all rectangles of free cells which cannot
+/// be extended in any direction without covering an occupied cell or leaving the bitmap.
+///
+/// Works row by row, keeping track of the number of free cells above each cell
+/// (a histogram), and using a stack to find every rectangle bounded by lower
+/// histogram bars on both sides.
+pub(crate) fn maximal_free_rects(bitmap: &Bitmap2d) -> Vec<Rectangle> {
+    if let Some(free) = bitmap.free_list() {
+        return free.to_vec();
+    }
+    let width = bitmap.width;
+    let height = bitmap.height;
+    let mut result = vec![];
+    let mut heights = vec![0usize; width];
+    let mut stack: Vec<(usize, usize)> = vec![];
+    for y in 0..height {
+        for (x, h) in heights.iter_mut().enumerate() {
+            *h = if bitmap.get(x, y) { 0 } else { *h + 1 };
+        }
+        stack.clear();
+        for x in 0..=width {
+            let cur = heights.get(x).copied().unwrap_or(0);
+            let mut start = x;
+            while let Some(&(s, h)) = stack.last() {
+                if h < cur {
+                    break;
+                }
+                stack.pop();
+                start = s;
+                if h > cur {
+                    let blocked_below = y + 1 == height || (s..x).any(|cx| bitmap.get(cx, y + 1));
+                    if blocked_below {
+                        result.push(Rectangle {
+                            x0: s,
+                            y0: y + 1 - h,
+                            x1: x,
+                            y1: y + 1,
+                        });
+                    }
+                }
+            }
+            if cur > 0 {
+                stack.push((start, cur));
+            }
+        }
+    }
+    result
+}
+
+/// Count the rectangles by area. `buckets` are the ascending lower area bounds
+/// of all but the first bucket, so the result has `buckets.len() + 1` entries.
+pub(crate) fn histogram(rects: &[Rectangle], buckets: &[usize]) -> Vec<usize> {
+    let mut counts = vec![0; buckets.len() + 1];
+    for rect in rects {
+        let bucket = buckets.iter().take_while(|limit| rect.area() >= **limit).count();
+        counts[bucket] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maximal_free_rects() {
+        let mut bitmap = Bitmap2d::with_backend(10, 10, crate::Backend::Bitmap);
+        for y in 0..5 {
+            for x in 0..5 {
+                bitmap.set(x, y, true);
+            }
+        }
+        let mut rects = maximal_free_rects(&bitmap);
+        rects.sort_by_key(|r| (r.y0, r.x0));
+        assert_eq!(rects, vec![
+            Rectangle { x0: 5, y0: 0, x1: 10, y1: 10 },
+            Rectangle { x0: 0, y0: 5, x1: 10, y1: 10 },
+        ]);
+        assert_eq!(histogram(&rects, &[10, 60]), vec![0, 2, 0]);
+    }
+}
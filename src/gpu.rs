@@ -0,0 +1,235 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::sync::mpsc;
+
+use wgpu::util::DeviceExt;
+
+/// Evaluates every position of an item in a summed area table of the occupancy, and finds
+/// the best one in two phases: the lowest score, and then the first position with that score.
+const SHADER: &str = r#"
+struct Params {
+    width: u32,
+    height: u32,
+    w0: u32,
+    h0: u32,
+    w1: u32,
+    h1: u32,
+    orientations: u32,
+    phase: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> sat: array<u32>;
+@group(0) @binding(2) var<storage, read_write> best: array<atomic<u32>, 2>;
+
+fn occupied(x0: u32, y0: u32, x1: u32, y1: u32) -> u32 {
+    let stride = params.width + 1u;
+    return sat[y1 * stride + x1] - sat[y0 * stride + x1] - sat[y1 * stride + x0] + sat[y0 * stride + x0];
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let x = id.x;
+    let y = id.y;
+    let o = id.z;
+    if (o >= params.orientations) {
+        return;
+    }
+    var w = params.w0;
+    var h = params.h0;
+    if (o == 1u) {
+        w = params.w1;
+        h = params.h1;
+    }
+    if (x + w > params.width || y + h > params.height || occupied(x, y, x + w, y + h) != 0u) {
+        return;
+    }
+    var free = 0u;
+    if (x > 0u) {
+        free += h - occupied(x - 1u, y, x, y + h);
+    }
+    if (x + w < params.width) {
+        free += h - occupied(x + w, y, x + w + 1u, y + h);
+    }
+    if (y > 0u) {
+        free += w - occupied(x, y - 1u, x + w, y);
+    }
+    if (y + h < params.height) {
+        free += w - occupied(x, y + h, x + w, y + h + 1u);
+    }
+    if (params.phase == 0u) {
+        atomicMin(&best[0], free);
+    } else if (free == atomicLoad(&best[0])) {
+        atomicMin(&best[1], (y * params.width + x) * 2u + o);
+    }
+}
+"#;
+
+/// Why the GPU could not be used.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum GpuError {
+    /// No suitable GPU adapter was found
+    NoAdapter,
+    /// The GPU adapter could not be opened, or failed while running
+    Device(String),
+}
+
+impl Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::NoAdapter => write!(f, "no GPU adapter found"),
+            GpuError::Device(message) => write!(f, "GPU error: {}", message),
+        }
+    }
+}
+
+impl Error for GpuError {}
+
+/// Finds the best position for an item on the GPU, by evaluating all positions in parallel.
+/// This is an experimental alternative to scanning candidate positions on the CPU, which can
+/// be much faster for large bins. Use it with `PlaceOptions::with_gpu`.
+///
+/// Creating an evaluator is slow, so it should be reused for many packings.
+pub struct GpuEvaluator {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl fmt::Debug for GpuEvaluator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuEvaluator").finish_non_exhaustive()
+    }
+}
+
+impl GpuEvaluator {
+    /// Open the default GPU adapter, and compile the shader
+    pub fn new() -> Result<GpuEvaluator, GpuError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .map_err(|_| GpuError::NoAdapter)?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+            .map_err(|err| GpuError::Device(err.to_string()))?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("bin_packing_2d evaluate"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("bin_packing_2d evaluate"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        Ok(GpuEvaluator { device, queue, pipeline })
+    }
+
+    /// The best position for an item of each of the sizes in 'orientations' (at most two),
+    /// in a bin of the given size, where 'occupied' returns whether a cell is occupied.
+    /// The position with the fewest free neighboring cells wins, and ties are broken by the
+    /// row, column, and index in 'orientations', in that order.
+    ///
+    /// Returns the position and the index of the orientation, or None if the item fits nowhere.
+    pub(crate) fn best_position(&self, width: usize, height: usize, occupied: impl Fn(usize, usize) -> bool, orientations: &[(usize, usize)])
+        -> Result<Option<(usize, usize, usize)>, GpuError> {
+        let sat = summed_area_table(width, height, occupied);
+        let (w0, h0) = orientations[0];
+        let (w1, h1) = orientations.get(1).copied().unwrap_or((w0, h0));
+        let params = |phase: u32| -> Vec<u8> {
+            [width, height, w0, h0, w1, h1, orientations.len(), phase as usize].iter()
+                .flat_map(|&x|(x as u32).to_le_bytes()).collect()
+        };
+        let storage = |contents: &[u8], usage: wgpu::BufferUsages| self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents,
+            usage,
+        });
+        let sat_buffer = storage(&sat.iter().flat_map(|x|x.to_le_bytes()).collect::<Vec<u8>>(), wgpu::BufferUsages::STORAGE);
+        let best_buffer = storage(&[u8::MAX; 8], wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC);
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 8,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        for phase in 0..2 {
+            let params_buffer = storage(&params(phase), wgpu::BufferUsages::UNIFORM);
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &self.pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: sat_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: best_buffer.as_entire_binding() },
+                ],
+            });
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(width.div_ceil(8) as u32, height.div_ceil(8) as u32, orientations.len() as u32);
+        }
+        encoder.copy_buffer_to_buffer(&best_buffer, 0, &readback, 0, 8);
+        self.queue.submit([encoder.finish()]);
+        let slice = readback.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| { let _ = sender.send(result); });
+        self.device.poll(wgpu::PollType::Wait).map_err(|err| GpuError::Device(format!("{:?}", err)))?;
+        receiver.recv().map_err(|err| GpuError::Device(err.to_string()))?
+            .map_err(|err| GpuError::Device(err.to_string()))?;
+        let data = slice.get_mapped_range();
+        let index = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        if index == u32::MAX {
+            return Ok(None);
+        }
+        let (position, orientation) = (index as usize / 2, index as usize % 2);
+        Ok(Some((position % width, position / width, orientation)))
+    }
+}
+
+/// True if the GPU can handle a bin of the given size, i.e. the number of occupied cells
+/// and the position indices fit in 32 bits.
+pub(crate) fn supports(width: usize, height: usize) -> bool {
+    (width + 1).checked_mul(height + 1).is_some_and(|cells|cells.checked_mul(2).is_some_and(|x|x < u32::MAX as usize))
+}
+
+/// Element `y * (width + 1) + x` is the number of occupied cells above and to the left of `x`, `y`
+fn summed_area_table(width: usize, height: usize, occupied: impl Fn(usize, usize) -> bool) -> Vec<u32> {
+    let stride = width + 1;
+    let mut sat = vec![0u32; stride * (height + 1)];
+    for y in 0..height {
+        let mut row = 0;
+        for x in 0..width {
+            row += occupied(x, y) as u32;
+            sat[(y + 1) * stride + x + 1] = sat[y * stride + x + 1] + row;
+        }
+    }
+    sat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summed_area_table() {
+        let sat = summed_area_table(3, 2, |x, y|x == 1 || y == 1);
+        assert_eq!(sat, vec![0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 3, 4]);
+        assert!(supports(1000, 1000));
+        assert!(!supports(100_000, 100_000));
+    }
+
+    #[test]
+    fn test_gpu_packing() {
+        // Only runs where a GPU (or software adapter) is available
+        let Ok(gpu) = GpuEvaluator::new() else {
+            return;
+        };
+        let items = (0..8).map(|id| crate::Item::rotatable(5, 10 - id, id));
+        let mut bin = crate::Bin::new(20, 20);
+        assert!(bin.place_all(items, crate::PlaceOptions::default().with_gpu(&gpu)));
+        let solution = bin.solution();
+        assert_eq!(crate::Bin::from_solution(20, 20, solution.iter().cloned()).err(), None);
+    }
+}
@@ -0,0 +1,282 @@
+use std::time::Instant;
+
+use crate::stepwise::sort_items;
+use crate::{Bin, Item, PackingStats, PlaceOptions, Strategy};
+
+/// Decides how a [`GrowableBin`] grows.
+pub trait GrowthPolicy {
+    /// The next size of a bin of size 'width' x 'height', in which an item did not fit.
+    /// Must not be smaller than the current size in either dimension.
+    /// Return None to stop growing.
+    fn grow(&mut self, width: usize, height: usize) -> Option<(usize, usize)>;
+}
+
+/// The dimension in which a [`DirectionalGrowth`] grows.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum GrowDirection {
+    /// Grow the height, adding rows at the bottom
+    Down,
+    /// Grow the width, adding columns to the right
+    Right,
+}
+
+/// How much a [`DirectionalGrowth`] grows at a time.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Increment {
+    /// Double the size
+    Double,
+    /// Add the given number of rows or columns. Must be > 0.
+    Step(usize),
+}
+
+/// Grow in a single dimension, for instance for strip packing. Growing stops when the
+/// size would overflow.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct DirectionalGrowth {
+    /// The dimension which grows
+    pub direction: GrowDirection,
+    /// How much to grow at a time
+    pub increment: Increment,
+}
+
+impl GrowthPolicy for DirectionalGrowth {
+    fn grow(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        let grow = |extent: usize| match self.increment {
+            Increment::Double => extent.checked_mul(2),
+            Increment::Step(step) => extent.checked_add(step),
+        };
+        match self.direction {
+            GrowDirection::Down => Some((width, grow(height)?)),
+            GrowDirection::Right => Some((grow(width)?, height)),
+        }
+    }
+}
+
+/// Grow both dimensions alternately, keeping a fixed aspect ratio, as is common for
+/// texture atlases.
+///
+/// If the bin has the aspect ratio, the width is doubled. Otherwise, the dimension which is
+/// too small is grown to restore the aspect ratio. A square bin thus grows like 4x4, 8x4, 8x8, 16x8 ...
+/// Growing stops when the size would overflow.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct AspectGrowth {
+    ratio_width: usize,
+    ratio_height: usize,
+    power_of_two: bool,
+}
+
+impl AspectGrowth {
+    /// Keep the aspect ratio 'ratio_width' : 'ratio_height', such as 2 : 1.
+    /// Panics if either is 0.
+    pub fn new(ratio_width: usize, ratio_height: usize) -> AspectGrowth {
+        if ratio_width == 0 || ratio_height == 0 {
+            panic!("Aspect ratio must not be 0 in any dimension");
+        }
+        AspectGrowth {
+            ratio_width,
+            ratio_height,
+            power_of_two: false,
+        }
+    }
+
+    /// Keep the bin square
+    pub fn square() -> AspectGrowth {
+        AspectGrowth::new(1, 1)
+    }
+
+    /// If true, both dimensions are rounded up to a power of two. Default is false.
+    pub fn with_power_of_two(mut self, power_of_two: bool) -> AspectGrowth {
+        self.power_of_two = power_of_two;
+        self
+    }
+}
+
+impl GrowthPolicy for AspectGrowth {
+    fn grow(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        // Computed in u128, so that the products can't overflow
+        let (w, h, rw, rh) = (width as u128, height as u128, self.ratio_width as u128, self.ratio_height as u128);
+        let (width, height) = match (w * rh).cmp(&(h * rw)) {
+            std::cmp::Ordering::Equal => (w * 2, h),
+            std::cmp::Ordering::Greater => (w, (w * rh).div_ceil(rw)),
+            std::cmp::Ordering::Less => ((h * rw).div_ceil(rh), h),
+        };
+        let (mut width, mut height) = (usize::try_from(width).ok()?, usize::try_from(height).ok()?);
+        if self.power_of_two {
+            width = width.checked_next_power_of_two()?;
+            height = height.checked_next_power_of_two()?;
+        }
+        Some((width, height))
+    }
+}
+
+/// A bin which grows when an item doesn't fit, as decided by a [`GrowthPolicy`].
+///
+/// This can be used for strip packing (a bin of fixed width and unlimited height),
+/// or for texture atlases which are enlarged as needed.
+#[derive(Clone)]
+pub struct GrowableBin<I:Clone, P:GrowthPolicy = DirectionalGrowth> {
+    bin: Bin<I>,
+    policy: P,
+    max_size: Option<(usize, usize)>,
+}
+
+impl<I:Clone, P:GrowthPolicy> GrowableBin<I, P> {
+    /// Create a bin of the initial size 'width' x 'height', growing according to 'policy'.
+    pub fn new(width: usize, height: usize, policy: P) -> GrowableBin<I, P> {
+        GrowableBin {
+            bin: Bin::new(width, height),
+            policy,
+            max_size: None,
+        }
+    }
+
+    /// Never grow the bin beyond 'max_width' x 'max_height'. Sizes proposed by the
+    /// growth policy are clamped to this. Default is no limit.
+    pub fn set_max_size(&mut self, max_size: Option<(usize, usize)>) {
+        self.max_size = max_size;
+    }
+
+    /// The underlying bin, at its current size
+    pub fn bin(&self) -> &Bin<I> {
+        &self.bin
+    }
+
+    /// The underlying bin, mutably. This can be used to change settings of the bin.
+    pub fn bin_mut(&mut self) -> &mut Bin<I> {
+        &mut self.bin
+    }
+
+    /// Consume this, returning the underlying bin
+    pub fn into_bin(self) -> Bin<I> {
+        self.bin
+    }
+
+    /// Place the items, growing the bin whenever an item doesn't fit.
+    ///
+    /// Items are placed largest first, in a single pass. Each item is tried with the
+    /// strategies of 'options' in order, and the bin is grown only if none of them fits.
+    /// An item which can't be placed even after the bin has grown enough to leave room for it
+    /// (or after reaching the maximum size) is skipped, and the bin is shrunk back to the
+    /// size it had before.
+    ///
+    /// Returns true if all items were placed, apart from optional ones. The bin is not grown
+    /// for optional items.
+    pub fn place_all(&mut self, input: impl Iterator<Item=Item<I>>, options: PlaceOptions) -> bool {
+        let start = Instant::now();
+        let mut options = options;
+        let mut items: Vec<Item<I>> = input.collect();
+        sort_items(&mut items, &mut options, self.bin.item_order.as_deref().map(|x|x as _));
+        let strategies = options.strategies().to_vec();
+        self.bin.stats = PackingStats::default();
+        self.bin.stats.passes = 1;
+        self.bin.rejections.clear();
+        let mut all_fit = true;
+        for item in &items {
+            if options.is_cancelled() {
+                all_fit = false;
+                break;
+            }
+            // The bin is not grown for optional items, which are only placed if space allows
+            let placed = if item.optional { self.try_place(item, &strategies, &mut options) } else { self.place_growing(item, &strategies, &mut options) };
+            if placed {
+                self.bin.stats.items_placed += 1;
+            } else {
+                self.bin.reject(item);
+                all_fit &= item.optional;
+            }
+        }
+        self.bin.stats.cancelled = options.was_cancelled();
+        self.bin.stats.zone_utilization = self.bin.zone_utilization();
+        self.bin.stats.weighted_utilization = self.bin.weighted_utilization();
+        self.bin.stats.kerf_area = self.bin.kerf_area(options.spacing());
+        self.bin.stats.shared_cut_length = self.bin.shared_cut_length(options.spacing());
+        self.bin.stats.duration = start.elapsed();
+        all_fit
+    }
+
+    fn place_growing(&mut self, item: &Item<I>, strategies: &[Strategy], options: &mut PlaceOptions) -> bool {
+        if self.try_place(item, strategies, options) {
+            return true;
+        }
+        let (width, height) = (self.bin.width(), self.bin.height());
+        let original_bitmap = self.bin.bitmap.clone();
+        let original_holes = (self.bin.largest_hole, self.bin.named_metrics.clone());
+        let mut orientations = vec![(item.w, item.h)];
+        if self.bin.allows_rotation(item) {
+            orientations.push((item.h, item.w));
+        }
+        let spacing = options.spacing().max(item.margins.iter().copied().max().unwrap_or(0));
+        // True once an empty strip large enough for the item has been added, after which
+        // growing further is pointless.
+        let room_added = |new_width: usize, new_height: usize| orientations.iter().any(|&(w, h)| {
+            (new_height >= height.saturating_add(h).saturating_add(spacing) && new_width >= w) ||
+                (new_width >= width.saturating_add(w).saturating_add(spacing) && new_height >= h)
+        });
+        let (mut new_width, mut new_height) = (width, height);
+        while !room_added(new_width, new_height) && !options.is_cancelled() {
+            let Some((mut next_width, mut next_height)) = self.policy.grow(new_width, new_height) else {
+                break;
+            };
+            if let Some((max_width, max_height)) = self.max_size {
+                next_width = next_width.min(max_width);
+                next_height = next_height.min(max_height);
+            }
+            if next_width < new_width || next_height < new_height || (next_width, next_height) == (new_width, new_height) {
+                break;
+            }
+            (new_width, new_height) = (next_width, next_height);
+            self.bin.grow(new_width, new_height);
+            if self.try_place(item, strategies, options) {
+                return true;
+            }
+        }
+        self.bin.bitmap = original_bitmap;
+        (self.bin.largest_hole, self.bin.named_metrics) = original_holes;
+        false
+    }
+
+    fn try_place(&mut self, item: &Item<I>, strategies: &[Strategy], options: &mut PlaceOptions) -> bool {
+        strategies.iter().any(|&strategy| self.bin.add_to_best_fit(item, strategy, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_growable_bin() {
+        let mut bin = GrowableBin::new(10, 4, DirectionalGrowth { direction: GrowDirection::Down, increment: Increment::Step(3) });
+        let items = (0..3).map(|id| Item::new(10, 4, id).with_allow_rotate(false));
+        assert!(bin.place_all(items, PlaceOptions::default()));
+        assert_eq!((bin.bin().width(), bin.bin().height()), (10, 13));
+        assert_eq!(bin.bin().solution().len(), 3);
+
+        let mut atlas = GrowableBin::new(4, 4, DirectionalGrowth { direction: GrowDirection::Right, increment: Increment::Double });
+        atlas.set_max_size(Some((8, 8)));
+        let items = [Item::new(6, 4, 0).with_allow_rotate(false), Item::new(3, 4, 1).with_allow_rotate(false)];
+        assert!(!atlas.place_all(items.into_iter(), PlaceOptions::default()));
+        assert_eq!(atlas.bin().width(), 8);
+        assert_eq!(atlas.bin().stats().items_rejected, 1);
+    }
+
+    #[test]
+    fn test_aspect_growth() {
+        let mut square = AspectGrowth::square();
+        assert_eq!(square.grow(4, 4), Some((8, 4)));
+        assert_eq!(square.grow(8, 4), Some((8, 8)));
+        let mut wide = AspectGrowth::new(2, 1).with_power_of_two(true);
+        assert_eq!(wide.grow(5, 3), Some((8, 4)));
+        assert_eq!(wide.grow(8, 4), Some((16, 4)));
+        assert_eq!(wide.grow(16, 4), Some((16, 8)));
+        // Growing stops instead of overflowing
+        assert_eq!(square.grow(usize::MAX / 2 + 1, usize::MAX / 2 + 1), None);
+        assert_eq!(square.grow(usize::MAX, usize::MAX), None);
+        assert_eq!(DirectionalGrowth { direction: GrowDirection::Right, increment: Increment::Step(2) }.grow(usize::MAX - 1, 1), None);
+
+        let mut atlas = GrowableBin::new(4, 4, AspectGrowth::square());
+        let items = (0..4).map(|id| Item::new(4, 4, id));
+        assert!(atlas.place_all(items, PlaceOptions::default()));
+        assert_eq!((atlas.bin().width(), atlas.bin().height()), (8, 8));
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{Item, PlacedItem, Rotation};
+
+/// An item placed by [`justified_rows`], together with the factor by
+/// which it was scaled.
+#[derive(Debug,Clone)]
+pub struct ScaledItem<I:Clone> {
+    /// Where the scaled item was placed. Items are never rotated.
+    pub placed: PlacedItem<I>,
+    /// The factor by which the item was scaled, in both dimensions.
+    /// The placed width and height are rounded to whole units, so they may deviate
+    /// slightly from the original size multiplied by this factor.
+    pub scale: f64,
+}
+
+/// The result of [`justified_rows`].
+#[derive(Debug,Clone)]
+pub struct JustifiedRows<I:Clone> {
+    /// The placed items, in input order.
+    pub items: Vec<ScaledItem<I>>,
+    /// The total height of all rows.
+    pub height: usize,
+}
+
+/// Lay out items in rows, like a photo gallery, keeping the input order.
+///
+/// Items are added to a row until their combined width, when scaled to 'row_height',
+/// reaches 'width'. All items of the row are then scaled by the same factor, so that the
+/// row fills 'width' exactly. The last row is not stretched, and keeps 'row_height'.
+///
+/// The aspect ratio of each item is preserved (up to rounding), and `allow_rotate` and `margins` are ignored.
+/// Panics if any item has zero width or height.
+pub fn justified_rows<I:Clone>(items: impl IntoIterator<Item=Item<I>>, width: usize, row_height: usize) -> JustifiedRows<I> {
+    let mut result = JustifiedRows {
+        items: vec![],
+        height: 0,
+    };
+    let mut row: Vec<Item<I>> = vec![];
+    let mut row_aspect = 0.0;
+    for item in items {
+        if item.w == 0 || item.h == 0 {
+            panic!("Item size must not be 0 in any dimension");
+        }
+        row_aspect += item.w as f64 / item.h as f64;
+        row.push(item);
+        if row_aspect * row_height as f64 >= width as f64 {
+            let height = width as f64 / row_aspect;
+            place_row(&mut result, &mut row, height, row_aspect);
+            row_aspect = 0.0;
+        }
+    }
+    if !row.is_empty() {
+        place_row(&mut result, &mut row, row_height as f64, row_aspect);
+    }
+    result
+}
+
+fn place_row<I:Clone>(result: &mut JustifiedRows<I>, row: &mut Vec<Item<I>>, height: f64, row_aspect: f64) {
+    let y0 = result.height;
+    let y1 = y0.saturating_add((height.round() as usize).max(1));
+    let mut cumulative_aspect = 0.0;
+    let mut x0 = 0;
+    let row_len = row.len();
+    for (index, item) in row.drain(..).enumerate() {
+        cumulative_aspect += item.w as f64 / item.h as f64;
+        let x1 = if index + 1 == row_len {
+            // Avoid rounding errors on the right edge
+            (row_aspect * height).round() as usize
+        } else {
+            (cumulative_aspect * height).round() as usize
+        };
+        result.items.push(ScaledItem {
+            placed: PlacedItem {
+                x0,
+                y0,
+                x1,
+                y1,
+                rotation: Rotation::None,
+                overlap: 0,
+                margins: [0; 4],
+                tags: item.tags,
+                id: item.id,
+            },
+            scale: height / item.h as f64,
+        });
+        x0 = x1;
+    }
+    result.height = y1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_justified_rows() {
+        let item = |id| Item::new(2, 1, id).with_allow_rotate(false);
+        let layout = justified_rows([item('A'), item('B'), item('C')], 12, 3);
+        let rects: Vec<_> = layout.items.iter().map(|x| (x.placed.x0, x.placed.y0, x.placed.x1, x.placed.y1)).collect();
+        assert_eq!(rects, vec![(0, 0, 6, 3), (6, 0, 12, 3), (0, 3, 6, 6)]);
+        assert_eq!(layout.items[0].scale, 3.0);
+        assert_eq!(layout.height, 6);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{PlacedItem, Rectangle};
+
+/// The material consumed by a placed item, see [`crate::Bin::material_usage`].
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct MaterialUsage {
+    /// The area of the item itself
+    pub item_area: usize,
+    /// The area of the kerf (spacing) attributed to the item
+    pub kerf_area: f64,
+}
+
+impl MaterialUsage {
+    /// The effective material consumed: the item area plus its share of the kerf
+    pub fn total(&self) -> f64 {
+        self.item_area as f64 + self.kerf_area
+    }
+}
+
+/// Attribute the kerf around 'items' to them. Each item gets a strip of half the spacing
+/// along each side which is not on the edge of the bin, since the cut along that side is
+/// shared with whatever lies beyond it.
+pub(crate) fn material_usage<I:Clone>(items: &[PlacedItem<I>], width: usize, height: usize, spacing: usize) -> Vec<MaterialUsage> {
+    let half = spacing as f64 / 2.0;
+    let share = |inner: bool| if inner { half } else { 0.0 };
+    items.iter().map(|item| {
+        let (w, h) = ((item.x1 - item.x0) as f64, (item.y1 - item.y0) as f64);
+        let kerf_w = w + share(item.x0 > 0) + share(item.x1 < width);
+        let kerf_h = h + share(item.y0 > 0) + share(item.y1 < height);
+        MaterialUsage {
+            item_area: item.rect().area(),
+            kerf_area: kerf_w * kerf_h - w * h,
+        }
+    }).collect()
+}
+
+/// The length of the cut shared by 'a' and 'b', which is the length along which they face
+/// each other across a kerf of 'spacing', so that a single cut separates them
+pub(crate) fn shared_cut(a: &Rectangle, b: &Rectangle, spacing: usize) -> usize {
+    let overlap = |a0: usize, a1: usize, b0: usize, b1: usize|a1.min(b1).saturating_sub(a0.max(b0));
+    let faces = |a1: usize, b0: usize|a1.checked_add(spacing) == Some(b0);
+    if faces(a.x1, b.x0) || faces(b.x1, a.x0) {
+        overlap(a.y0, a.y1, b.y0, b.y1)
+    } else if faces(a.y1, b.y0) || faces(b.y1, a.y0) {
+        overlap(a.x0, a.x1, b.x0, b.x1)
+    } else {
+        0
+    }
+}
+
+/// The total length of the cuts shared by any two of 'items', see [`shared_cut`]
+pub(crate) fn shared_cut_length<I:Clone>(items: &[PlacedItem<I>], spacing: usize) -> usize {
+    items.iter().enumerate().map(|(index, a)| {
+        items[index + 1..].iter().map(|b|shared_cut(&a.rect(), &b.rect(), spacing)).sum::<usize>()
+    }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bin, Item, PlaceOptions};
+
+    #[test]
+    fn test_material_usage() {
+        let mut bin = Bin::new(11, 4);
+        let items = [Item::new(5, 4, 'A'), Item::new(5, 4, 'B')];
+        assert!(bin.place_all(items.into_iter(), PlaceOptions::default().with_spacing(1)));
+        // Each item has one inner side, along which it gets half of the 1 wide kerf
+        let usage = bin.material_usage(1);
+        assert_eq!(usage, vec![MaterialUsage { item_area: 20, kerf_area: 2.0 }; 2]);
+        assert_eq!(usage[0].total(), 22.0);
+        assert_eq!(bin.stats().kerf_area, 4.0);
+    }
+
+    #[test]
+    fn test_shared_cut() {
+        let items = || [Item::new(5, 2, 'A'), Item::new(3, 1, 'B')].into_iter();
+        let mut bin = Bin::new(10, 4);
+        bin.set_default_allow_rotate(false);
+        // By default, 'B' is placed to the right of 'A', sharing a cut of length 1
+        let mut default = bin.clone();
+        assert!(default.place_all(items(), PlaceOptions::default()));
+        assert_eq!(default.placement_of(&'B').map(|x|(x.x0, x.y0)), Some((5, 0)));
+        // Below 'A', it shares a cut of length 3
+        assert!(bin.place_all(items(), PlaceOptions::default().with_shared_cut_weight(1)));
+        assert_eq!(bin.placement_of(&'B').map(|x|(x.x0, x.y0)), Some((0, 2)));
+        assert_eq!(bin.stats().shared_cut_length, 3);
+        // With a kerf, items only share a cut across it
+        let a = Rectangle { x0: 0, y0: 0, x1: 5, y1: 2 };
+        assert_eq!(shared_cut(&a, &Rectangle { x0: 6, y0: 1, x1: 8, y1: 4 }, 1), 1);
+        assert_eq!(shared_cut(&a, &Rectangle { x0: 5, y0: 1, x1: 8, y1: 4 }, 1), 0);
+    }
+}
@@ -2,36 +2,44 @@
 //!
 //! See <https://en.wikipedia.org/wiki/Bin_packing_problem> .
 //!
+//! With the `log` feature, the progress of packing is logged using the `log` crate:
+//! passes at debug level, and rejected items at trace level.
+//!
+//! With the `metrics` feature, each finished packing is reported through the `metrics`
+//! facade, to whichever recorder the application has installed:
+//! * `bin_packing_2d_packings_total` counter, labeled with 'outcome' ("all_fit", "partial" or "cancelled")
+//! * `bin_packing_2d_items_placed_total` and `bin_packing_2d_items_rejected_total` counters
+//! * `bin_packing_2d_pack_duration_seconds` and `bin_packing_2d_utilization_ratio` histograms
+//!
+//! With the experimental `gpu` feature, a `GpuEvaluator` can be set using
+//! `PlaceOptions::with_gpu`, to search for the best position of each item on the GPU.
+//!
+//! With the `report` feature, `write_html_report` writes a standalone HTML page showing
+//! the layout of a bin, for sharing results with people who don't run the packer.
+//!
+//! With the `rand_core` feature, `PlaceOptions::with_rng` sets the random number generator
+//! used to shuffle equally sized items, instead of the one seeded using `with_seed`.
+//!
+//! With the `image` feature, `Bin::from_luma_image` creates a bin for a partially used sheet
+//! from a photograph.
+//!
+//! For photo-gallery style layouts, where items are scaled to fill rows of equal
+//! width, see [`justified_rows`].
+//!
 //! Example usage:
 //!
 //! ```rust
 //!
-//! use bin_packing_2d::{Bin, Item};
+//! use bin_packing_2d::prelude::*;
 //! let items = [
-//!     Item {
-//!         w: 10,
-//!         h: 3,
-//!         id: 'D'
-//!     },
-//!     Item {
-//!         w: 10,
-//!         h: 3,
-//!         id: 'A'
-//!     },
-//!     Item {
-//!         w: 10,
-//!         h: 3,
-//!         id: 'B'
-//!     },
-//!     Item {
-//!         w: 1,
-//!         h: 10,
-//!         id: 'C'
-//!     },
+//!     Item::rotatable(10, 3, 'D'),
+//!     Item::rotatable(10, 3, 'A'),
+//!     Item::rotatable(10, 3, 'B'),
+//!     Item::rotatable(1, 10, 'C'),
 //! ];
 //!
 //! let mut bin = Bin::new(10, 10);   // Create 10x10 bin
-//! let all_fit: bool = bin.place_all(items.into_iter()); // Pack all the items
+//! let all_fit: bool = bin.pack(items); // Pack all the items, using the default options
 //!
 //! println!("All items placed: {:?}", all_fit);
 //! println!("Solution: {:#?}", bin.solution());
@@ -47,7 +55,17 @@
 //!         y0: 0,
 //!         x1: 10,
 //!         y1: 3,
-//!         rotated: false,
+//!         rotation: None,
+//!         overlap: 0,
+//!         margins: [
+//!             0,
+//!             0,
+//!             0,
+//!             0,
+//!         ],
+//!         tags: Tags(
+//!             0,
+//!         ),
 //!         id: 'D',
 //!     },
 //!     PlacedItem {
@@ -55,7 +73,17 @@
 //!         y0: 3,
 //!         x1: 10,
 //!         y1: 6,
-//!         rotated: false,
+//!         rotation: None,
+//!         overlap: 0,
+//!         margins: [
+//!             0,
+//!             0,
+//!             0,
+//!             0,
+//!         ],
+//!         tags: Tags(
+//!             0,
+//!         ),
 //!         id: 'A',
 //!     },
 //!     PlacedItem {
@@ -63,7 +91,17 @@
 //!         y0: 6,
 //!         x1: 10,
 //!         y1: 9,
-//!         rotated: false,
+//!         rotation: None,
+//!         overlap: 0,
+//!         margins: [
+//!             0,
+//!             0,
+//!             0,
+//!             0,
+//!         ],
+//!         tags: Tags(
+//!             0,
+//!         ),
 //!         id: 'B',
 //!     },
 //!     PlacedItem {
@@ -71,53 +109,139 @@
 //!         y0: 9,
 //!         x1: 10,
 //!         y1: 10,
-//!         rotated: true,
+//!         rotation: Cw90,
+//!         overlap: 0,
+//!         margins: [
+//!             0,
+//!             0,
+//!             0,
+//!             0,
+//!         ],
+//!         tags: Tags(
+//!             0,
+//!         ),
 //!         id: 'C',
 //!     },
 //! ]
 //! ```
 //!
-//!
+//! Use [`Bin::place_all`] together with [`PlaceOptions`] to control how items are placed,
+//! for instance to set a deadline or the spacing between items.
 //!
 #![deny(missing_docs)]
 #![deny(warnings)]
 
-use std::cell::RefCell;
-use std::cmp::Reverse;
-use bit_vec::BitVec;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::{Arc, Mutex, PoisonError};
 
+mod adjacency;
+mod banding;
+mod blocked;
+mod bitmap;
+mod cache;
+mod color;
+mod completion;
+mod config;
+mod conformance;
+mod csv;
+mod cutting_stock;
+mod double_sided;
+mod equivalence;
+mod fingerprint;
+mod free_rects;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod growable;
+mod justified;
+mod kerf;
+mod linear;
+mod linked;
+mod multi;
+mod occupancy;
+mod options;
+mod pareto;
+mod persist;
+mod pool;
+mod presets;
+mod rectangle;
+mod repack;
+#[cfg(feature = "report")]
+mod report;
+mod scan;
+mod seeded;
+mod sequence;
+mod spans;
+mod stats;
+mod stepwise;
+mod tags;
+mod tuner;
+mod validate;
+mod waste;
+mod weights;
 
-struct Bitmap2d {
-    width: usize,
-    height: usize,
-    bits: BitVec,
-}
+use bitmap::Bitmap2d;
+use stepwise::sort_items;
+pub use adjacency::Adjacency;
+pub use banding::{BandingRules, EdgeBanding};
+pub use bitmap::Backend;
+pub use blocked::BlockPattern;
+pub use cache::{CacheStore, CachedSolution, MemoryStore, PackerCache};
+pub use color::{Color, ColorMap};
+pub use config::PackConfig;
+pub use conformance::{check_conformance, conformance_corpus, ConformanceInstance};
+pub use csv::{read_items_csv, write_csv};
+pub use cutting_stock::{CuttingPlan, Demand, Pattern};
+pub use double_sided::{DoubleSidedBin, Side, SidedItem};
+pub use equivalence::solutions_equivalent;
+pub use fingerprint::{compare_fingerprints, fingerprint_instance};
+#[cfg(feature = "gpu")]
+pub use gpu::{GpuError, GpuEvaluator};
+pub use growable::{AspectGrowth, DirectionalGrowth, GrowDirection, GrowableBin, GrowthPolicy, Increment};
+pub use justified::{justified_rows, JustifiedRows, ScaledItem};
+pub use kerf::MaterialUsage;
+pub use linked::Linked;
+pub use multi::{Assignment, MultiBin};
+pub use options::{BinSelection, ContactWeights, Heuristic, OverlapTolerance, PlaceOptions, Quality, SliverPolicy, SpacingPolicy, TieBreak};
+pub use pareto::{LayoutScore, ParetoLayout};
+pub use persist::{migrate_solution, read_solution, read_solution_for_config, read_solution_iter, read_solution_with_config, write_solution, write_solution_with_config, SolutionError, SOLUTION_FORMAT_VERSION};
+pub use pool::{JobHandle, JobResult, PackJob, PackerPool};
+pub use presets::Preset;
+pub use rectangle::Rectangle;
+#[cfg(feature = "report")]
+pub use report::write_html_report;
+pub use scan::{Corner, ScanOrder};
+pub use sequence::Edge;
+pub use stats::{CandidateCounts, CostEstimate, Feasibility, Infeasibility, PackOutcome, PackingStats};
+pub use stepwise::{PackIterator, RejectReason, Step};
+pub use tags::Tags;
+pub use tuner::{Tuner, TunerConfig};
+pub use validate::{validate_all, ItemError};
+pub use weights::WeightMap;
 
-impl Bitmap2d {
-    fn clear(&mut self) {
-        self.bits.clear();
-    }
-    fn new(width: usize, height: usize) -> Bitmap2d {
-        if width < 1 || height < 1 {
-            panic!("Width and height must both be > 0");
-        }
-        Bitmap2d {
-            width,
-            height,
-            bits: BitVec::from_elem(width*height, false)
-        }
-    }
-    fn get(&self, x: usize, y: usize) -> bool {
-        self.bits[y*self.width + x]
-    }
-    fn set(&mut self, x: usize, y: usize, value: bool) {
-        self.bits.set(y*self.width + x, value)
-    }
+/// The commonly used types of this crate.
+///
+/// Glob-importing this module is the recommended way to use the crate:
+///
+/// ```rust
+/// use bin_packing_2d::prelude::*;
+/// let mut bin: Bin<u32> = Bin::new(10, 10);
+/// assert!(bin.place_all(std::iter::empty(), PlaceOptions::default()));
+/// ```
+pub mod prelude {
+    pub use crate::{Bin, Candidate, CompactSolution, Corner, Heuristic, Hole, Item, MultiBin, PackingStats, PlaceOptions, PlacedItem, Rectangle, Rotation, Run, ScanOrder, Strategy, Tags};
 }
 
 /// An item that is to be packed.
 /// Note that the item might be rotated 90 degrees when placed
+///
+/// Items are created using 'new' or 'rotatable', and the 'with_*' methods, so that adding
+/// fields does not break code creating items.
 #[derive(PartialEq,Eq,Debug,Hash,Clone)]
+#[non_exhaustive]
 pub struct Item<I> {
     /// Width of item
     /// Note that the item might be rotated 90 degrees when placed
@@ -125,8 +249,26 @@ pub struct Item<I> {
     /// Height of item
     /// Note that the item might be rotated 90 degrees when placed
     pub h: usize,
-    /// Item can be rotated
-    pub allow_rotate: bool,
+    /// Item can be rotated.
+    /// If None, the default of the bin is used (see `Bin::set_default_allow_rotate`).
+    pub allow_rotate: Option<bool>,
+    /// Clearance required on each side of the item: left, right, top, bottom.
+    /// No other item may be placed within the clearance, but it may extend past the edge
+    /// of the bin. The sides are those of the item in its unrotated orientation; when the
+    /// item is rotated clockwise, its left side becomes the top side, its top side becomes
+    /// the right side, and so on.
+    pub margins: [usize; 4],
+    /// Tags which are copied to the placed item, see [`Tags`]
+    pub tags: Tags,
+    /// If true, the item is a filler which is placed if space allows. Optional items are packed
+    /// after all mandatory ones, and packing succeeds without them.
+    /// See also `PlaceOptions::with_utilization_target`.
+    pub optional: bool,
+    /// If set, the item must be placed with its left edge ('PlacedItem::x0') in this range,
+    /// for instance where a print head can reach
+    pub x_band: Option<Range<usize>>,
+    /// If set, the item must be placed with its top edge ('PlacedItem::y0') in this range
+    pub y_band: Option<Range<usize>>,
     /// An id for the item.
     /// This is not interpreted by this library, but can be useful to keep
     /// track of items.
@@ -134,9 +276,83 @@ pub struct Item<I> {
 }
 
 impl<I> Item<I> {
+    /// Create an item of size 'w' x 'h'. Whether it may be rotated is decided by
+    /// the bin it is placed in (see `Bin::set_default_allow_rotate`).
+    pub fn new(w: usize, h: usize, id: I) -> Item<I> {
+        Item { w, h, allow_rotate: None, margins: [0; 4], tags: Tags::NONE, optional: false, x_band: None, y_band: None, id }
+    }
+
+    /// Create an item of size 'w' x 'h', which may be rotated.
+    pub fn rotatable(w: usize, h: usize, id: I) -> Item<I> {
+        Item::new(w, h, id).with_allow_rotate(true)
+    }
+
+    /// Set whether the item may be rotated, see 'allow_rotate'
+    pub fn with_allow_rotate(mut self, allow_rotate: bool) -> Item<I> {
+        self.allow_rotate = Some(allow_rotate);
+        self
+    }
+
+    /// Set the clearance required on each side of the item, see 'margins'
+    pub fn with_margins(mut self, margins: [usize; 4]) -> Item<I> {
+        self.margins = margins;
+        self
+    }
+
+    /// Attach 'tags' to the item, see [`Tags`]
+    pub fn with_tags(mut self, tags: Tags) -> Item<I> {
+        self.tags = tags;
+        self
+    }
+
+    /// Mark the item as optional, see 'optional'
+    pub fn with_optional(mut self, optional: bool) -> Item<I> {
+        self.optional = optional;
+        self
+    }
+
+    /// Restrict the left edge of the item to 'band', see 'x_band'
+    pub fn with_x_band(mut self, band: Range<usize>) -> Item<I> {
+        self.x_band = Some(band);
+        self
+    }
+
+    /// Restrict the top edge of the item to 'band', see 'y_band'
+    pub fn with_y_band(mut self, band: Range<usize>) -> Item<I> {
+        self.y_band = Some(band);
+        self
+    }
+
     fn size(&self) -> usize {
         self.w.max(self.h)
     }
+
+    /// The same item with id 'id'
+    fn with_id<J>(&self, id: J) -> Item<J> {
+        Item { w: self.w, h: self.h, allow_rotate: self.allow_rotate, margins: self.margins, tags: self.tags, optional: self.optional, x_band: self.x_band.clone(), y_band: self.y_band.clone(), id }
+    }
+
+    /// Whether the item may be placed with its top left corner at 'x0', 'y0', see 'x_band'
+    fn bands_allow(&self, x0: usize, y0: usize) -> bool {
+        self.x_band.as_ref().is_none_or(|band|band.contains(&x0)) && self.y_band.as_ref().is_none_or(|band|band.contains(&y0))
+    }
+
+    /// Check that the item can be placed in 'bin' without overflowing or panicking, and
+    /// that it fits in the bin when it is empty. See also [`validate_all`] and
+    /// `PlaceOptions::with_validation`.
+    pub fn validate(&self, bin: &Bin<I>) -> Result<(), ItemError> where I: Clone {
+        validate::validate(self, bin)
+    }
+
+    /// The clearance required on each side when placed, see 'margins'
+    fn placed_margins(&self, rotated: bool) -> [usize; 4] {
+        Rotation::quarter_turn(rotated).rotate_sides(self.margins)
+    }
+
+    /// True if the item is the same when rotated by a quarter turn, including its margins
+    fn is_symmetric(&self) -> bool {
+        self.w == self.h && self.placed_margins(true) == self.margins
+    }
 }
 
 /// A placed item. This contains information on where an item was placed.
@@ -159,13 +375,41 @@ pub struct PlacedItem<I:Clone> {
     /// One past the bottom edge of the item, horizontally.
     /// This takes 'rotation' into account.
     pub y1: usize,
-    /// True if the object was rotated 90 degrees to fit
-    pub rotated: bool,
+    /// How the object was rotated to fit. See also 'rotated'.
+    pub rotation: Rotation,
+    /// The number of cells of the item which overlap previously placed items.
+    /// Always 0 unless an overlap tolerance is set, see `PlaceOptions::with_overlap_tolerance`.
+    pub overlap: usize,
+    /// The clearance around the item which must be kept free: left, right, top, bottom.
+    /// Unlike 'Item::margins', this is in the placed orientation.
+    pub margins: [usize; 4],
+    /// The tags of the item
+    pub tags: Tags,
     /// The user-supplied id of the object.
     pub id: I
 }
 
 impl<I:Clone> PlacedItem<I> {
+    /// True if the item was rotated by a quarter turn, so that its width and height are swapped
+    pub fn rotated(&self) -> bool {
+        self.rotation.swaps_sides()
+    }
+    /// The width of the item as placed, `x1 - x0`
+    pub fn width(&self) -> usize {
+        self.x1 - self.x0
+    }
+    /// The height of the item as placed, `y1 - y0`
+    pub fn height(&self) -> usize {
+        self.y1 - self.y0
+    }
+    /// The width of the item before rotation, i.e. 'Item::w'
+    pub fn original_w(&self) -> usize {
+        if self.rotated() { self.height() } else { self.width() }
+    }
+    /// The height of the item before rotation, i.e. 'Item::h'
+    pub fn original_h(&self) -> usize {
+        if self.rotated() { self.width() } else { self.height() }
+    }
     /// Check if the item, when placed in the rotation chosen,
     /// contains the given point
     pub fn contains(&self, pos: (usize,usize)) -> bool {
@@ -173,6 +417,32 @@ impl<I:Clone> PlacedItem<I> {
         x >= self.x0 && x < self.x1 &&
             y >= self.y0 && y < self.y1
     }
+    /// The area covered by the item
+    fn rect(&self) -> Rectangle {
+        Rectangle { x0: self.x0, y0: self.y0, x1: self.x1, y1: self.y1 }
+    }
+    /// The item including its margins
+    fn margin_area(&self) -> Rectangle {
+        let [left, right, top, bottom] = self.margins;
+        Rectangle {
+            x0: self.x0.saturating_sub(left),
+            y0: self.y0.saturating_sub(top),
+            x1: self.x1.saturating_add(right),
+            y1: self.y1.saturating_add(bottom),
+        }
+    }
+    /// True if 'rect' lies within the margins of this item
+    fn violates_margins(&self, rect: &Rectangle) -> bool {
+        self.margins != [0; 4] && self.margin_area().intersection_area(rect) > self.rect().intersection_area(rect)
+    }
+    /// The sum of the horizontal and vertical gaps between this item and the
+    /// rectangle `x0..x1`, `y0..y1`. Zero if they touch or overlap.
+    fn distance_to(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> usize {
+        let gap = |a0: usize, a1: usize, b0: usize, b1: usize| {
+            b0.saturating_sub(a1).max(a0.saturating_sub(b1))
+        };
+        gap(self.x0, self.x1, x0, x1) + gap(self.y0, self.y1, y0, y1)
+    }
 }
 ///A free, unused area
 #[derive(Debug,Clone,Copy)]
@@ -183,16 +453,187 @@ pub struct Hole {
     pub height: usize,
 }
 
+/// A horizontal run of cells in a single row of a bin, which are either all free
+/// or all occupied. Covers the columns `x0..x1`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Run {
+    /// The first column of the run
+    pub x0: usize,
+    /// One past the last column of the run
+    pub x1: usize,
+    /// True if the cells of the run are occupied by placed items
+    pub occupied: bool,
+}
+
+/// The solution of a bin, in a compact form suitable for uploading to a GPU buffer.
+/// See [`Bin::compact_solution`].
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct CompactSolution<I> {
+    /// `[x0, y0, x1, y1]` of each placed item, with the same meaning as the
+    /// corresponding fields of [`PlacedItem`].
+    pub rects: Vec<[u16; 4]>,
+    /// The id of each placed item. `ids[n]` is the id of the item placed at `rects[n]`.
+    pub ids: Vec<I>,
+}
+
+/// A possible position for an item. See [`Bin::best_positions`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Candidate {
+    /// The horizontal coordinate of the left edge of the item
+    pub x0: usize,
+    /// The vertical coordinate of the top edge of the item
+    pub y0: usize,
+    /// True if the item is rotated 90 degrees
+    pub rotated: bool,
+    /// The highest priority of any zone the position overlaps (see `Bin::add_zone`).
+    /// Positions in lower priority zones are always preferred, regardless of score.
+    pub zone: usize,
+    /// The score of the position, within its zone. Lower is better.
+    pub score: usize,
+}
+
+impl Candidate {
+    /// Sort key; lower is better
+    fn rank(&self) -> (usize, usize) {
+        (self.zone, self.score)
+    }
+}
+
+/// The evaluation of a possible position for an item
+struct Fit {
+    /// Score according to the contact heuristic
+    contact: usize,
+    /// Additional penalties, for instance for creating slivers
+    penalty: usize,
+}
+
 /// A bin into which objects are to be packed.
+///
+/// Cloning a bin clones its current contents. A callback registered using 'set_autosave'
+/// is shared between the clones.
+#[derive(Clone)]
 pub struct Bin<I:Clone> {
     bitmap: Bitmap2d,
     items: Vec<PlacedItem<I>>,
     largest_hole: Hole,
-    metric: fn(Hole)->usize,
+    metric: Metric,
+    // Metrics added using 'add_metric': name, metric and the largest hole according to it
+    named_metrics: Vec<(String, Metric, Hole)>,
+    autosave: Option<AutosaveCallback<I>>,
+    stats: PackingStats,
+    preferences: Vec<(I, I, usize)>,
+    /// Equality of ids. Only available if 'prefer_near', 'restrict_to_regions' or
+    /// 'forbid_regions' has been called, since 'I' is not generally required to implement 'PartialEq'.
+    id_eq: Option<fn(&I, &I) -> bool>,
+    // Rectangles which items with the given id must be placed inside
+    regions: Vec<(I, Vec<Rectangle>)>,
+    // Rectangles which items with the given id must not intersect
+    forbidden: Vec<(I, Vec<Rectangle>)>,
+    default_allow_rotate: bool,
+    zones: Vec<(Rectangle, usize)>,
+    // Rows above this are used
+    watermark: usize,
+    // Items must lie left of and above these coordinates, see 'limit_extent'
+    extent: Option<(usize, usize)>,
+    // Areas which are always occupied, see 'reserve_rows' and 'block_pattern'
+    reserved: Vec<Reservation>,
+    // Index of 'items' by id, built by 'placement_of'. Reset whenever 'items' changes.
+    placement_index: RefCell<Option<HashMap<I, usize>>>,
+    rejections: Vec<(I, RejectReason)>,
+    // See 'candidate_counts'. The counter is updated while evaluating the positions of an item.
+    candidate_counter: Cell<CandidateCounts>,
+    candidate_counts: Vec<(I, CandidateCounts)>,
+    unloading: Option<(Edge, SequenceFn<I>)>,
+    // Replaces the default order in which items are packed, see 'set_item_order'
+    item_order: Option<OrderFn<I>>,
+    // See 'set_weight_map'. Shared, since maps of large bins take a lot of memory.
+    weights: Option<Arc<WeightMap>>,
+}
+
+/// Parts of a bin reserved using 'reserve_rows', 'reserve_cols' or 'block_pattern'
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+enum Reservation {
+    Rows(Range<usize>),
+    Cols(Range<usize>),
+    Pattern(BlockPattern),
+    // Occupied cells of a scanned sheet, see 'from_occupancy_bitmap'
+    Mask(Vec<Rectangle>),
+}
+
+impl Reservation {
+    /// The reserved areas in a bin of the given size
+    fn rects(&self, width: usize, height: usize) -> Vec<Rectangle> {
+        match self {
+            Reservation::Rows(rows) => vec![Rectangle { x0: 0, y0: rows.start, x1: width, y1: rows.end }],
+            Reservation::Cols(cols) => vec![Rectangle { x0: cols.start, y0: 0, x1: cols.end, y1: height }],
+            Reservation::Pattern(pattern) => pattern.rects(width, height),
+            Reservation::Mask(rects) => rects.iter().map(|x|Rectangle { x0: x.x0, y0: x.y0, x1: x.x1.min(width), y1: x.y1.min(height) }).collect(),
+        }
+    }
+}
+
+type AutosaveCallback<I> = Arc<Mutex<dyn FnMut(&[PlacedItem<I>]) + Send>>;
+type SequenceFn<I> = Arc<dyn Fn(&I) -> usize + Send + Sync>;
+type Compare<'a, I> = dyn Fn(&Item<I>, &Item<I>) -> Ordering + 'a;
+type OrderFn<I> = Arc<dyn Fn(&Item<I>, &Item<I>) -> Ordering + Send + Sync>;
+type Metric = Arc<dyn Fn(Hole) -> usize + Send + Sync>;
+
+/// The clockwise rotation of a placed item. Items are currently only rotated by a quarter
+/// turn, but solutions may use the other rotations.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub enum Rotation {
+    /// Not rotated
+    #[default]
+    None,
+    /// Rotated 90 degrees clockwise
+    Cw90,
+    /// Rotated 180 degrees
+    Cw180,
+    /// Rotated 270 degrees clockwise, i.e. 90 degrees counterclockwise
+    Cw270,
+}
+
+impl Rotation {
+    /// The rotation in degrees: 0, 90, 180 or 270
+    pub fn degrees(self) -> usize {
+        match self {
+            Rotation::None => 0,
+            Rotation::Cw90 => 90,
+            Rotation::Cw180 => 180,
+            Rotation::Cw270 => 270,
+        }
+    }
+
+    /// The rotation by the given number of degrees, if it is 0, 90, 180 or 270
+    pub fn from_degrees(degrees: usize) -> Option<Rotation> {
+        [Rotation::None, Rotation::Cw90, Rotation::Cw180, Rotation::Cw270].into_iter().find(|x|x.degrees() == degrees)
+    }
+
+    /// True if the width and height of the item are swapped
+    pub fn swaps_sides(self) -> bool {
+        matches!(self, Rotation::Cw90 | Rotation::Cw270)
+    }
+
+    /// The rotation used for items placed rotated or not
+    pub(crate) fn quarter_turn(rotated: bool) -> Rotation {
+        if rotated { Rotation::Cw90 } else { Rotation::None }
+    }
+
+    /// Where the sides of an item (left, right, top, bottom, as in 'Item::margins') end up
+    /// after the rotation, in the same order
+    pub(crate) fn rotate_sides<T:Copy>(self, sides: [T; 4]) -> [T; 4] {
+        let [left, right, top, bottom] = sides;
+        match self {
+            Rotation::None => sides,
+            Rotation::Cw90 => [bottom, top, left, right],
+            Rotation::Cw180 => [right, left, bottom, top],
+            Rotation::Cw270 => [top, bottom, right, left],
+        }
+    }
 }
 
 /// Constraints on placing
-#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
 pub enum Strategy {
     /// Rotate the item 90 degrees
     Rotate,
@@ -212,7 +653,17 @@ struct Rect {
 
 impl Hole {
     fn default_area(&self) -> usize {
-        self.width * self.height
+        self.width.saturating_mul(self.height)
+    }
+
+    /// True if an item of 'w' x 'h' fits in the hole without being rotated
+    pub fn fits(&self, w: usize, h: usize) -> bool {
+        w <= self.width && h <= self.height
+    }
+
+    /// True if an item of 'w' x 'h' fits in the hole in either orientation
+    pub fn fits_rotatable(&self, w: usize, h: usize) -> bool {
+        self.fits(w, h) || self.fits(h, w)
     }
 }
 
@@ -296,6 +747,139 @@ impl Rect {
     }
 }
 
+/// The largest free rectangle of a 'width' x 'height' grid, where 'is_occupied' tells whether
+/// the cell at 'x', 'y' is occupied, for instance from a camera scan of a partially used sheet.
+/// Holes are compared by area, like the default metric of [`Bin::get_largest_hole`].
+pub fn largest_hole(width: usize, height: usize, is_occupied: impl Fn(usize, usize) -> bool) -> Hole {
+    if width == 0 || height == 0 {
+        return Hole { width: 0, height: 0 };
+    }
+    let mut bitmap = Bitmap2d::with_backend(width, height, Backend::Auto);
+    for y in 0..height {
+        let mut x = 0;
+        while x < width {
+            let start = x;
+            while x < width && is_occupied(x, y) {
+                x += 1;
+            }
+            if x > start {
+                bitmap.fill(start, y, x - start, 1, true);
+            }
+            x += 1;
+        }
+    }
+    largest_hole_in(&bitmap, &|hole: Hole|hole.default_area())
+}
+
+/// The largest hole in 'bitmap' according to 'metric'
+fn largest_hole_in(bitmap: &Bitmap2d, metric: &dyn Fn(Hole) -> usize) -> Hole {
+    if !bitmap.is_dense() {
+        // The distance map below would need 4 bytes per cell, which is too much for
+        // the very large bins using other backends. Use the largest maximal free rectangle instead.
+        return free_rects::maximal_free_rects(bitmap).iter().map(|x|x.hole())
+            .max_by_key(|&hole|metric(hole)).unwrap_or(Hole{width:0,height:0});
+    }
+    // The distance of each free cell to the nearest occupied cell or edge, 0 for occupied cells
+    let (width, height) = (bitmap.width, bitmap.height);
+    let mut offshore_map: Vec<u32> = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            offshore_map.push(if bitmap.get(x, y) {0} else {u32::MAX});
+        }
+    }
+
+    let mut dist = 0;
+    loop {
+        let mut no_progress = true;
+        let nextdist = dist + 1;
+        for y in 0..height {
+            for x in 0..width {
+                if offshore_map[y * width + x] != u32::MAX {
+                    continue;
+                }
+                // Cells next to the edge have a neighbor at distance 0 outside the bin
+                let at_edge = x == 0 || y == 0 || x + 1 == width || y + 1 == height;
+                let next_to_dist = (dist == 0 && at_edge) || (y.saturating_sub(1)..(y + 2).min(height)).any(|ny| {
+                    offshore_map[ny * width + x.saturating_sub(1)..ny * width + (x + 2).min(width)].contains(&dist)
+                });
+                if next_to_dist {
+                    offshore_map[y * width + x] = nextdist;
+                    no_progress = false;
+                }
+            }
+        }
+
+        if no_progress {
+            break;
+        }
+        dist = nextdist;
+    }
+    if dist == 0 {
+        return Hole{width:0,height:0};
+    }
+    let mut candidates = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            if offshore_map[y * width + x] != dist {
+                continue;
+            }
+            candidates.push(Rect{x0:x,y0:y,x1:x,y1:y});
+        }
+    }
+    let mut biggest_hole = Hole {
+        width: 0,
+        height: 0,
+    };
+    let mut biggest_area = 0;
+
+
+    for mut rect in candidates {
+        loop {
+            let mut progress = false;
+            let dirs = if metric(rect.grow_right().hole()) > metric(rect.grow_down().hole()) {
+                [true, false]
+            } else {
+                [false, true]
+            };
+            for horiz in dirs {
+                if horiz {
+                    if !rect.left_neighbors().map(|x|x.is_obstructed(bitmap)).unwrap_or(true) {
+                        progress = true;
+                        rect = rect.grow_left();
+                        break;
+                    }
+                    if !rect.right_neighbors(bitmap.width).map(|x|x.is_obstructed(bitmap)).unwrap_or(true) {
+                        progress = true;
+                        rect = rect.grow_right();
+                        break;
+                    }
+                } else {
+                    if !rect.top_neighbors().map(|x|x.is_obstructed(bitmap)).unwrap_or(true) {
+                        progress = true;
+                        rect = rect.grow_up();
+                        break;
+                    }
+                    if !rect.bottom_neighbors(bitmap.height).map(|x|x.is_obstructed(bitmap)).unwrap_or(true) {
+                        progress = true;
+                        rect = rect.grow_down();
+                        break;
+                    }
+                }
+            }
+
+            if !progress {
+                break;
+            }
+        }
+        let metric = metric(rect.hole());
+        if metric > biggest_area {
+            biggest_area = metric;
+            biggest_hole = rect.hole();
+        }
+    }
+    biggest_hole
+}
+
 
 impl<I:Clone> Bin<I> {
 
@@ -315,6 +899,9 @@ impl<I:Clone> Bin<I> {
     /// Note: If some objects couldn't fit, this slice will have less elements
     /// than the user attmpted to place.
     /// This library does not generate optimal solutions.
+    ///
+    /// Very large solutions can be processed in parts using `chunks`, for instance to write
+    /// them using [`write_solution`] one chunk at a time.
     pub fn solution(&self) -> &[PlacedItem<I>] {
         &self.items
     }
@@ -326,297 +913,1624 @@ impl<I:Clone> Bin<I> {
         self.items
     }
 
+    /// The placement of the item with the given id, or None if it has not been placed.
+    /// If several placed items have the same id, the first one is returned.
+    ///
+    /// An index of the placed items is built on the first call, and reused until
+    /// more items are placed, so repeated lookups are cheap.
+    pub fn placement_of(&self, id: &I) -> Option<&PlacedItem<I>> where I: Eq + Hash {
+        let mut index = self.placement_index.borrow_mut();
+        let index = index.get_or_insert_with(|| {
+            let mut index = HashMap::with_capacity(self.items.len());
+            for (position, item) in self.items.iter().enumerate() {
+                index.entry(item.id.clone()).or_insert(position);
+            }
+            index
+        });
+        index.get(id).map(|&position| &self.items[position])
+    }
+
+    /// The placed items which have all the tags in 'tag', in placement order
+    pub fn filter_by_tag(&self, tag: Tags) -> impl Iterator<Item=&PlacedItem<I>> {
+        self.items.iter().filter(move |x|x.tags.contains(tag))
+    }
+
+    /// The items which could not be placed by the most recent call to 'place_all'
+    /// (or a similar method), with the reason. Items which were never tried because
+    /// packing was cancelled are not included.
+    pub fn rejections(&self) -> &[(I, RejectReason)] {
+        &self.rejections
+    }
+
+    /// How the candidate positions evaluated for each item of the kept pass of the most recent
+    /// 'place_all' or 'pack_stepwise' turned out, in the order the items were tried. Only
+    /// recorded if enabled using `PlaceOptions::with_candidate_stats`. Items which were
+    /// rejected without a search, because an item of the same size had failed, have no
+    /// evaluations.
+    pub fn candidate_counts(&self) -> &[(I, CandidateCounts)] {
+        &self.candidate_counts
+    }
+
+    /// Put the solution in a canonical form, so that equal layouts give equal solutions,
+    /// for instance for hashing or diffing: items are sorted by 'x0', then 'y0', then id,
+    /// and square items are reported as not rotated, since rotating them changes nothing.
+    pub fn canonicalize(&mut self) where I: Ord {
+        for item in &mut self.items {
+            if item.x1 - item.x0 == item.y1 - item.y0 {
+                item.rotation = Rotation::None;
+            }
+        }
+        self.items.sort_by(|a, b|(a.x0, a.y0, &a.id).cmp(&(b.x0, b.y0, &b.id)));
+        self.items_changed();
+    }
+    /// The pairs of placed items which share part of an edge, with the length of the
+    /// shared part. Items touching only at a corner are not included.
+    pub fn adjacency(&self) -> Vec<Adjacency> {
+        adjacency::adjacency(&self.items)
+    }
+    /// The total length of cuts needed to separate all placed items from each other and from
+    /// the rest of the bin: the length of all item edges not on the border of the bin, with
+    /// edges shared by two items counted once.
+    pub fn cut_length(&self) -> usize {
+        pareto::cut_length(&self.items, self.width(), self.height())
+    }
+    /// Return the set of placed objects as a [`CompactSolution`], with
+    /// coordinates stored as `u16`.
+    /// Returns None if the bin is too large for all coordinates to fit in a `u16`.
+    pub fn compact_solution(&self) -> Option<CompactSolution<I>> {
+        if self.width() > u16::MAX as usize || self.height() > u16::MAX as usize {
+            return None;
+        }
+        Some(CompactSolution {
+            rects: self.items.iter().map(|x|[x.x0 as u16, x.y0 as u16, x.x1 as u16, x.y1 as u16]).collect(),
+            ids: self.items.iter().map(|x|x.id.clone()).collect(),
+        })
+    }
+
+    /// Statistics about the most recent call to 'place_all'.
+    pub fn stats(&self) -> &PackingStats {
+        &self.stats
+    }
+
+    /// The approximate number of bytes of memory used by the bin, including the
+    /// solution, but not counting heap memory owned by item ids.
+    pub fn memory_footprint(&self) -> usize {
+        std::mem::size_of::<Self>() +
+            self.bitmap.memory_bytes() +
+            self.items.capacity() * std::mem::size_of::<PlacedItem<I>>()
+    }
+
+    /// Estimate the resources needed to pack 'item_count' items into a bin of
+    /// the given size, without doing any work. This can be used to reject
+    /// requests which would be too expensive before running them.
+    pub fn estimate_cost(width: usize, height: usize, item_count: usize) -> CostEstimate {
+        let area = width.saturating_mul(height);
+        // Bitmap and its copy for restarting passes, the distance map of the hole
+        // calculation (only used for bitmaps which aren't tiled), the input items,
+        // and the solution and its copy.
+        let distance_map = if Bitmap2d::tiled_for(width, height) { 0 } else { area.saturating_mul(std::mem::size_of::<u32>()) };
+        let memory_bytes = Bitmap2d::initial_bytes(width, height).saturating_mul(2)
+            .saturating_add(distance_map)
+            .saturating_add(item_count.saturating_mul(std::mem::size_of::<Item<I>>()))
+            .saturating_add(item_count.saturating_mul(2 * std::mem::size_of::<PlacedItem<I>>()))
+            .saturating_add(std::mem::size_of::<Self>());
+        // Both orientations in each of the default passes
+        let passes = PlaceOptions::default().strategies().len() as u64;
+        let evaluations = (area as u64).saturating_mul(item_count as u64).saturating_mul(2 * passes);
+        CostEstimate {
+            memory_bytes,
+            evaluations,
+        }
+    }
+
+    /// Quickly check whether 'items' could possibly all be placed in this bin, in addition
+    /// to the items already placed, without packing. This compares the total area of the items
+    /// to the free area, each item to the size of the bin, and checks that the items which
+    /// are wider (or taller) than half the bin, and therefore can't be placed next to each
+    /// other, fit when stacked. Spacing and margins are not taken into account.
+    ///
+    /// This takes time proportional to the number of items, and can be used to warn about
+    /// hopeless inputs before running the much slower packing.
+    pub fn estimate_feasibility(&self, items: &[Item<I>]) -> Feasibility {
+        let (width, height) = (self.width(), self.height() - self.watermark);
+        let mut required_area = 0usize;
+        let (mut wide_height, mut tall_width) = (0usize, 0usize);
+        for (index, item) in items.iter().enumerate() {
+            let mut orientations = vec![(item.w, item.h)];
+            if self.allows_rotation(item) {
+                orientations.push((item.h, item.w));
+            }
+            orientations.retain(|&(w, h)|w <= width && h <= height);
+            if orientations.is_empty() {
+                return Feasibility::Impossible(Infeasibility::TooLarge(index));
+            }
+            required_area = required_area.saturating_add(item.w.saturating_mul(item.h));
+            // Only items which are wide (or tall) in every allowed orientation count
+            if orientations.iter().all(|&(w, _)|w > width / 2) {
+                wide_height = wide_height.saturating_add(orientations.iter().map(|&(_, h)|h).min().unwrap_or(0));
+            }
+            if orientations.iter().all(|&(_, h)|h > height / 2) {
+                tall_width = tall_width.saturating_add(orientations.iter().map(|&(w, _)|w).min().unwrap_or(0));
+            }
+        }
+        let available = width.saturating_mul(height) - self.bitmap.count_occupied(0, self.watermark, width, height);
+        if required_area > available {
+            return Feasibility::Impossible(Infeasibility::Area { required: required_area, available });
+        }
+        if wide_height > height {
+            return Feasibility::Impossible(Infeasibility::WideItems { required: wide_height, available: height });
+        }
+        if tall_width > width {
+            return Feasibility::Impossible(Infeasibility::TallItems { required: tall_width, available: width });
+        }
+        Feasibility::Possible
+    }
+
     /// Create a new bin width the given horizontal width and vertical height.
+    ///
+    /// Very large bins (more than 2^26 cells) store their occupancy as 64x64 tiles, where
+    /// completely free or completely occupied tiles use no extra memory. For such bins,
+    /// 'get_largest_hole' returns the largest free rectangle, as measured by the metric.
     pub fn new(width: usize, height: usize) -> Bin<I> {
+        Bin::with_backend(width, height, Backend::Auto)
+    }
+
+    /// Create a new bin, using the given backend to keep track of occupied space.
+    /// The results of the free rectangle backend may differ slightly from those of the others,
+    /// since it only evaluates positions in the corners of free rectangles.
+    /// Panics if the number of cells (or tiles) of the bin overflows, in which case 'FreeRects' should be used.
+    pub fn with_backend(width: usize, height: usize, backend: Backend) -> Bin<I> {
         Bin {
-            bitmap: Bitmap2d::new(width,height),
+            bitmap: Bitmap2d::with_backend(width, height, backend),
             items: vec![],
             largest_hole: Hole {
                 width, height
             },
-            metric: |hole|hole.default_area(),
+            metric: Arc::new(|hole: Hole|hole.default_area()),
+            named_metrics: vec![],
+            autosave: None,
+            stats: PackingStats::default(),
+            preferences: vec![],
+            id_eq: None,
+            regions: vec![],
+            forbidden: vec![],
+            default_allow_rotate: true,
+            zones: vec![],
+            watermark: 0,
+            extent: None,
+            reserved: vec![],
+            placement_index: RefCell::new(None),
+            rejections: vec![],
+            candidate_counter: Cell::new(CandidateCounts::default()),
+            candidate_counts: vec![],
+            unloading: None,
+            item_order: None,
+            weights: None,
         }
     }
 
-    fn calculate_largest_hole(&self) -> Hole {
-        let offshore_map = RefCell::new(vec![]);
-        for bit in self.bitmap.bits.iter() {
-            offshore_map.borrow_mut().push(if bit {0} else {u32::MAX});
-        }
+    /// Create a bin for the stock described by 'preset', with the margins reserved (see
+    /// 'reserve_rows') and its default for rotation. Pack it using `Preset::options`, which
+    /// keeps the kerf free between items.
+    pub fn from_preset(preset: &Preset) -> Bin<I> {
+        presets::bin(preset)
+    }
 
-        let get = |x:isize,y:isize| -> Option<u32>{
-            let cx = x as usize;
-            let cy = y as usize;
-            if cx < self.bitmap.width && cy< self.bitmap.height {
-                return Some(offshore_map.borrow()[cy*self.bitmap.width+cx]);
-            }
-            None
-        };
-        let set = |x:usize,y:usize,val:u32| {
-            debug_assert!(x < self.bitmap.width && y< self.bitmap.height);
-            offshore_map.borrow_mut()[y*self.bitmap.width+x] = val;
-        };
+    /// Create a bin for a partially used sheet, for instance from a camera scan. 'bits' tells
+    /// whether each cell is occupied, row by row. The occupied cells are reserved (see
+    /// 'reserved_areas'), so they stay occupied when the bin is packed.
+    ///
+    /// Panics if 'bits' does not have 'width' x 'height' elements. See also [`largest_hole`].
+    pub fn from_occupancy_bitmap(width: usize, height: usize, bits: &[bool]) -> Bin<I> {
+        occupancy::from_occupancy_bitmap(width, height, bits)
+    }
 
+    /// Like 'from_occupancy_bitmap', with a cell for each pixel of 'image', which is occupied if
+    /// its brightness is below 'threshold'. Invert the image first if the used parts of the
+    /// sheet are the bright ones.
+    #[cfg(feature = "image")]
+    pub fn from_luma_image(image: &image::DynamicImage, threshold: u8) -> Bin<I> {
+        occupancy::from_luma_image(image, threshold)
+    }
 
-        let mut dist = 0;
-        loop {
-            let mut no_progress = true;
-            let nextdist= dist + 1 ;
-            for y in 0..self.bitmap.height {
-                for x in 0..self.bitmap.width {
-                    if get(x as isize,y as isize).unwrap() != u32::MAX {
-                        continue;
-                    }
-                    'inner: for dy in -1..=1 {
-                        for dx in -1..=1 {
-                            let cy = y as isize + dy;
-                            let cx = x as isize + dx;
-                            let mapdist = get(cx,cy).unwrap_or(0);
-                            if mapdist == dist {
-                                set(x,y, nextdist);
-                                no_progress = false;
-                                break 'inner;
-                            }
-                        }
-                    }
-                }
-            }
+    /// Create a bin of the given size, containing the given already placed items.
+    /// This can be used to resume packing from a solution saved earlier, for
+    /// instance using 'set_autosave'. Calls to 'place_all' will add to the given items.
+    ///
+    /// Returns an error if any item is empty, extends outside the bin, overlaps earlier items
+    /// by more cells than given by its 'overlap' field, or violates margins.
+    pub fn from_solution(width: usize, height: usize, items: impl IntoIterator<Item=PlacedItem<I>>) -> Result<Bin<I>, SolutionError> {
+        let mut bin = Bin::new(width, height);
+        bin.load_solution(items)?;
+        Ok(bin)
+    }
 
-            if no_progress {
-                break;
+    /// Add the given placed items to the bin, checking them like 'from_solution' does.
+    /// The index in an error is that within 'items'. On error, the bin is left partially loaded.
+    fn load_solution(&mut self, items: impl IntoIterator<Item=PlacedItem<I>>) -> Result<(), SolutionError> {
+        let (width, height) = (self.width(), self.height());
+        for (index, item) in items.into_iter().enumerate() {
+            if item.x1 <= item.x0 || item.y1 <= item.y0 {
+                return Err(SolutionError::Empty(index));
+            }
+            if item.x1 > width || item.y1 > height {
+                return Err(SolutionError::OutOfBounds(index));
+            }
+            let (w, h) = (item.width(), item.height());
+            if self.bitmap.count_occupied(item.x0, item.y0, w, h) > item.overlap {
+                return Err(SolutionError::Overlap(index));
+            }
+            let rect = Rectangle { x0: item.x0, y0: item.y0, x1: item.x1, y1: item.y1 };
+            if self.items.iter().any(|x|x.violates_margins(&rect) || item.violates_margins(&Rectangle { x0: x.x0, y0: x.y0, x1: x.x1, y1: x.y1 })) {
+                return Err(SolutionError::Margin(index));
             }
-            dist = nextdist;
+            self.bitmap.fill(item.x0, item.y0, w, h, true);
+            self.items.push(item);
         }
-        if dist == 0 {
-            return Hole{width:0,height:0};
+        self.items_changed();
+        self.update_holes(false);
+        Ok(())
+    }
+
+    /// Mark the rows above 'y' (i.e. rows 0 to y-1) as used, for instance because that part
+    /// of a roll of material has already been consumed. Items are never placed above the watermark.
+    ///
+    /// The watermark can only be raised. Setting a lower watermark than the current one has no
+    /// effect. Panics if 'y' is larger than the height of the bin.
+    pub fn set_watermark(&mut self, y: usize) {
+        if y > self.height() {
+            panic!("Watermark must not be below the bottom of the bin");
         }
-        let mut candidates = vec![];
-        for y in 0..self.bitmap.height {
-            for x in 0..self.bitmap.width {
-                if get(x as isize,y as isize).unwrap() != dist {
-                    continue;
-                }
-                candidates.push(Rect{x0:x,y0:y,x1:x,y1:y});
-            }
+        if y > self.watermark {
+            self.bitmap.fill(0, self.watermark, self.width(), y - self.watermark, true);
+            self.watermark = y;
+            self.update_holes(false);
         }
-        let mut biggest_hole = Hole {
-            width: 0,
-            height: 0,
-        };
-        let mut biggest_area = 0;
+    }
 
+    /// The watermark set using 'set_watermark', or 0 if none has been set
+    pub fn watermark(&self) -> usize {
+        self.watermark
+    }
 
-        for mut rect in candidates {
-            loop {
-                let mut progress = false;
-                let dirs ;
-                if self.measure(rect.grow_right().hole()) > self.measure(rect.grow_down().hole()) {
-                    dirs = [true, false];
-                } else {
-                    dirs = [false, true];
-                }
-                for horiz in dirs {
-                    if horiz {
-                        if rect.left_neighbors().map(|x|x.is_obstructed(&self.bitmap)).unwrap_or(true) == false {
-                            progress = true;
-                            rect = rect.grow_left();
-                            break;
-                        }
-                        if rect.right_neighbors(self.bitmap.width).map(|x|x.is_obstructed(&self.bitmap)).unwrap_or(true) == false {
-                            progress = true;
-                            rect = rect.grow_right();
-                            break;
-                        }
-                    } else {
-                        if rect.top_neighbors().map(|x|x.is_obstructed(&self.bitmap)).unwrap_or(true) == false {
-                            progress = true;
-                            rect = rect.grow_up();
-                            break;
-                        }
-                        if rect.bottom_neighbors(self.bitmap.height).map(|x|x.is_obstructed(&self.bitmap)).unwrap_or(true) == false {
-                            progress = true;
-                            rect = rect.grow_down();
-                            break;
-                        }
-                    }
-                }
+    /// Only place items within the columns `0..max_x` and rows `0..max_y`, for instance because
+    /// only the first part of a roll should be used today. Unlike a smaller bin, the holes and
+    /// statistics still cover the whole bin, and the coordinates are unchanged. The limit
+    /// replaces any earlier one; limiting to the size of the bin removes it.
+    pub fn limit_extent(&mut self, max_x: usize, max_y: usize) {
+        self.extent = Some((max_x, max_y));
+    }
 
-                if !progress {
-                    break;
-                }
-            }
-            let metric = self.measure(rect.hole());
-            if metric > biggest_area {
-                biggest_area = metric;
-                biggest_hole = rect.hole();
-            }
+    /// The limit set using 'limit_extent', or the size of the bin if none has been set
+    pub fn extent_limit(&self) -> (usize, usize) {
+        let (max_x, max_y) = self.extent.unwrap_or((usize::MAX, usize::MAX));
+        (max_x.min(self.width()), max_y.min(self.height()))
+    }
+
+    /// Mark the given rows as unusable over the whole width of the bin, for instance because
+    /// of grippers holding the material. Items are never placed on reserved cells. Items
+    /// already placed there are kept. Panics if the rows extend past the bottom of the bin.
+    pub fn reserve_rows(&mut self, rows: Range<usize>) {
+        if rows.end > self.height() {
+            panic!("Reserved rows must be inside the bin");
         }
-        biggest_hole
+        self.reserve(Reservation::Rows(rows));
+        self.update_holes(false);
     }
 
-    fn measure(&self, hole: Hole) -> usize {
-        (self.metric)(hole)
+    /// Mark the given columns as unusable over the whole height of the bin, like 'reserve_rows'.
+    /// Panics if the columns extend past the right edge of the bin.
+    pub fn reserve_cols(&mut self, cols: Range<usize>) {
+        if cols.end > self.width() {
+            panic!("Reserved columns must be inside the bin");
+        }
+        self.reserve(Reservation::Cols(cols));
+        self.update_holes(false);
     }
 
-    /// Determine how sizes of holes are measured, for the feature
-    /// 'get_largest_hole' that returns the largest non-occupied area in a bin.
-    /// Default is area (width * height).
-    ///
-    /// Must be called _before_ 'place_all', to have any effect
-    pub fn set_metric(&mut self, metric: fn(Hole)->usize) {
-        self.metric = metric;
+    /// Reserve a stripe of 'width' rows at the start of every 'n' rows, i.e. rows `0..width`,
+    /// `n..n + width`, and so on, for instance for sprocket holes at regular intervals along
+    /// a roll. The last stripe is cut off at the bottom of the bin. Panics if 'n' is 0.
+    pub fn reserve_every(&mut self, n: usize, width: usize) {
+        if n == 0 {
+            panic!("Reserved stripes must be at least one row apart");
+        }
+        for y in (0..self.height()).step_by(n) {
+            self.reserve(Reservation::Rows(y..y.saturating_add(width).min(self.height())));
+        }
+        self.update_holes(false);
     }
 
-    /// Return the largest free area available after the most recent successful or unsuccessful
-    /// 'place_all'.
-    pub fn get_largest_hole(&self) -> Hole {
-        self.largest_hole
+    /// Block the cells of a repeating pattern, such as the holes of a vacuum table, like
+    /// 'reserve_rows' does. This is the same as reserving each block, but cheaper to set up,
+    /// and the blocks are repeated into the added area when the bin grows.
+    /// Panics if the pitch of the pattern is 0.
+    pub fn block_pattern(&mut self, pattern: BlockPattern) {
+        pattern.check();
+        self.reserve(Reservation::Pattern(pattern));
+        self.update_holes(false);
     }
 
-    /// Place all the items given by the iterator 'items'.
-    /// Returns true if all items could be placed.
-    /// The solution can be retrieved by calling the 'solution'-method.
-    /// Note that this library does not in general produce optimal solutions.
-    pub fn place_all(&mut self, input: impl Iterator<Item=Item<I>>, mut cancel: impl FnMut() -> bool) -> bool {
-        let mut input_items:Vec<Item<I>> = input.collect();
-        input_items.sort_by_key(|x|Reverse(x.size()));
-        let any_rotatable = input_items.iter().any(|x|x.allow_rotate);
-        if self.place_all_impl(&input_items, Strategy::DoNotRotate, &mut cancel) {
-            self.largest_hole = self.calculate_largest_hole();
-            return true;
+    /// Add a reservation, without updating the holes
+    fn reserve(&mut self, reservation: Reservation) {
+        let rects: Vec<Rectangle> = reservation.rects(self.width(), self.height()).into_iter().filter(|x|x.x0 < x.x1 && x.y0 < x.y1).collect();
+        if !rects.is_empty() {
+            for rect in rects {
+                self.bitmap.fill(rect.x0, rect.y0, rect.width(), rect.height(), true);
+            }
+            self.reserved.push(reservation);
         }
-        self.largest_hole = self.calculate_largest_hole();
-        if !any_rotatable {
-            return false; //No point in trying passes where rotation is allowed, since none of the items allow rotation
+    }
+
+    /// The areas reserved using 'reserve_rows', 'reserve_cols', 'reserve_every', 'block_pattern'
+    /// and 'from_occupancy_bitmap', in the order they were reserved. They may overlap each other.
+    pub fn reserved_areas(&self) -> Vec<Rectangle> {
+        self.reserved.iter().flat_map(|x|x.rects(self.width(), self.height())).filter(|x|x.x0 < x.x1 && x.y0 < x.y1).collect()
+    }
+
+    /// Mark the reserved areas as occupied, after the bitmap has been rebuilt
+    fn fill_reserved(&mut self) {
+        for rect in self.reserved_areas() {
+            self.bitmap.fill(rect.x0, rect.y0, rect.width(), rect.height(), true);
         }
-        if cancel() {
-            return false;
+    }
+
+    /// Require that items can be unloaded in delivery order, through 'edge'. 'sequence' gives
+    /// the position of an item in the delivery order, given its id. An item is never placed
+    /// such that an item which is delivered later lies between it and 'edge', nor such that
+    /// it lies between 'edge' and an item which is delivered earlier.
+    ///
+    /// Items are still placed largest first, so this works best together with a scan order
+    /// starting at the edge opposite to 'edge', which places the items placed first furthest
+    /// from it. Items with equal positions in the delivery order don't constrain each other.
+    pub fn set_unloading_order(&mut self, edge: Edge, sequence: impl Fn(&I) -> usize + Send + Sync + 'static) {
+        self.unloading = Some((edge, Arc::new(sequence)));
+    }
+
+    /// Pack items in the order given by 'compare' instead of the default order (mandatory
+    /// items before optional ones, each largest first). For instance, sorting by order number
+    /// first keeps the items of an order together.
+    ///
+    /// Sorting is stable, so items which compare equal keep their relative input order, after
+    /// shuffling if a seed or random number generator is set in the options. Not used by
+    /// `MultiBin::place_all_parallel`.
+    pub fn set_item_order(&mut self, compare: impl Fn(&Item<I>, &Item<I>) -> Ordering + Send + Sync + 'static) {
+        self.item_order = Some(Arc::new(compare));
+    }
+
+    /// Set whether items which don't specify 'allow_rotate' (i.e. where it is None)
+    /// may be rotated. Default is true.
+    pub fn set_default_allow_rotate(&mut self, allow_rotate: bool) {
+        self.default_allow_rotate = allow_rotate;
+    }
+
+    /// Declare a zone of the bin with the given priority. Items are always placed in
+    /// the lowest priority zone possible, which makes it possible to, for instance, fill
+    /// the left half of a sheet before starting on the right half. Cells which are not in any
+    /// zone have priority 0. A position overlapping several zones gets the highest of their priorities.
+    ///
+    /// The utilization of each zone is reported in `PackingStats::zone_utilization`. The zone
+    /// is clipped to the bin.
+    pub fn add_zone(&mut self, area: Rectangle, priority: usize) {
+        let (x1, y1) = (area.x1.min(self.width()), area.y1.min(self.height()));
+        let area = Rectangle { x0: area.x0.min(x1), y0: area.y0.min(y1), x1, y1 };
+        self.zones.push((area, priority));
+    }
+
+    /// The fraction of the bin which is occupied, including any rows above the watermark
+    pub fn utilization(&self) -> f64 {
+        let occupied = self.bitmap.count_occupied(0, 0, self.width(), self.height());
+        occupied as f64 / (self.width() as f64 * self.height() as f64)
+    }
+
+    /// The fraction of each zone (see 'add_zone') which is occupied, in the order
+    /// the zones were added.
+    pub fn zone_utilization(&self) -> Vec<f64> {
+        self.zones.iter().map(|(zone, _)| {
+            let occupied = self.bitmap.count_occupied(zone.x0, zone.y0, zone.width(), zone.height());
+            occupied as f64 / zone.area().max(1) as f64
+        }).collect()
+    }
+
+    /// Set the weight of each cell, for the weighted utilization (see 'weighted_utilization'),
+    /// which is reported in `PackingStats::weighted_utilization`. The map may be shared
+    /// between bins. It does not affect where items are placed.
+    pub fn set_weight_map(&mut self, weights: impl Into<Arc<WeightMap>>) {
+        self.weights = Some(weights.into());
+    }
+
+    /// The map set using 'set_weight_map', if any
+    pub fn weight_map(&self) -> Option<&WeightMap> {
+        self.weights.as_deref()
+    }
+
+    /// The total weight of the occupied cells divided by the total weight of all cells, using
+    /// the map set using 'set_weight_map'. None if no map is set.
+    pub fn weighted_utilization(&self) -> Option<f64> {
+        weights::weighted_utilization(self)
+    }
+
+    /// Declare that the items with ids 'a' and 'b' should preferably be placed close to each other,
+    /// for instance because they belong to the same sub-assembly.
+    ///
+    /// When an item is placed after its partner, 'weight' times the distance between the two items
+    /// (the sum of the horizontal and vertical gaps) is added to the score of each position.
+    /// The preference applies to all items with the given ids.
+    pub fn prefer_near(&mut self, a: I, b: I, weight: usize) where I: PartialEq {
+        self.id_eq = Some(<I as PartialEq>::eq);
+        self.preferences.push((a, b, weight));
+    }
+
+    /// Only allow the items with id 'id' to be placed entirely inside one of 'regions', for
+    /// instance the temperature zones of a press which the item may be processed in. Calling
+    /// this again for the same id adds more regions. Items without regions may be placed
+    /// anywhere.
+    pub fn restrict_to_regions(&mut self, id: I, regions: impl IntoIterator<Item=Rectangle>) where I: PartialEq {
+        self.id_eq = Some(<I as PartialEq>::eq);
+        self.regions.push((id, regions.into_iter().collect()));
+    }
+
+    /// Do not allow the items with id 'id' to intersect any of 'regions', for instance a branded
+    /// corner of the sheet which must not end up on customer-facing parts. Unlike areas which
+    /// are blocked for all items, other items may still be placed there. Calling this again
+    /// for the same id adds more regions.
+    pub fn forbid_regions(&mut self, id: I, regions: impl IntoIterator<Item=Rectangle>) where I: PartialEq {
+        self.id_eq = Some(<I as PartialEq>::eq);
+        self.forbidden.push((id, regions.into_iter().collect()));
+    }
+
+    /// Check that every item of the solution lies inside its regions set using
+    /// 'restrict_to_regions', and outside its regions set using 'forbid_regions'. This is
+    /// always the case for items placed by this library, but not necessarily for solutions
+    /// loaded using 'from_solution', or placed before the regions were set.
+    pub fn verify_regions(&self) -> Result<(), SolutionError> {
+        match self.items.iter().position(|x|!self.regions_allow(&x.id, &x.rect())) {
+            Some(index) => Err(SolutionError::Region(index)),
+            None => Ok(()),
         }
-        self.items.clear();
-        self.bitmap.clear();
-        if self.place_all_impl(&input_items, Strategy::Rotate, &mut cancel) {
-            self.largest_hole = self.calculate_largest_hole();
+    }
+
+    /// True if an item with id 'id' may be placed at 'rect', according to the regions set
+    /// using 'restrict_to_regions' and 'forbid_regions'
+    fn regions_allow<'a>(&'a self, id: &'a I, rect: &Rectangle) -> bool {
+        let Some(id_eq) = self.id_eq.filter(|_|!self.regions.is_empty() || !self.forbidden.is_empty()) else {
             return true;
+        };
+        let matching = |regions: &'a [(I, Vec<Rectangle>)]| regions.iter().filter(move |(x, _)|id_eq(x, id)).flat_map(|(_, regions)|regions);
+        let mut allowed = matching(&self.regions).peekable();
+        let inside = allowed.peek().is_none() || allowed.any(|region|region.contains(rect));
+        inside && !matching(&self.forbidden).any(|region|region.intersects(rect))
+    }
+
+    /// Register a callback which is invoked after every placement, with all items placed so far.
+    /// This can be used to save the partial solution of a long running pack, for instance
+    /// using [`write_solution`], so that it can be resumed using 'from_solution' if the
+    /// process is interrupted.
+    ///
+    /// Note that 'place_all' may restart packing from scratch with a different strategy,
+    /// in which case the partial solution shrinks again.
+    pub fn set_autosave(&mut self, autosave: impl FnMut(&[PlacedItem<I>]) + Send + 'static) {
+        self.autosave = Some(Arc::new(Mutex::new(autosave)));
+    }
+
+    /// Call the callback registered using 'set_autosave', if any, with the items placed so far
+    fn run_autosave(&self) {
+        if let Some(autosave) = &self.autosave {
+            (autosave.lock().unwrap_or_else(PoisonError::into_inner))(&self.items);
         }
-        let new_largest_hole = self.calculate_largest_hole();
-        if self.measure(new_largest_hole) > self.measure(self.largest_hole) {
-            self.largest_hole = new_largest_hole;
+    }
+
+    /// Recalculate the largest hole according to each metric. If 'keep_larger' is set,
+    /// holes are only replaced by larger ones.
+    fn update_holes(&mut self, keep_larger: bool) {
+        let largest_hole = self.calculate_largest_hole(&*self.metric);
+        if !keep_larger || self.measure(largest_hole) > self.measure(self.largest_hole) {
+            self.largest_hole = largest_hole;
         }
-        if cancel() {
-            return false;
+        for index in 0..self.named_metrics.len() {
+            let metric = self.named_metrics[index].1.clone();
+            let hole = self.calculate_largest_hole(&*metric);
+            let current = &mut self.named_metrics[index].2;
+            if !keep_larger || metric(hole) > metric(*current) {
+                *current = hole;
+            }
         }
-        self.items.clear();
-        self.bitmap.clear();
-        let placed = self.place_all_impl(&input_items, Strategy::RotateIfSuitable, &mut cancel);
-        let new_largest_hole = self.calculate_largest_hole();
-        if self.measure(new_largest_hole) > self.measure(self.largest_hole) {
-            self.largest_hole = new_largest_hole;
+    }
+
+    fn calculate_largest_hole(&self, metric: &dyn Fn(Hole) -> usize) -> Hole {
+        largest_hole_in(&self.bitmap, metric)
+    }
+
+    fn measure(&self, hole: Hole) -> usize {
+        (self.metric)(hole)
+    }
+
+    /// Determine how sizes of holes are measured, for the feature
+    /// 'get_largest_hole' that returns the largest non-occupied area in a bin.
+    /// Default is area (width * height).
+    ///
+    /// Must be called _before_ 'place_all', to have any effect
+    pub fn set_metric(&mut self, metric: impl Fn(Hole)->usize + Send + Sync + 'static) {
+        self.metric = Arc::new(metric);
+    }
+
+    /// Return the largest free area available after the most recent successful or unsuccessful
+    /// 'place_all'.
+    pub fn get_largest_hole(&self) -> Hole {
+        self.largest_hole
+    }
+
+    /// Also keep track of the largest hole according to 'metric', in addition to the one set
+    /// using 'set_metric'. The hole can be retrieved using 'largest_hole_by' with the same name.
+    /// Adding a metric with the name of an existing one replaces it.
+    pub fn add_metric(&mut self, name: impl Into<String>, metric: impl Fn(Hole)->usize + Send + Sync + 'static) {
+        let name = name.into();
+        let metric: Metric = Arc::new(metric);
+        let hole = self.calculate_largest_hole(&*metric);
+        self.named_metrics.retain(|(x, _, _)|*x != name);
+        self.named_metrics.push((name, metric, hole));
+    }
+
+    /// A metric which measures holes by how well they suit 'item': the area of holes which
+    /// the item fits in, in either orientation if it may be rotated, and 0 for other holes.
+    /// Add it using 'add_metric' to find the largest hole the next item can actually use:
+    ///
+    /// ```
+    /// use bin_packing_2d::prelude::*;
+    /// let mut bin = Bin::new(10, 4);
+    /// assert!(bin.pack([Item::new(6, 4, 'A')]));
+    /// let next = Item::new(2, 4, 'B');
+    /// bin.add_metric("next", bin.metric_for_item(&next));
+    /// let hole = bin.largest_hole_by("next").unwrap();
+    /// assert!(hole.fits(next.w, next.h));
+    /// ```
+    pub fn metric_for_item(&self, item: &Item<I>) -> impl Fn(Hole)->usize + Send + Sync + 'static {
+        let (w, h, rotatable) = (item.w, item.h, self.allows_rotation(item));
+        move |hole: Hole| {
+            let fits = if rotatable { hole.fits_rotatable(w, h) } else { hole.fits(w, h) };
+            if fits { hole.default_area() } else { 0 }
+        }
+    }
+
+    /// The largest free area according to the metric added as 'name' using 'add_metric', after
+    /// the most recent 'place_all'. None if no such metric has been added.
+    pub fn largest_hole_by(&self, name: &str) -> Option<Hole> {
+        self.named_metrics.iter().find(|(x, _, _)|x == name).map(|(_, _, hole)|*hole)
+    }
+
+    /// Return all maximal free rectangles in the bin. A free rectangle is maximal if it
+    /// cannot be extended in any direction without overlapping a placed item or
+    /// leaving the bin. Note that the rectangles may overlap each other.
+    pub fn free_rectangles(&self) -> Vec<Rectangle> {
+        free_rects::maximal_free_rects(&self.bitmap)
+    }
+
+    /// Return the occupancy of the bin, run-length encoded.
+    /// The returned vector contains one element per row, each of which is
+    /// the list of alternating free and occupied runs covering the row from left to right.
+    pub fn occupancy_rle(&self) -> Vec<Vec<Run>> {
+        (0..self.bitmap.height).map(|y|self.bitmap.row_runs(y)).collect()
+    }
+
+    /// The widest run of free cells in row 'y', the leftmost if there are several. None if
+    /// the row is fully occupied, or 'y' is outside the bin.
+    ///
+    /// The free runs of each queried row are kept up to date as items are placed, so repeated
+    /// queries, for instance to find where something can be printed between already placed
+    /// items, are cheap.
+    pub fn widest_free_span(&self, y: usize) -> Option<Run> {
+        if y >= self.height() {
+            return None;
         }
-        placed
+        self.bitmap.widest_free_span(y).map(|(x0, x1)|Run { x0, x1, occupied: false })
+    }
+
+    /// A canonical text summary of the layout, suitable for committing as a fixture in
+    /// regression tests: the bin size, the packing counts, the utilization, a stable hash of
+    /// the occupancy, and one line per placed item, ordered by position. Compare it to the
+    /// expected one using [`compare_fingerprints`]. The `Display` representation of the
+    /// ids must not contain line breaks.
+    pub fn fingerprint(&self) -> String where I: std::fmt::Display {
+        fingerprint::fingerprint(self)
     }
-    fn place_all_impl(&mut self, items: &[Item<I>], strategy: Strategy, mut cancel: impl FnMut() -> bool) -> bool {
-        let mut all_fit = true;
-        for item in items {
-            if !self.add_to_best_fit(item, strategy, &mut cancel) {
-                all_fit = false;
+
+    /// The material consumed by each item of the solution, in the same order, when cut with
+    /// a kerf of 'spacing', for instance to allocate the cost of waste to the items. The kerf
+    /// between two items is split evenly between them; no kerf is attributed along the edges
+    /// of the bin. Other waste, such as unused space, is not included.
+    pub fn material_usage(&self, spacing: usize) -> Vec<MaterialUsage> {
+        kerf::material_usage(&self.items, self.width(), self.height(), spacing)
+    }
+
+    /// For each item of the solution, in the same order, the number of free cells within
+    /// 'halo' cells of it which are unusable, because they are not part of any free rectangle
+    /// the smallest placed item would fit in (in either orientation). Items which cause a lot
+    /// of such waste around them may be worth redesigning. Cells near several items are
+    /// counted for each of them.
+    pub fn wasted_neighborhood(&self, halo: usize) -> Vec<usize> {
+        waste::wasted_neighborhood(self, halo)
+    }
+
+    /// For each item of the solution, in the same order, the length of edge banding it needs,
+    /// such as for the visible edges of furniture panels. Which edges are banded is given by
+    /// 'rules' for each item; by default, parts of edges along the edges of the bin and along
+    /// reserved areas are not banded, since they are factory edges or scrap.
+    pub fn edge_banding(&self, rules: impl Fn(&PlacedItem<I>) -> BandingRules) -> Vec<EdgeBanding> {
+        banding::edge_banding(self, rules)
+    }
+
+    /// The total length of the cuts shared by adjacent items, where two items face each other
+    /// across a kerf of 'spacing', so that a single cut separates them. See also
+    /// `PlaceOptions::with_shared_cut_weight`.
+    pub fn shared_cut_length(&self, spacing: usize) -> usize {
+        kerf::shared_cut_length(&self.items, spacing)
+    }
+
+    /// The total kerf area attributed to the items in the bin, see 'material_usage'
+    fn kerf_area(&self, spacing: usize) -> f64 {
+        self.material_usage(spacing).iter().map(|x|x.kerf_area).sum()
+    }
+
+    /// Summarize the fragmentation of the free space, by counting the maximal
+    /// free rectangles (see 'free_rectangles') by area.
+    ///
+    /// 'buckets' are the ascending lower area limits of the buckets after the first.
+    /// The returned vector has one more element than 'buckets'. Element 0 is the number of
+    /// rectangles with an area smaller than `buckets[0]`, element 1 the number with an area
+    /// of at least `buckets[0]` but smaller than `buckets[1]`, and so on.
+    pub fn hole_histogram(&self, buckets: &[usize]) -> Vec<usize> {
+        free_rects::histogram(&self.free_rectangles(), buckets)
+    }
+
+    /// Place all the items given by the iterator 'items'.
+    /// Returns true if all items could be placed, apart from optional ones (see 'Item::optional').
+    /// The solution can be retrieved by calling the 'solution'-method.
+    /// Note that this library does not in general produce optimal solutions.
+    ///
+    /// Items are placed largest first, or in the order set using 'set_item_order'. Items
+    /// of the same size are placed in the order they are given.
+    ///
+    /// See [`PlaceOptions`] for the available options. If a final search is enabled
+    /// using `PlaceOptions::with_final_search`, it runs after the last pass.
+    pub fn place_all(&mut self, input: impl Iterator<Item=Item<I>>, options: PlaceOptions) -> bool {
+        let mut packer = self.pack_stepwise(input, options);
+        packer.by_ref().for_each(drop);
+        let all_fit = if packer.result() == Some(true) || packer.bin().stats.cancelled {
+            packer.result() == Some(true)
+        } else {
+            let placed = packer.placed().to_vec();
+            let (mut options, items) = packer.into_parts();
+            match options.final_search() {
+                Some((max_items, budget)) => completion::complete(self, &items, &placed, max_items, budget, &mut options),
+                None => false,
             }
-            if cancel() {
-                return false;
+        };
+        #[cfg(debug_assertions)]
+        self.audit();
+        all_fit
+    }
+
+    /// Pack a one-dimensional instance, where every item spans the whole height of the bin
+    /// (or every item spans its whole width) in some allowed orientation, by laying the items
+    /// out side by side, longest first, skipping those which no longer fit. This is much faster
+    /// than 'place_all', and leaves no gaps between the items. Optional items are packed after
+    /// mandatory ones. Returns whether all mandatory items could be placed, like 'place_all'.
+    ///
+    /// Returns None without changing the bin if the instance is not one-dimensional, or if
+    /// the bin is not empty, items have margins, or items are restricted to regions.
+    pub fn pack_1d(&mut self, input: impl Iterator<Item=Item<I>>) -> Option<bool> {
+        let result = linear::pack_1d(self, input.collect());
+        #[cfg(debug_assertions)]
+        if result.is_some() {
+            self.audit();
+        }
+        result
+    }
+
+    /// Check the invariants maintained by the placement code: every item lies within the bin,
+    /// the cells it covers are occupied, and it overlaps the items placed before it by at most
+    /// its 'overlap'. This takes time proportional to the area of the items, so it is only
+    /// done in debug builds, after 'place_all'.
+    #[cfg(debug_assertions)]
+    fn audit(&self) {
+        let mut covered = Bitmap2d::with_backend(self.width(), self.height(), self.bitmap.backend());
+        for (index, item) in self.items.iter().enumerate() {
+            assert!(item.x0 < item.x1 && item.y0 < item.y1 && item.x1 <= self.width() && item.y1 <= self.height(), "Placed item #{} is outside the bin", index);
+            let (w, h) = (item.width(), item.height());
+            assert_eq!(self.bitmap.count_occupied(item.x0, item.y0, w, h), item.rect().area(), "Placed item #{} covers free cells", index);
+            assert!(covered.count_occupied(item.x0, item.y0, w, h) <= item.overlap, "Placed item #{} overlaps earlier items", index);
+            covered.fill(item.x0, item.y0, w, h, true);
+        }
+    }
+
+    /// Like 'place_all', but if not all items fit, also find out approximately how large the
+    /// bin would need to be for them to fit. This is done by packing the items again into
+    /// larger virtual bins, with the same contents and aspect ratio as this one, searching for
+    /// the smallest size (to within about 1%) that fits. This bin itself is only packed once.
+    ///
+    /// Finding the size may take many times as long as packing once, so the cancel callback
+    /// and deadline in 'options' also apply to the search.
+    pub fn place_all_with_outcome(&mut self, input: impl Iterator<Item=Item<I>>, options: PlaceOptions) -> PackOutcome {
+        // Largest size searched, as percentage added to each dimension
+        const MAX_GROWTH: usize = 1500;
+        let mut virtual_bin = self.clone();
+        virtual_bin.autosave = None;
+        let mut packer = self.pack_stepwise(input, options);
+        packer.by_ref().for_each(drop);
+        if packer.result() == Some(true) {
+            return PackOutcome::AllFit;
+        }
+        let (mut options, items) = packer.into_parts();
+        // None if the grown size overflows
+        let scaled = |growth: usize| Some((
+            self.width().checked_mul(100 + growth)?.div_ceil(100),
+            self.height().checked_mul(100 + growth)?.div_ceil(100),
+        ));
+        // Pack into a virtual bin grown by 'growth' percent. None if cancelled or too large.
+        let mut fits = |growth: usize| -> Option<bool> {
+            if options.is_cancelled() {
+                return None;
+            }
+            let (width, height) = scaled(growth)?;
+            let mut bin = virtual_bin.clone();
+            bin.grow(width, height);
+            let mut packer = PackIterator::new(&mut bin, items.iter().cloned(), std::mem::take(&mut options), false);
+            packer.by_ref().for_each(drop);
+            let result = packer.result();
+            options = packer.into_parts().0;
+            result.filter(|_| !options.was_cancelled())
+        };
+        // Double the growth until everything fits, then narrow it down
+        let (mut too_small, mut large_enough) = (0, 10);
+        loop {
+            match fits(large_enough) {
+                None => return PackOutcome::Partial { suggested_bin: None },
+                Some(true) => break,
+                Some(false) if large_enough >= MAX_GROWTH => return PackOutcome::Partial { suggested_bin: None },
+                Some(false) => (too_small, large_enough) = (large_enough, (large_enough * 2).min(MAX_GROWTH)),
             }
         }
-        all_fit
+        while large_enough - too_small > 1 {
+            let middle = (too_small + large_enough) / 2;
+            match fits(middle) {
+                None => break,
+                Some(true) => large_enough = middle,
+                Some(false) => too_small = middle,
+            }
+        }
+        PackOutcome::Partial { suggested_bin: scaled(large_enough) }
     }
 
-    fn place(&mut self, x0: usize, y0:usize, item: &Item<I>, rotated: bool) {
-        let w = if rotated {item.h} else {item.w};
-        let h = if rotated {item.w} else {item.h};
-        for y in y0..y0+h {
-            for x in x0..x0+w {
-                self.bitmap.set(x,y,true);
+    /// Pack 'items' in many different ways, and return the layouts which are not dominated by
+    /// any other, i.e. for which no other layout is at least as good in all of placed area,
+    /// cut length and number of rotated items (see [`LayoutScore`]). The layouts are sorted
+    /// by decreasing placed area, and this bin is not modified.
+    ///
+    /// Each layout is created by a single pass, using options created by 'options' with the
+    /// strategy, heuristic, scan order and seed replaced. Every combination of three strategies,
+    /// two heuristics and two scan orders is tried, for the input order and for 'seeds' shuffles
+    /// of equally sized items, so this takes about `12 * (seeds + 1)` times as long as a single pass.
+    /// If packing is cancelled, the layouts found so far are returned.
+    pub fn pareto_layouts<'o>(&self, items: &[Item<I>], options: impl Fn() -> PlaceOptions<'o>, seeds: u64) -> Vec<ParetoLayout<I>> {
+        pareto::pareto_layouts(self, items, &options, seeds)
+    }
 
+    /// Plan how to cut the given quantities of items from as few sheets as possible, where each
+    /// sheet is a copy of this bin. Each element of 'demand' is a [`Demand`], or a tuple of an
+    /// item and the number of copies needed of it.
+    ///
+    /// Rather than packing every sheet separately, layouts (patterns) are chosen greedily: in
+    /// each round, a few candidate patterns are packed from the items still needed, and the one
+    /// covering the most area is used on as many sheets as possible without cutting more items
+    /// than needed. The space left in the pattern is then filled with extra copies, as far as
+    /// allowed by 'max_overrun'. This is much faster than packing each sheet when quantities
+    /// are high, and tends to need fewer sheets. 'options' is called to create the options for
+    /// each packing.
+    pub fn cutting_plan<'o, D:Into<Demand<I>>>(&self, demand: impl IntoIterator<Item=D>, options: impl Fn() -> PlaceOptions<'o>) -> CuttingPlan<I> {
+        cutting_stock::cutting_plan(self, demand.into_iter().map(Into::into).collect(), &options)
+    }
+
+    /// Like 'place_all', but for groups of items which must be placed at fixed offsets from
+    /// each other (see [`Linked`]). Single items can be converted to groups using `Linked::from`.
+    ///
+    /// Each group is placed as if it were a single item the size of its bounding box, so
+    /// the spaces between its items are never used by other items of the same call. They may
+    /// be used by later calls. The items of a placed group are reported as separate placed
+    /// items, and a rejected group is reported by the id of its first item.
+    pub fn place_linked(&mut self, groups: impl IntoIterator<Item=Linked<I>>, mut options: PlaceOptions) -> bool {
+        let groups: Vec<Linked<I>> = groups.into_iter().collect();
+        let units: Vec<Item<I>> = groups.iter().map(|group|group.unit(group.items().all(|x|self.allows_rotation(x)))).collect();
+        // Sort like 'place_all' would, keeping track of which group each unit belongs to
+        let mut order: Vec<Item<usize>> = units.iter().enumerate().map(|(index, x)|x.with_id(index)).collect();
+        let compare = self.item_order.clone().map(|compare|{ let units = &units; move |a: &Item<usize>, b: &Item<usize>|compare(&units[a.id], &units[b.id]) });
+        sort_items(&mut order, &mut options, compare.as_ref().map(|x|x as _));
+        let initial_count = self.items.len();
+        let mut packer = PackIterator::new(self, order.iter().map(|x|units[x.id].clone()), options, false);
+        packer.by_ref().for_each(drop);
+        let placed = packer.placed().to_vec();
+        let all_fit = packer.result() == Some(true);
+        let units = self.items.split_off(initial_count);
+        let mut items = self.items.clone();
+        let placed_groups = order.iter().zip(placed).filter(|(_, placed)|*placed).map(|(x, _)|&groups[x.id]);
+        for (group, unit) in placed_groups.zip(units) {
+            items.extend(group.placed_items(unit.x0, unit.y0, unit.rotated()));
+        }
+        // Only the items of the groups are occupied, not the rest of their bounding boxes
+        let stats = self.stats.clone();
+        self.restore(items);
+        self.stats = stats;
+        self.run_autosave();
+        all_fit
+    }
+
+    /// Like 'place_all', but calls 'on_place' for each placed item as soon as it is certain
+    /// that the item will stay where it was placed. This makes it possible to start processing
+    /// the first items while later ones are still being packed.
+    ///
+    /// Items placed in the last pass are reported as soon as that pass has placed at least as
+    /// much as the best earlier pass, since cancelling would otherwise bring back the earlier
+    /// layout. Items placed in earlier passes are reported when packing has finished, since
+    /// otherwise the next pass starts over.
+    pub fn place_all_streaming(&mut self, input: impl Iterator<Item=Item<I>>, options: PlaceOptions, mut on_place: impl FnMut(&PlacedItem<I>)) -> bool {
+        let initial_count = self.items.len();
+        let mut packer = self.pack_stepwise(input, options);
+        let mut reported = 0;
+        // Items are only ever appended during a pass, so the reported items stay a prefix
+        let mut report = |solution: &[PlacedItem<I>]| {
+            solution[initial_count + reported..].iter().for_each(&mut on_place);
+            reported = solution.len() - initial_count;
+        };
+        while packer.next().is_some() {
+            if packer.placements_are_final() {
+                report(packer.solution());
             }
         }
+        report(packer.solution());
+        packer.result() == Some(true)
+    }
+
+    /// Place all items using the default options. Equivalent to
+    /// `place_all(input.into_iter(), PlaceOptions::default())`.
+    pub fn pack(&mut self, input: impl IntoIterator<Item=Item<I>>) -> bool {
+        self.place_all(input.into_iter(), PlaceOptions::default())
+    }
+
+    /// Run a single pass with the given strategy, adding the items to the bin in the order
+    /// given, without sorting them and without restarting if some items don't fit.
+    /// Any strategies set in 'options' are ignored.
+    /// Returns true if all items could be placed.
+    ///
+    /// Together with 'keep_best', this can be used to build custom retry pipelines:
+    ///
+    /// ```rust
+    /// use bin_packing_2d::prelude::*;
+    /// let items: Vec<_> = (1..8).map(|id| Item::rotatable(id, 8 - id, id)).collect();
+    /// let mut best = Bin::new(8, 8);
+    /// for strategy in [Strategy::DoNotRotate, Strategy::RotateIfSuitable] {
+    ///     let mut by_area = items.clone();
+    ///     by_area.sort_by_key(|x| std::cmp::Reverse(x.w * x.h));
+    ///     let mut attempt = Bin::new(8, 8);
+    ///     attempt.pack_pass(by_area, strategy, PlaceOptions::default());
+    ///     best.keep_best(attempt);
+    /// }
+    /// ```
+    pub fn pack_pass(&mut self, input: impl IntoIterator<Item=Item<I>>, strategy: Strategy, options: PlaceOptions) -> bool {
+        let mut packer = PackIterator::new(self, input, options.with_strategies([strategy]), false);
+        packer.by_ref().for_each(drop);
+        packer.result() == Some(true)
+    }
+
+    /// Remove the item with the given id, returning its placement, or None if it has not been
+    /// placed. If several placed items have the same id, the first one is removed. The other
+    /// items are not moved; see 'repack_region' to fill the space.
+    pub fn remove_item(&mut self, id: &I) -> Option<PlacedItem<I>> where I: PartialEq {
+        repack::remove_item(self, id)
+    }
+
+    /// Repack part of the bin: place 'items' within 'region', for instance to fill the gap
+    /// left after an operator removed a part (see 'remove_item'). Only the positions within
+    /// the region are evaluated, which is much faster than repacking the whole bin.
+    ///
+    /// Placed items intersecting the region which have the id of one of 'items' (typically
+    /// the items they were placed from) are removed first, and placed again as that item.
+    /// Other placed items are not moved. The items are placed largest first (or in the order
+    /// set using 'set_item_order'), in a single pass, rotated where this fits better.
+    ///
+    /// If one of the removed items does not fit again, the placed items are left where they
+    /// were, and only the other items are placed. Returns the items which did not fit; the
+    /// stats and rejections describe the repacking.
+    pub fn repack_region(&mut self, region: Rectangle, items: impl IntoIterator<Item=Item<I>>, options: PlaceOptions) -> Vec<Item<I>> where I: PartialEq {
+        repack::repack_region(self, region, items.into_iter().collect(), options)
+    }
+
+    /// Like 'place_all', but starting from 'seed', a known good layout of some of the items, for
+    /// instance from an earlier order or made by hand. The seed is added to the bin, and only
+    /// the remaining items are packed, which is usually much faster than packing from scratch.
+    /// If not all of them fit, the items are also packed from scratch, and the better of the
+    /// two solutions (as defined by 'keep_best') is kept.
+    ///
+    /// Each seed item must match a different item of 'input', by id and size, and must be
+    /// allowed to be rotated if it is. The seed is checked like in 'from_solution', and against
+    /// the regions of the items. If it is not valid, an error is returned and the bin is unchanged.
+    /// Otherwise, returns whether all items were placed.
+    pub fn place_all_seeded<'o>(&mut self, input: impl IntoIterator<Item=Item<I>>, seed: impl IntoIterator<Item=PlacedItem<I>>, options: impl Fn() -> PlaceOptions<'o>) -> Result<bool, SolutionError> where I: PartialEq {
+        seeded::place_all_seeded(self, input.into_iter().collect(), seed.into_iter().collect(), &options)
+    }
+
+    /// Replace the contents of this bin with those of 'other', if 'other' contains a better solution.
+    /// A solution is better if it contains more items, or the same number of items covering a larger
+    /// area, or failing that, a larger largest hole (see 'get_largest_hole').
+    /// Returns true if this bin was replaced.
+    pub fn keep_best(&mut self, other: Bin<I>) -> bool {
+        let quality = |bin: &Bin<I>| {
+            let (count, area) = bin.placed_quality();
+            (count, area, bin.measure(bin.largest_hole))
+        };
+        if quality(&other) > quality(self) {
+            *self = other;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The number of placed items and their total area, which is how layouts are compared
+    /// before looking at the free space
+    pub(crate) fn placed_quality(&self) -> (usize, usize) {
+        (self.items.len(), self.items.iter().map(|x|x.rect().area()).fold(0, usize::saturating_add))
+    }
+
+    /// Like 'place_all', but returns an iterator which places one item each time 'next' is
+    /// called. The partial solution can be inspected between steps. Packing is finished
+    /// when the iterator returns None.
+    ///
+    /// ```rust
+    /// use bin_packing_2d::prelude::*;
+    /// let mut bin = Bin::new(10, 10);
+    /// let items = (0..4).map(|id| Item::new(5, 5, id).with_allow_rotate(false));
+    /// let mut packer = bin.pack_stepwise(items, PlaceOptions::default());
+    /// while let Some(step) = packer.next() {
+    ///     println!("{:?}, {} items placed so far", step, packer.solution().len());
+    /// }
+    /// assert_eq!(packer.result(), Some(true));
+    /// ```
+    pub fn pack_stepwise<'o>(&mut self, input: impl IntoIterator<Item=Item<I>>, options: PlaceOptions<'o>) -> PackIterator<'_, 'o, I> {
+        PackIterator::new(self, input, options, true)
+    }
+
+    /// Place 'item' with its top left corner at 'x0,y0'. At most 'allowed_overlap' of the cells
+    /// may already be occupied, which is checked in debug builds.
+    fn place(&mut self, x0: usize, y0:usize, item: &Item<I>, rotated: bool, allowed_overlap: usize) {
+        let w = if rotated {item.h} else {item.w};
+        let h = if rotated {item.w} else {item.h};
+        debug_assert!(x0 < self.width() && w <= self.width() - x0 && y0 < self.height() && h <= self.height() - y0,
+            "Item placed outside the bin");
+        debug_assert!(y0 >= self.watermark, "Item placed above the watermark");
+        let overlap = self.bitmap.count_occupied(x0, y0, w, h);
+        debug_assert!(overlap <= allowed_overlap, "Item placed on {} occupied cells", overlap);
+        self.bitmap.fill(x0, y0, w, h, true);
+        self.items_changed();
         self.items.push(PlacedItem{
             x0,
             y0,
             x1: x0+w,
             y1: y0+h,
-            rotated,
+            rotation: Rotation::quarter_turn(rotated),
+            overlap,
+            margins: item.placed_margins(rotated),
+            tags: item.tags,
             id: item.id.clone(),
         });
+        self.run_autosave();
     }
-    fn evaluate_fit(&self, x0: usize, y0: usize, w: usize, h: usize) -> Option<usize> {
-        if x0 >= self.bitmap.width || y0 >= self.bitmap.height || x0 + w > self.bitmap.width || y0 + h > self.bitmap.height {
+    fn evaluate_fit(&self, x0: usize, y0: usize, item: &Item<I>, rotated: bool, options: &PlaceOptions) -> Option<Fit> {
+        let (w, h) = if rotated { (item.h, item.w) } else { (item.w, item.h) };
+        let margins = item.placed_margins(rotated);
+        let count = |counter: fn(&mut CandidateCounts) -> &mut u64| if options.candidate_stats() {
+            let mut counts = self.candidate_counter.get();
+            *counter(&mut counts) += 1;
+            self.candidate_counter.set(counts);
+        };
+        count(|x|&mut x.evaluated);
+        if x0 >= self.bitmap.width || y0 >= self.bitmap.height || w > self.bitmap.width - x0 || h > self.bitmap.height - y0 {
+            count(|x|&mut x.out_of_bounds);
             return None;
         }
-        for y in y0..y0+h {
-            for x in x0..x0+w {
-                if self.bitmap.get(x, y) {
-                    return None; //No fit
-                }
+        let rect = Rectangle { x0, y0, x1: x0 + w, y1: y0 + h };
+        if y0 < self.watermark || !item.bands_allow(x0, y0) || self.extent.is_some_and(|(max_x, max_y)|x0 + w > max_x || y0 + h > max_y) ||
+            self.items.iter().any(|x|x.violates_margins(&rect)) || !self.regions_allow(&item.id, &rect) {
+            count(|x|&mut x.constrained);
+            return None;
+        }
+        if let Some((edge, sequence)) = &self.unloading {
+            let position = sequence(&item.id);
+            let blocked = self.items.iter().any(|placed| {
+                let other = Rectangle { x0: placed.x0, y0: placed.y0, x1: placed.x1, y1: placed.y1 };
+                let other_position = sequence(&placed.id);
+                (other_position > position && sequence::blocks(*edge, &other, &rect)) ||
+                    (other_position < position && sequence::blocks(*edge, &rect, &other))
+            });
+            if blocked {
+                count(|x|&mut x.constrained);
+                return None;
             }
         }
+        // The area which must be free: the item itself plus the required spacing or
+        // margin on each side, clipped to the bin.
+        let spacing = options.spacing();
+        let cx0 = x0.saturating_sub(spacing.max(margins[0]));
+        let cy0 = y0.saturating_sub(spacing.max(margins[2]));
+        let cx1 = (x0 + w).saturating_add(spacing.max(margins[1])).min(self.bitmap.width);
+        let cy1 = (y0 + h).saturating_add(spacing.max(margins[3])).min(self.bitmap.height);
+        // Occupied cells are allowed within the item itself, up to the overlap tolerance,
+        // but not within the spacing around it.
+        let allowed_overlap = options.overlap_tolerance().allowed_cells(w.saturating_mul(h));
+        let sharing = self.edge_sharing(item, &rect, options);
+        let mut overlap = 0;
+        let mut free = [0; 4];
+        if self.bitmap.is_dense() {
+            for y in cy0..cy1 {
+                for x in cx0..cx1 {
+                    if self.bitmap.get(x, y) {
+                        let inside = x >= x0 && x < x0 + w && y >= y0 && y < y0 + h;
+                        if !inside && sharing.iter().any(|shared|shared.contains(&Rectangle { x0: x, y0: y, x1: x + 1, y1: y + 1 })) {
+                            continue;
+                        }
+                        if !inside || overlap == allowed_overlap {
+                            count(|x|&mut x.occupied);
+                            return None; //No fit
+                        }
+                        overlap += 1;
+                    }
+                }
+            }
+
+            // Free neighboring cells on each side: left, right, top, bottom.
+            for y in cy0..cy1 {
+                if cx0 > 0 && !self.bitmap.get(cx0-1,y) { free[0] += 1}
+                if cx1 < self.bitmap.width && !self.bitmap.get(cx1,y) { free[1] += 1}
+            }
 
-        let mut points = 0;
-        for y in y0..y0+h {
-            if x0 > 0 && !self.bitmap.get(x0-1,y) { points += 1}
-            if x0+w < self.bitmap.width && !self.bitmap.get(x0+w,y) { points += 1}
+            for x in cx0..cx1 {
+                if cy0 > 0 && !self.bitmap.get(x,cy0-1) { free[2] += 1}
+                if cy1 < self.bitmap.height && !self.bitmap.get(x, cy1) { free[3] += 1}
+            }
+        } else {
+            // Counting occupied cells of whole areas is cheaper than checking cells one by one
+            overlap = self.bitmap.count_occupied(x0, y0, w, h);
+            let clearance = Rectangle { x0: cx0, y0: cy0, x1: cx1, y1: cy1 };
+            let shared: usize = sharing.iter().map(|x|x.intersection_area(&clearance)).sum();
+            if overlap > allowed_overlap || self.bitmap.count_occupied(cx0, cy0, cx1 - cx0, cy1 - cy0).saturating_sub(shared) > overlap {
+                count(|x|&mut x.occupied);
+                return None; //No fit
+            }
+            let free_cells = |x0: usize, y0: usize, w: usize, h: usize| w * h - self.bitmap.count_occupied(x0, y0, w, h);
+            if cx0 > 0 { free[0] = free_cells(cx0 - 1, cy0, 1, cy1 - cy0) }
+            if cx1 < self.bitmap.width { free[1] = free_cells(cx1, cy0, 1, cy1 - cy0) }
+            if cy0 > 0 { free[2] = free_cells(cx0, cy0 - 1, cx1 - cx0, 1) }
+            if cy1 < self.bitmap.height { free[3] = free_cells(cx0, cy1, cx1 - cx0, 1) }
+        }
+        // A side is touching something if it is at the edge of the bin, or if
+        // any neighboring cell is occupied.
+        let mut touching = [cx0 == 0, cx1 == self.bitmap.width, cy0 == 0, cy1 == self.bitmap.height];
+        let side_lengths = [cy1 - cy0, cy1 - cy0, cx1 - cx0, cx1 - cx0];
+        for side in 0..4 {
+            touching[side] |= free[side] < side_lengths[side];
         }
 
-        for x in x0..x0+w {
-            if y0 > 0 && !self.bitmap.get(x,y0-1) { points += 1}
-            if y0 + h < self.bitmap.height && !self.bitmap.get(x, y0+h) { points += 1}
+        let contact = options.heuristic().contact_score(free, touching);
+        let slivers = match options.sliver_policy() {
+            SliverPolicy::Allow => 0,
+            SliverPolicy::Penalize { min_width, .. } | SliverPolicy::Forbid { min_width } => {
+                self.count_sliver_sides(cx0, cy0, cx1, cy1, min_width)
+            }
+        };
+        let penalty = match options.sliver_policy() {
+            SliverPolicy::Forbid { .. } if slivers > 0 => return None,
+            SliverPolicy::Penalize { penalty, .. } => slivers.saturating_mul(penalty),
+            _ => 0,
+        };
+        let unshared = match options.shared_cut_weight() {
+            0 => 0,
+            weight => {
+                let shared: usize = self.items.iter().map(|x|kerf::shared_cut(&x.rect(), &rect, spacing)).sum();
+                w.saturating_add(h).saturating_mul(2).saturating_sub(shared).saturating_mul(weight)
+            },
+        };
+        // Prefer exact fits: each overlapping cell counts like a free neighboring cell
+        Some(Fit { contact, penalty: penalty.saturating_add(overlap).saturating_add(unshared) })
+    }
+
+    /// The placed items which 'item' may touch without spacing when placed at 'rect', because
+    /// they share part of an edge with it, see 'SpacingPolicy'
+    fn edge_sharing(&self, item: &Item<I>, rect: &Rectangle, options: &PlaceOptions) -> Vec<Rectangle> {
+        let tags = match options.spacing_policy() {
+            SpacingPolicy::Uniform => return vec![],
+            SpacingPolicy::SharedEdgeAllowed => Tags::NONE,
+            SpacingPolicy::SharedEdgeTagged(tags) => tags,
+        };
+        if options.spacing() == 0 || item.margins != [0; 4] || !item.tags.contains(tags) {
+            return vec![];
         }
+        self.items.iter()
+            .filter(|x|x.margins == [0; 4] && x.tags.contains(tags) && kerf::shared_cut(&x.rect(), rect, 0) > 0)
+            .map(|x|x.rect())
+            .collect()
+    }
 
-        Some(points)
+    /// Count the sides of the rectangle `x0..x1`, `y0..y1` where the free space
+    /// between the rectangle and the nearest obstacle or bin edge is narrower
+    /// than 'min_width', but not zero.
+    fn count_sliver_sides(&self, x0: usize, y0: usize, x1: usize, y1: usize, min_width: usize) -> usize {
+        let (width, height) = (self.bitmap.width, self.bitmap.height);
+        let is_sliver = |gap: usize| gap > 0 && gap < min_width;
+        let free_run = |cells: &mut dyn Iterator<Item=(usize, usize)>| {
+            cells.take(min_width).take_while(|&(x, y)|!self.bitmap.get(x, y)).count()
+        };
+        let mut sides = 0;
+        if (y0..y1).any(|y|is_sliver(free_run(&mut (0..x0).rev().map(|x|(x, y))))) {
+            sides += 1;
+        }
+        if (y0..y1).any(|y|is_sliver(free_run(&mut (x1..width).map(|x|(x, y))))) {
+            sides += 1;
+        }
+        if (x0..x1).any(|x|is_sliver(free_run(&mut (0..y0).rev().map(|y|(x, y))))) {
+            sides += 1;
+        }
+        if (x0..x1).any(|x|is_sliver(free_run(&mut (y1..height).map(|y|(x, y))))) {
+            sides += 1;
+        }
+        sides
     }
-    fn add_to_best_fit(&mut self, item: &Item<I>, strategy: Strategy, mut cancel: impl FnMut() -> bool) -> bool {
+    /// Call 'visit' for every position where 'item' fits, in scan order.
+    /// Unless 'exhaustive' is set, the scan stops early once a fit has been found
+    /// and a line of the scan without any occupied cells has been passed.
+    /// Returns the number of positions evaluated, or None if cancelled.
+    fn scan_candidates(&self, item: &Item<I>, strategy: Strategy, options: &mut PlaceOptions, exhaustive: bool, mut visit: impl FnMut(Candidate)) -> Option<u64> {
         if item.w == 0 || item.h == 0 {
             panic!("Item size must not be 0 in any dimension");
         }
-        if item.w > self.bitmap.width && item.h > self.bitmap.height {
-            return false; //Impossible to fit.
+        let orientations = self.orientations(item, strategy, options);
+        let partners = self.placed_partners(&item.id);
+        let scan = options.scan_order();
+        let (width, height) = (self.bitmap.width, self.bitmap.height);
+        // Evaluate a position, and visit it if the item fits there. Returns true if it fits.
+        let mut consider = |x0: usize, y0: usize, w: usize, h: usize, rotated: bool, scan_index: usize, options: &PlaceOptions| {
+            let Some(candidate) = self.candidate_at(item, &partners, (x0, y0, w, h, rotated), scan_index, options) else {
+                return false;
+            };
+            visit(candidate);
+            true
+        };
+        if let Some(free) = self.bitmap.free_list() {
+            // Only positions in the corners of free rectangles (leaving room for the spacing)
+            // are evaluated, in the order the scan would have found them.
+            let spacing = options.spacing();
+            let mut positions = vec![];
+            for &(w, h, rotated) in &orientations {
+                for rect in free {
+                    let near = |start: usize| start.saturating_add(if start > 0 { spacing } else { 0 });
+                    // Directly against the items before the free rectangle, if they may share edges
+                    let touching = |start: usize|(options.spacing_policy() != SpacingPolicy::Uniform).then_some(start);
+                    let far = |end: usize, extent: usize, size: usize| end.checked_sub(size.checked_add(if end < extent { spacing } else { 0 })?);
+                    // With a band, also the first position in the band
+                    let band_start = |band: &Option<Range<usize>>, start: usize|band.as_ref().map(|band|band.start.max(near(start)));
+                    for x0 in [Some(near(rect.x0)), far(rect.x1, width, w), band_start(&item.x_band, rect.x0), touching(rect.x0)].into_iter().flatten() {
+                        for y0 in [Some(near(rect.y0)), far(rect.y1, height, h), band_start(&item.y_band, rect.y0), touching(rect.y0)].into_iter().flatten() {
+                            positions.push((scan.position_key(x0, y0, w, h, width, height), x0, y0, w, h, rotated));
+                        }
+                    }
+                }
+            }
+            positions.sort_unstable();
+            positions.dedup();
+            for (scan_index, &(_, x0, y0, w, h, rotated)) in positions.iter().enumerate() {
+                if scan_index % 256 == 0 && options.is_cancelled() {
+                    return None;
+                }
+                consider(x0, y0, w, h, rotated, scan_index, options);
+            }
+            return Some(positions.len() as u64);
         }
-        let mut cur_best_fit = usize::MAX;
-        let smallest_dim = item.h.min(item.w);
-        let mut best_fit = None;
-        for y in 0..self.bitmap.height.saturating_sub(smallest_dim - 1) {
+        // The smallest width and height over the orientations. Positions closer than this to
+        // the far edges can't fit the item, for instance all but x = 0 for a full width item.
+        let largest_dim = item.w.max(item.h);
+        let min_size = orientations.iter().fold((largest_dim, largest_dim), |(min_w, min_h), &(w, h, _)|(min_w.min(w), min_h.min(h)));
+        // Fast path for square items, which only have one orientation: for each row, first find
+        // all positions where the item itself is free at once, and only evaluate those.
+        let square = options.deduplicate() && item.w == item.h && orientations.len() == 1 && self.bitmap.is_dense() &&
+            matches!(scan, ScanOrder::Rows(_)) && options.overlap_tolerance().allowed_cells(item.w.saturating_mul(item.h)) == 0;
+        // Once the scan has taken long enough to pay for finding the maximal free rectangles,
+        // only positions where the item lies inside one of them are evaluated. This makes the
+        // time taken depend on the free space, rather than the area, of nearly full bins.
+        let use_free_rects = self.bitmap.is_dense() && options.overlap_tolerance().allowed_cells(item.w.saturating_mul(item.h)) == 0;
+        let mut anchors: Option<Vec<Vec<Rectangle>>> = None;
+        let mut line_anchors: Vec<Vec<Rectangle>> = vec![];
+        let mut body_free = vec![];
+        let mut found = false;
+        let mut cells = vec![];
+        let mut scan_index = 0;
+        let mut evaluations = 0;
+        for line in 0..scan.line_count(width, height, min_size) {
             let mut had_busy = false;
-            if cancel() {
-                return false;
+            if options.is_cancelled() {
+                return None;
+            }
+            if use_free_rects && anchors.is_none() && !found && evaluations > (width * height / 16) as u64 {
+                anchors = Some(self.free_anchors(&orientations));
             }
-            for x in 0..self.bitmap.width.saturating_sub(smallest_dim - 1) {
+            scan.line_cells(line, width, height, min_size, &mut cells);
+            if let Some(anchors) = &anchors {
+                line_anchors = Self::line_anchors(anchors, &orientations, &cells, scan);
+            }
+            if square && !cells.is_empty() {
+                // The top row of the item is the same for the whole line. The rightmost column
+                // is used, since it is a valid anchor for any corner.
+                let y0 = scan.anchor(width - 1, cells[0].1, item.w, item.h).map(|(_, y0)|y0);
+                self.free_squares(y0, item.w, &mut body_free);
+            }
+            for &(x, y) in &cells {
                 if self.bitmap.get(x, y) {
                    had_busy = true;
                 }
-                if strategy == Strategy::DoNotRotate || strategy == Strategy::RotateIfSuitable {
-                    if let Some(fit) = self.evaluate_fit(x,y,item.w,item.h) {
-                        if fit < cur_best_fit {
-                            cur_best_fit = fit;
-                            best_fit = Some((x,y,false));
-                        }
+                for (orientation, &(w, h, rotated)) in orientations.iter().enumerate() {
+                    let Some((x0, y0)) = scan.anchor(x, y, w, h).filter(|&(x0, y0)|x0 + w <= width && y0 + h <= height) else {
+                        continue;
+                    };
+                    if square && !body_free[x0] {
+                        continue;
                     }
-                }
-                if item.allow_rotate && (strategy == Strategy::Rotate || strategy == Strategy::RotateIfSuitable) {
-                    if let Some(fit) = self.evaluate_fit(x, y, item.h, item.w) { //Rotated
-                        if fit < cur_best_fit {
-                            cur_best_fit = fit;
-                            best_fit = Some((x, y, true));
-                        }
+                    if anchors.is_some() && !line_anchors[orientation].iter().any(|r|r.x0 <= x0 && x0 < r.x1 && r.y0 <= y0 && y0 < r.y1) {
+                        continue;
                     }
+                    evaluations += 1;
+                    found |= consider(x0, y0, w, h, rotated, scan_index, options);
                 }
+                scan_index += 1;
             }
-            if !had_busy && best_fit.is_some() {
+            // With zones, a better position may be found anywhere in the bin
+            if !exhaustive && !had_busy && found && self.zones.is_empty() {
                 break;
             }
         }
-        if let Some((fit_x,fit_y,rotated)) = best_fit {
-            self.place(fit_x,fit_y, item, rotated);
+        Some(evaluations)
+    }
+    /// For each orientation, the rectangles of top left positions where the item lies entirely
+    /// inside a maximal free rectangle. These are the only positions where it can fit.
+    fn free_anchors(&self, orientations: &[(usize, usize, bool)]) -> Vec<Vec<Rectangle>> {
+        let free = free_rects::maximal_free_rects(&self.bitmap);
+        orientations.iter().map(|&(w, h, _)| {
+            free.iter().filter(|r|r.width() >= w && r.height() >= h)
+                .map(|r|Rectangle { x0: r.x0, y0: r.y0, x1: r.x1 + 1 - w, y1: r.y1 + 1 - h })
+                .collect()
+        }).collect()
+    }
+    /// The part of 'anchors' which may contain the top left positions of the items placed at
+    /// 'cells', for each orientation
+    fn line_anchors(anchors: &[Vec<Rectangle>], orientations: &[(usize, usize, bool)], cells: &[(usize, usize)], scan: ScanOrder) -> Vec<Vec<Rectangle>> {
+        orientations.iter().zip(anchors).map(|(&(w, h, _), anchors)| {
+            let mut positions = cells.iter().filter_map(|&(x, y)|scan.anchor(x, y, w, h));
+            let Some((x0, y0)) = positions.next() else {
+                return vec![];
+            };
+            let bounds = positions.fold(Rectangle { x0, y0, x1: x0 + 1, y1: y0 + 1 }, |r, (x, y)|r.union(&Rectangle { x0: x, y0: y, x1: x + 1, y1: y + 1 }));
+            anchors.iter().filter(|r|r.intersects(&bounds)).copied().collect()
+        }).collect()
+    }
+    /// Set 'out[x0]' to true if a square of the given size is free, with its top left corner at
+    /// 'x0', 'y0', for each column 'x0'. All are false if 'y0' is None or too large.
+    fn free_squares(&self, y0: Option<usize>, size: usize, out: &mut Vec<bool>) {
+        out.clear();
+        out.resize(self.bitmap.width, false);
+        let Some(y0) = y0.filter(|&y0|y0 + size <= self.bitmap.height) else {
+            return;
+        };
+        // The number of consecutive free columns ending at 'x'
+        let mut run = 0;
+        for x in 0..self.bitmap.width {
+            let free = (y0..y0 + size).all(|y|!self.bitmap.get(x, y));
+            run = if free { run + 1 } else { 0 };
+            if run >= size {
+                out[x + 1 - size] = true;
+            }
+        }
+    }
+    /// Score 'item' at 'position' (left, top, width, height and whether it is rotated), which
+    /// is number 'scan_index' in scan order. None if the item does not fit there.
+    fn candidate_at(&self, item: &Item<I>, partners: &[(&PlacedItem<I>, usize)], position: (usize, usize, usize, usize, bool), scan_index: usize, options: &PlaceOptions) -> Option<Candidate> {
+        let (x0, y0, w, h, rotated) = position;
+        let fit = self.evaluate_fit(x0, y0, item, rotated, options)?;
+        let score = match options.heuristic() {
+            Heuristic::FirstFit => scan_index,
+            _ => fit.contact,
+        }.saturating_add(fit.penalty);
+        let score = partners.iter()
+            .map(|(partner, weight)|weight.saturating_mul(partner.distance_to(x0, y0, x0 + w, y0 + h)))
+            .fold(score, usize::saturating_add);
+        let zone = self.zone_priority(&Rectangle { x0, y0, x1: x0 + w, y1: y0 + h });
+        Some(Candidate { x0, y0, rotated, zone, score })
+    }
+    /// The sizes 'item' may be placed with using 'strategy', and whether each is rotated
+    fn orientations(&self, item: &Item<I>, strategy: Strategy, options: &PlaceOptions) -> Vec<(usize, usize, bool)> {
+        // For square items, the rotated fit is the same as the unrotated one, unless the margins differ
+        let symmetric = options.deduplicate() && item.is_symmetric();
+        let mut orientations = vec![];
+        if strategy == Strategy::DoNotRotate || strategy == Strategy::RotateIfSuitable {
+            orientations.push((item.w, item.h, false));
+        }
+        if self.allows_rotation(item) && (strategy == Strategy::Rotate || (strategy == Strategy::RotateIfSuitable && !symmetric)) {
+            orientations.push((item.h, item.w, true));
+        }
+        orientations
+    }
+    /// Place 'item' at the best position found by the GPU evaluator set in 'options'. Returns
+    /// None if there is no evaluator, the evaluator does not support the options or constraints
+    /// in use, or the GPU failed, in which case the normal scan should be used.
+    #[cfg(feature = "gpu")]
+    fn add_on_gpu(&mut self, item: &Item<I>, strategy: Strategy, options: &PlaceOptions) -> Option<bool> {
+        let gpu = options.gpu()?;
+        // The GPU only implements the default scoring, without any constraints
+        let supported = self.bitmap.is_dense() && gpu::supports(self.width(), self.height()) &&
+            options.heuristic() == Heuristic::MaxContact && options.spacing() == 0 &&
+            options.sliver_policy() == SliverPolicy::Allow && options.shared_cut_weight() == 0 && options.overlap_tolerance().allowed_cells(item.w.saturating_mul(item.h)) == 0 &&
+            options.scan_order() == ScanOrder::default() && options.tie_break() == TieBreak::ScanOrder &&
+            item.margins == [0; 4] && self.items.iter().all(|x|x.margins == [0; 4]) && item.x_band.is_none() && item.y_band.is_none() && self.extent.is_none() &&
+            self.zones.is_empty() && self.regions.is_empty() && self.forbidden.is_empty() && self.unloading.is_none() && self.placed_partners(&item.id).is_empty();
+        if !supported {
+            return None;
+        }
+        let orientations = self.orientations(item, strategy, options);
+        if orientations.is_empty() {
+            return Some(false);
+        }
+        let sizes: Vec<_> = orientations.iter().map(|&(w, h, _)|(w, h)).collect();
+        let best = gpu.best_position(self.width(), self.height(), |x, y|self.bitmap.get(x, y), &sizes).ok()?;
+        self.record_evaluations((self.width() * self.height() * sizes.len()) as u64);
+        let Some((x0, y0, orientation)) = best else {
+            return Some(false);
+        };
+        self.place(x0, y0, item, orientations[orientation].2, 0);
+        Some(true)
+    }
+    fn add_to_best_fit(&mut self, item: &Item<I>, strategy: Strategy, options: &mut PlaceOptions) -> bool {
+        if self.is_too_large(item) {
+            return false; //Impossible to fit.
+        }
+        if let Some(min_offcut) = options.min_offcut() {
+            return self.add_to_best_fit_with_offcuts(item, strategy, options, min_offcut);
+        }
+        #[cfg(feature = "gpu")]
+        if let Some(placed) = self.add_on_gpu(item, strategy, options) {
+            return placed;
+        }
+        let tie_break = options.tie_break();
+        let bounding_box = self.bounding_box();
+        let mut best_fit: Option<(Candidate, usize)> = None;
+        let evaluations = self.scan_candidates(item, strategy, options, false, |candidate| {
+            let key = Self::tie_key(tie_break, bounding_box, item, &candidate);
+            if best_fit.is_none_or(|(best, best_key)|(candidate.rank(), key) < (best.rank(), best_key)) {
+                best_fit = Some((candidate, key));
+            }
+        });
+        let Some(evaluations) = evaluations else {
+            return false;
+        };
+        self.record_evaluations(evaluations);
+        if let Some((best, _)) = best_fit {
+            self.place(best.x0, best.y0, item, best.rotated, options.overlap_tolerance().allowed_cells(item.w.saturating_mul(item.h)));
             true
         } else {
             false
         }
     }
+    /// The smallest rectangle containing all placed items, or None if the bin is empty
+    fn bounding_box(&self) -> Option<Rectangle> {
+        self.items.iter().map(|x|x.rect()).reduce(|a, b|a.union(&b))
+    }
+    /// Key used to choose between candidates with equal rank; lower is better.
+    /// 'bounding_box' is the bounding box of the placed items.
+    fn tie_key(tie_break: TieBreak, bounding_box: Option<Rectangle>, item: &Item<I>, candidate: &Candidate) -> usize {
+        match tie_break {
+            TieBreak::ScanOrder => 0,
+            TieBreak::SmallestBoundingBox => {
+                let (w, h) = if candidate.rotated { (item.h, item.w) } else { (item.w, item.h) };
+                let rect = Rectangle { x0: candidate.x0, y0: candidate.y0, x1: candidate.x0 + w, y1: candidate.y0 + h };
+                match bounding_box {
+                    Some(bounding_box) => bounding_box.union(&rect).area() - bounding_box.area(),
+                    None => rect.area(),
+                }
+            }
+        }
+    }
+    /// The already placed items which the item with id 'id' prefers to be near
+    /// (see 'prefer_near'), together with the weight of each preference.
+    fn placed_partners(&self, id: &I) -> Vec<(&PlacedItem<I>, usize)> {
+        let Some(id_eq) = self.id_eq else {
+            return vec![];
+        };
+        let mut wanted = vec![];
+        for (a, b, weight) in &self.preferences {
+            if id_eq(a, id) {
+                wanted.push((b, *weight));
+            }
+            if id_eq(b, id) {
+                wanted.push((a, *weight));
+            }
+        }
+        if wanted.is_empty() {
+            return vec![];
+        }
+        let mut partners = vec![];
+        for placed in &self.items {
+            for (partner_id, weight) in &wanted {
+                if id_eq(&placed.id, partner_id) {
+                    partners.push((placed, *weight));
+                }
+            }
+        }
+        partners
+    }
+    /// Feed everything about the bin which affects the result of packing into 'state',
+    /// i.e. its size, contents and settings, apart from the unloading and item order, which
+    /// can't be hashed.
+    fn hash_config<H: Hasher>(&self, state: &mut H) where I: Hash {
+        (self.width(), self.height(), self.bitmap.backend()).hash(state);
+        for item in &self.items {
+            (item.x0, item.y0, item.x1, item.y1, item.rotation, item.overlap, item.margins, &item.id).hash(state);
+        }
+        self.preferences.hash(state);
+        self.default_allow_rotate.hash(state);
+        self.zones.hash(state);
+        self.watermark.hash(state);
+        self.extent.hash(state);
+        self.reserved.hash(state);
+        self.regions.hash(state);
+        self.forbidden.hash(state);
+    }
+
+    /// Enlarge the bin to 'width' x 'height', keeping all placed items.
+    fn grow(&mut self, width: usize, height: usize) {
+        self.bitmap = self.bitmap.resized(width, height);
+        self.bitmap.fill(0, 0, width, self.watermark, true);
+        self.fill_reserved();
+        self.update_holes(false);
+    }
+
+    /// Must be called whenever 'items' is modified
+    fn items_changed(&mut self) {
+        *self.placement_index.get_mut() = None;
+    }
+
+    /// Replace the contents of the bin with the given, already validated, items.
+    fn restore(&mut self, items: Vec<PlacedItem<I>>) {
+        self.bitmap = Bitmap2d::with_backend(self.width(), self.height(), self.bitmap.backend());
+        self.bitmap.fill(0, 0, self.width(), self.watermark, true);
+        self.fill_reserved();
+        for item in &items {
+            self.bitmap.fill(item.x0, item.y0, item.width(), item.height(), true);
+        }
+        self.items = items;
+        self.items_changed();
+        self.update_holes(false);
+        self.stats = PackingStats::default();
+    }
+
+    /// The highest priority of the zones intersecting 'rect', or 0 if none
+    fn zone_priority(&self, rect: &Rectangle) -> usize {
+        self.zones.iter().filter(|(zone, _)|zone.intersects(rect)).map(|(_, priority)|*priority).max().unwrap_or(0)
+    }
+    /// True if 'item' would not fit even if the bin was empty (apart from the watermark), within
+    /// the extent limit
+    fn is_too_large(&self, item: &Item<I>) -> bool {
+        let (max_x, max_y) = self.extent_limit();
+        let (width, height) = (max_x, max_y.saturating_sub(self.watermark));
+        let fits = |w: usize, h: usize| w <= width && h <= height;
+        !(fits(item.w, item.h) || (self.allows_rotation(item) && fits(item.h, item.w)))
+    }
+    /// Record that 'item' could not be placed
+    fn reject(&mut self, item: &Item<I>) -> RejectReason {
+        let reason = if self.is_too_large(item) { RejectReason::TooLarge } else { RejectReason::NoSpace };
+        self.stats.items_rejected += 1;
+        self.rejections.push((item.id.clone(), reason));
+        reason
+    }
+    /// Whether 'item' may be rotated, taking the default of the bin into account
+    fn allows_rotation(&self, item: &Item<I>) -> bool {
+        item.allow_rotate.unwrap_or(self.default_allow_rotate)
+    }
+    fn record_evaluations(&mut self, evaluations: u64) {
+        self.stats.evaluations += evaluations;
+        self.stats.peak_evaluations = self.stats.peak_evaluations.max(evaluations);
+    }
+    /// Like 'add_to_best_fit', but only accepts positions after which all maximal free
+    /// rectangles are at least 'min_offcut' in both dimensions. Candidates are
+    /// tried best first, up to a limit.
+    fn add_to_best_fit_with_offcuts(&mut self, item: &Item<I>, strategy: Strategy, options: &mut PlaceOptions, min_offcut: usize) -> bool {
+        const MAX_ATTEMPTS: usize = 256;
+        let mut candidates = vec![];
+        let Some(evaluations) = self.scan_candidates(item, strategy, options, true, |candidate| candidates.push(candidate)) else {
+            return false;
+        };
+        self.record_evaluations(evaluations);
+        let (tie_break, bounding_box) = (options.tie_break(), self.bounding_box());
+        candidates.sort_by_key(|x|(x.rank(), Self::tie_key(tie_break, bounding_box, item, x)));
+        for candidate in candidates.iter().take(MAX_ATTEMPTS) {
+            let (w, h) = if candidate.rotated { (item.h, item.w) } else { (item.w, item.h) };
+            let mut trial = self.bitmap.clone();
+            trial.fill(candidate.x0, candidate.y0, w, h, true);
+            let acceptable = free_rects::maximal_free_rects(&trial).iter()
+                .all(|rect|rect.width() >= min_offcut && rect.height() >= min_offcut);
+            if acceptable {
+                self.place(candidate.x0, candidate.y0, item, candidate.rotated, options.overlap_tolerance().allowed_cells(item.w.saturating_mul(item.h)));
+                return true;
+            }
+        }
+        if !candidates.is_empty() {
+            self.stats.offcut_rejections += 1;
+        }
+        false
+    }
+
+    /// Return the 'k' best positions for 'item', best first, without placing it.
+    /// Positions are scored as by `place_all` with default options, considering
+    /// both orientations if the item allows rotation.
+    ///
+    /// The chosen position can then be committed using 'place_candidate'.
+    pub fn best_positions(&self, item: &Item<I>, k: usize) -> Vec<Candidate> {
+        let mut candidates = vec![];
+        let strategy = if self.allows_rotation(item) { Strategy::RotateIfSuitable } else { Strategy::DoNotRotate };
+        self.scan_candidates(item, strategy, &mut PlaceOptions::default(), true, |candidate| candidates.push(candidate));
+        // Stable sort, so ties are resolved in scan order, like in 'place_all'
+        candidates.sort_by_key(|x|x.rank());
+        candidates.truncate(k);
+        candidates
+    }
+
+    /// Place 'item' at the best position within 'hole', typically one of the rectangles returned
+    /// by 'free_rectangles'. Only positions where the whole item lies within 'hole' are
+    /// evaluated, which is much faster than searching the whole bin. Positions are scored as
+    /// by 'best_positions'.
+    ///
+    /// Returns the placed item, or None if it does not fit anywhere within 'hole'.
+    pub fn place_in_hole(&mut self, item: &Item<I>, hole: &Rectangle) -> Option<PlacedItem<I>> {
+        if item.w == 0 || item.h == 0 {
+            panic!("Item size must not be 0 in any dimension");
+        }
+        let options = PlaceOptions::default();
+        let strategy = if self.allows_rotation(item) { Strategy::RotateIfSuitable } else { Strategy::DoNotRotate };
+        let (x1, y1) = (hole.x1.min(self.width()), hole.y1.min(self.height()));
+        let partners = self.placed_partners(&item.id);
+        let mut best: Option<Candidate> = None;
+        let mut scan_index = 0;
+        for (w, h, rotated) in self.orientations(item, strategy, &options) {
+            let (Some(x_end), Some(y_end)) = (x1.checked_sub(w), y1.checked_sub(h)) else {
+                continue;
+            };
+            for y0 in hole.y0..=y_end {
+                for x0 in hole.x0..=x_end {
+                    let candidate = self.candidate_at(item, &partners, (x0, y0, w, h, rotated), scan_index, &options);
+                    scan_index += 1;
+                    if let Some(candidate) = candidate.filter(|x|best.is_none_or(|best|x.rank() < best.rank())) {
+                        best = Some(candidate);
+                    }
+                }
+            }
+        }
+        self.record_evaluations(scan_index as u64);
+        let best = best?;
+        self.place(best.x0, best.y0, item, best.rotated, options.overlap_tolerance().allowed_cells(item.w.saturating_mul(item.h)));
+        self.items.last().cloned()
+    }
+
+    /// Place 'item' at the position given by 'candidate', typically obtained from 'best_positions'.
+    /// Returns false, and places nothing, if the item does not fit at that position.
+    pub fn place_candidate(&mut self, item: &Item<I>, candidate: &Candidate) -> bool {
+        if self.evaluate_fit(candidate.x0, candidate.y0, item, candidate.rotated, &PlaceOptions::default()).is_none() {
+            return false;
+        }
+        self.place(candidate.x0, candidate.y0, item, candidate.rotated, 0);
+        true
+    }
 
 }
 
@@ -625,70 +2539,547 @@ impl<I:Clone> Bin<I> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_place_in_hole() {
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.pack([Item::new(10, 4, 'A'), Item::new(4, 6, 'B')]));
+        let holes = bin.free_rectangles();
+        assert_eq!(holes, vec![Rectangle { x0: 4, y0: 4, x1: 10, y1: 10 }]);
+        let placed = bin.place_in_hole(&Item::new(3, 3, 'C'), &holes[0]).unwrap();
+        assert!(holes[0].contains(&placed.rect()));
+        assert_eq!(bin.solution().len(), 3);
+        // Only the occupied part of the bin is left outside this hole
+        assert!(bin.place_in_hole(&Item::new(2, 2, 'D'), &Rectangle { x0: 0, y0: 0, x1: 4, y1: 10 }).is_none());
+        assert!(bin.place_in_hole(&Item::rotatable(7, 1, 'E'), &holes[0]).is_none());
+        assert!(bin.place_in_hole(&Item::rotatable(6, 1, 'F'), &holes[0]).is_some());
+    }
+
     #[test]
     fn test_hole() {
         let items = [
-            Item {
-                w: 10,
-                h: 3,
-                allow_rotate: true,
-                id: 'A'
-            },
-            Item {
-                w: 5,
-                h: 3,
-                allow_rotate: true,
-                id: 'B'
-            },
-            Item {
-                w: 10,
-                h: 5,
-                allow_rotate: true,
-                id: 'C'
-            },
+            Item::rotatable(10, 3, 'A'),
+            Item::rotatable(5, 3, 'B'),
+            Item::rotatable(10, 5, 'C'),
             ];
         let mut bin = Bin::new(10,10);
         bin.set_metric(|hole|hole.width);
-        let all_fit = bin.place_all(items.into_iter(),||false);
+        let all_fit = bin.place_all(items.into_iter(), PlaceOptions::default());
         print_solution(&bin, true);
         println!("Hole: {:?} (all_fit: {:?})", bin.largest_hole, all_fit)
 
     }
 
+    #[test]
+    fn test_occupancy_rle() {
+        let placed = |id, x0, y0, x1, y1|PlacedItem { x0, y0, x1, y1, rotation: Rotation::None, overlap: 0, margins: [0; 4], tags: Tags::NONE, id };
+        let bin = Bin::from_solution(5, 2, [placed('A', 1, 0, 3, 1), placed('B', 3, 1, 5, 2)]).unwrap();
+        let run = |x0, x1, occupied|Run { x0, x1, occupied };
+        assert_eq!(bin.occupancy_rle(), vec![
+            vec![run(0, 1, false), run(1, 3, true), run(3, 5, false)],
+            vec![run(0, 3, false), run(3, 5, true)],
+        ]);
+    }
+
     #[test]
     fn it_works() {
 
         let items = [
-            Item {
-                w: 10,
-                h: 3,
-                allow_rotate: true,
-                id: 'D'
-            },
-            Item {
-                w: 10,
-                h: 3,
-                allow_rotate: true,
-                id: 'A'
-            },
-            Item {
-                w: 10,
-                h: 3,
-                allow_rotate: true,
-                id: 'B'
-            },
-            Item {
-                w: 1,
-                h: 10,
-                allow_rotate: true,
-                id: 'C'
-            },
+            Item::rotatable(10, 3, 'D'),
+            Item::rotatable(10, 3, 'A'),
+            Item::rotatable(10, 3, 'B'),
+            Item::rotatable(1, 10, 'C'),
         ];
         let mut bin = Bin::new(10,10);
-        let all_fit = bin.place_all(items.into_iter(),||false);
+        let all_fit = bin.place_all(items.into_iter(), PlaceOptions::default());
         println!("All items placed: {:?}", all_fit);
         print_solution(&bin, false);
     }
+
+    #[test]
+    fn test_compact_solution() {
+        let items = || [Item::new(10, 3, 'A').with_allow_rotate(false), Item::new(1, 7, 'B').with_allow_rotate(false)].into_iter();
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all(items(), PlaceOptions::default()));
+        let compact = bin.compact_solution().unwrap();
+        assert_eq!(compact.rects, vec![[0, 0, 10, 3], [0, 3, 1, 10]]);
+        assert_eq!(compact.ids, vec!['A', 'B']);
+        // Coordinates which don't fit in a u16
+        let mut bin = Bin::with_backend(u16::MAX as usize + 1, 10, Backend::FreeRects);
+        assert!(bin.place_all(items(), PlaceOptions::default()));
+        assert!(bin.compact_solution().is_none());
+    }
+
+    #[test]
+    fn test_evaluations() {
+        // With free rectangles, the corners of each free rectangle are evaluated: 4 in the
+        // empty bin, then 4 in each of the two free rectangles left next to and below A,
+        // one of which is shared.
+        let mut bin = Bin::with_backend(10, 10, Backend::FreeRects);
+        let items = [Item::new(4, 4, 'A'), Item::new(4, 4, 'B')];
+        assert!(bin.pack_pass(items, Strategy::DoNotRotate, PlaceOptions::default()));
+        assert_eq!(bin.stats().items_placed, 2);
+        assert_eq!((bin.stats().evaluations, bin.stats().peak_evaluations), (4 + 7, 7));
+        // Every position, in both orientations, in each of the three default passes
+        assert_eq!(Bin::<char>::estimate_cost(10, 10, 2).evaluations, 100 * 2 * 2 * 3);
+        let estimate = Bin::<char>::estimate_cost(10, 10, 0);
+        assert_eq!(estimate.memory_bytes, 2 * Bitmap2d::initial_bytes(10, 10) + 100 * std::mem::size_of::<u32>() + std::mem::size_of::<Bin<char>>());
+    }
+    #[test]
+    fn test_spacing() {
+        let items = [
+            Item::new(4, 4, 'A').with_allow_rotate(false),
+            Item::new(4, 4, 'B').with_allow_rotate(false),
+        ];
+        let mut bin = Bin::new(9,4);
+        assert!(bin.place_all(items.clone().into_iter(), PlaceOptions::default().with_spacing(1)));
+        let mut bin = Bin::new(8,4);
+        assert!(!bin.place_all(items.into_iter(), PlaceOptions::default().with_spacing(1)));
+    }
+    #[test]
+    fn test_spacing_policy() {
+        const TABBED: Tags = Tags::tag(0);
+        let item = |id: char, tags: Tags|Item::new(4, 4, id).with_tags(tags);
+        for backend in [Backend::Bitmap, Backend::Tiled, Backend::FreeRects] {
+            let pack = |items: [Item<char>; 3], policy: SpacingPolicy| {
+                let mut bin = Bin::with_backend(12, 4, backend);
+                bin.place_all(items.into_iter(), PlaceOptions::default().with_spacing(1).with_spacing_policy(policy))
+            };
+            let plain = || [item('A', Tags::NONE), item('B', Tags::NONE), item('C', Tags::NONE)];
+            assert!(!pack(plain(), SpacingPolicy::Uniform));
+            assert!(pack(plain(), SpacingPolicy::SharedEdgeAllowed), "{:?}", backend);
+            assert!(!pack(plain(), SpacingPolicy::SharedEdgeTagged(TABBED)));
+            assert!(pack([item('A', TABBED), item('B', TABBED), item('C', TABBED)], SpacingPolicy::SharedEdgeTagged(TABBED)));
+        }
+        // Items of the same size with different tags are not deduplicated
+        let solve = |deduplicate: bool| {
+            let mut bin = Bin::new(8, 4);
+            let options = PlaceOptions::default().with_spacing(1).with_spacing_policy(SpacingPolicy::SharedEdgeTagged(TABBED)).with_deduplication(deduplicate);
+            bin.place_all([item('A', TABBED), item('B', Tags::NONE), item('C', TABBED)].into_iter(), options);
+            bin.solution().iter().map(|x|x.id).collect::<Vec<_>>()
+        };
+        assert_eq!(solve(true), vec!['A', 'C']);
+        assert_eq!(solve(true), solve(false));
+        // Items touching only at a corner keep the spacing
+        let mut bin = Bin::new(9, 9);
+        let options = PlaceOptions::default().with_spacing(1).with_spacing_policy(SpacingPolicy::SharedEdgeAllowed);
+        assert!(bin.pack([Item::new(4, 4, 'A')]));
+        let item = Item::new(4, 4, 'B');
+        assert!(bin.evaluate_fit(4, 2, &item, false, &options).is_some());
+        assert!(bin.evaluate_fit(4, 4, &item, false, &options).is_none());
+        assert!(bin.evaluate_fit(5, 5, &item, false, &options).is_some());
+    }
+    #[test]
+    fn test_deduplication() {
+        let mut items = vec![];
+        for (i, (w, h)) in [(3, 3), (4, 2), (3, 3), (7, 7), (4, 2), (7, 7), (1, 5), (3, 3)].into_iter().enumerate() {
+            items.push(Item::new(w, h, i).with_allow_rotate(i % 2 == 0));
+        }
+        let solve = |deduplicate: bool| {
+            let mut bin = Bin::new(10, 10);
+            let all_fit = bin.place_all(items.clone().into_iter(), PlaceOptions::default().with_deduplication(deduplicate));
+            let placed: Vec<_> = bin.solution().iter().map(|x|(x.x0, x.y0, x.x1, x.y1, x.rotated(), x.id)).collect();
+            (all_fit, placed)
+        };
+        assert_eq!(solve(true), solve(false));
+    }
+    #[test]
+    fn test_scan_order() {
+        let item = Item::new(2, 3, 'A').with_allow_rotate(false);
+        let place = |order: ScanOrder| {
+            let mut bin = Bin::new(10, 10);
+            assert!(bin.place_all([item.clone()].into_iter(), PlaceOptions::default().with_scan_order(order)));
+            let placed = &bin.solution()[0];
+            (placed.x0, placed.y0)
+        };
+        assert_eq!(place(ScanOrder::Rows(Corner::TopLeft)), (0, 0));
+        assert_eq!(place(ScanOrder::Rows(Corner::TopRight)), (8, 0));
+        assert_eq!(place(ScanOrder::Columns(Corner::BottomLeft)), (0, 7));
+        assert_eq!(place(ScanOrder::Rows(Corner::BottomRight)), (8, 7));
+    }
+    #[test]
+    fn test_best_positions() {
+        let mut bin = Bin::new(10, 10);
+        let item = Item::rotatable(10, 4, 'A');
+        let candidates = bin.best_positions(&item, 3);
+        assert_eq!(candidates.len(), 3);
+        assert!(candidates.windows(2).all(|w|w[0].score <= w[1].score));
+        assert!(bin.place_candidate(&item, &candidates[1]));
+        assert!(!bin.place_candidate(&item, &candidates[1]));
+        assert_eq!(bin.solution().len(), 1);
+    }
+    #[test]
+    fn test_send() {
+        fn send<T: Send>() {}
+        let mut bin = Bin::new(10, 10);
+        bin.set_autosave(|_|{});
+        bin.set_unloading_order(Edge::Bottom, |id: &usize|*id);
+        bin.set_item_order(|a, b|a.id.cmp(&b.id));
+        send::<Bin<usize>>();
+        assert!(std::thread::spawn(move ||bin.place_all([Item::new(5, 5, 0)].into_iter(), PlaceOptions::default())).join().unwrap());
+    }
+    #[test]
+    fn test_resume() {
+        let saved = Arc::new(Mutex::new(vec![]));
+        let mut bin = Bin::new(10, 10);
+        let sink = saved.clone();
+        bin.set_autosave(move |partial| {
+            let mut sink = sink.lock().unwrap();
+            sink.clear();
+            write_solution(&mut *sink, partial).unwrap();
+        });
+        let item = |id| Item::rotatable(5, 5, id);
+        assert!(bin.place_all([item(1), item(2)].into_iter(), PlaceOptions::default()));
+
+        let partial: Vec<PlacedItem<u32>> = read_solution(&saved.lock().unwrap()[..]).unwrap();
+        let mut resumed = Bin::from_solution(10, 10, partial).unwrap();
+        assert!(resumed.place_all([item(3), item(4)].into_iter(), PlaceOptions::default()));
+        assert!(!resumed.place_all([item(5)].into_iter(), PlaceOptions::default()));
+        assert_eq!(resumed.solution().len(), 4);
+
+        let overlapping = [PlacedItem { x0: 0, y0: 0, x1: 5, y1: 5, rotation: Rotation::None, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: 1 }, PlacedItem { x0: 4, y0: 4, x1: 6, y1: 6, rotation: Rotation::None, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: 2 }];
+        assert_eq!(Bin::from_solution(10, 10, overlapping).err(), Some(SolutionError::Overlap(1)));
+    }
+    #[test]
+    fn test_sliver_policy() {
+        let item = |w| Item::new(w, 4, 'A').with_allow_rotate(false);
+        let forbid = || PlaceOptions::default().with_sliver_policy(SliverPolicy::Forbid { min_width: 3 });
+        assert!(!Bin::new(10, 4).place_all([item(8)].into_iter(), forbid()));
+        assert!(Bin::new(10, 4).place_all([item(7)].into_iter(), forbid()));
+        assert!(Bin::new(10, 4).place_all([item(8)].into_iter(), PlaceOptions::default()));
+        // C only fits once B has filled the sliver next to it, so it is not skipped as a copy of A
+        for deduplicate in [true, false] {
+            let items = [Item::new(8, 4, 'A'), Item::new(2, 4, 'B'), Item::new(8, 4, 'C')];
+            let mut bin = Bin::new(10, 4);
+            bin.pack_pass(items, Strategy::DoNotRotate, forbid().with_deduplication(deduplicate));
+            let placed: Vec<_> = bin.solution().iter().map(|x|(x.id, x.x0)).collect();
+            assert_eq!(placed, vec![('B', 0), ('C', 2)]);
+        }
+    }
+    #[test]
+    fn test_min_offcut() {
+        let item = |w, id| Item::new(w, 10, id).with_allow_rotate(false);
+        let options = || PlaceOptions::default().with_min_offcut(3);
+        // Placing the 8 wide item would leave a 2 wide offcut, which is too small
+        let mut bin = Bin::new(10, 10);
+        assert!(!bin.place_all([item(8, 'A')].into_iter(), options()));
+        assert_eq!(bin.stats().offcut_rejections, 1);
+        // The 2 wide item fills the offcut completely, which is allowed
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all([item(7, 'A'), item(3, 'B')].into_iter(), options()));
+        assert!(bin.free_rectangles().is_empty());
+    }
+    #[test]
+    fn test_prefer_near() {
+        let items = [
+            Item::new(3, 3, 'A').with_allow_rotate(false),
+            Item::new(3, 3, 'C').with_allow_rotate(false),
+            Item::new(2, 2, 'B').with_allow_rotate(false),
+        ];
+        let position_of_b = |bin: &Bin<char>| {
+            let b = bin.solution().iter().find(|x|x.id == 'B').unwrap();
+            (b.x0, b.y0)
+        };
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all(items.clone().into_iter(), PlaceOptions::default()));
+        assert_eq!(position_of_b(&bin), (6, 0));
+        let mut bin = Bin::new(10, 10);
+        bin.prefer_near('A', 'B', 10);
+        assert!(bin.place_all(items.into_iter(), PlaceOptions::default()));
+        assert_eq!(position_of_b(&bin), (0, 3));
+    }
+    #[test]
+    fn test_default_allow_rotate() {
+        let item = Item::new(4, 10, 'A');
+        let mut bin = Bin::new(10, 4);
+        assert!(bin.place_all([item.clone()].into_iter(), PlaceOptions::default()));
+        let mut bin = Bin::new(10, 4);
+        bin.set_default_allow_rotate(false);
+        assert!(!bin.place_all([item.clone()].into_iter(), PlaceOptions::default()));
+        assert!(bin.place_all([Item { allow_rotate: Some(true), ..item }].into_iter(), PlaceOptions::default()));
+    }
+    #[test]
+    fn test_overlap_tolerance() {
+        let item = |id| Item::new(3, 3, id).with_allow_rotate(false);
+        let mut bin = Bin::new(5, 3);
+        assert!(!bin.place_all([item(1), item(2)].into_iter(), PlaceOptions::default()));
+        let mut bin = Bin::new(5, 3);
+        let options = PlaceOptions::default().with_overlap_tolerance(OverlapTolerance::Cells(3));
+        assert!(bin.place_all([item(1), item(2)].into_iter(), options));
+        assert_eq!(bin.solution().iter().map(|x|x.overlap).collect::<Vec<_>>(), vec![0, 3]);
+        assert!(Bin::from_solution(5, 3, bin.take_solution()).is_ok());
+    }
+    #[test]
+    fn test_zones() {
+        let items = (0..3).map(|id| Item::new(5, 5, id).with_allow_rotate(false));
+        let mut bin = Bin::new(10, 10);
+        bin.add_zone(Rectangle { x0: 5, y0: 0, x1: 10, y1: 10 }, 1);
+        assert!(bin.place_all(items, PlaceOptions::default()));
+        let positions: Vec<_> = bin.solution().iter().map(|x|(x.x0, x.y0)).collect();
+        assert_eq!(positions, vec![(0, 0), (0, 5), (5, 0)]);
+        assert_eq!(bin.stats().zone_utilization, vec![0.5]);
+
+        // Zones extending past the bin are clipped
+        let mut bin = Bin::new(10, 10);
+        bin.add_zone(Rectangle { x0: 5, y0: 5, x1: 15, y1: 15 }, 1);
+        bin.add_zone(Rectangle { x0: 12, y0: 0, x1: 15, y1: 15 }, 1);
+        assert!(bin.place_all([Item::new(5, 5, 0)].into_iter(), PlaceOptions::default()));
+        assert_eq!(bin.stats().zone_utilization, vec![0.0, 0.0]);
+    }
+    #[test]
+    fn test_free_rect_scan() {
+        // Items of varying sizes leave a fragmented bin, in which most of the scan is skipped
+        let items = || (0..120).map(|id| Item::rotatable(1 + id * 7 % 9, 1 + id * 5 % 7, id));
+        let mut dense = Bin::with_backend(40, 40, Backend::Bitmap);
+        dense.place_all(items(), PlaceOptions::default());
+        let mut tiled = Bin::with_backend(40, 40, Backend::Tiled);
+        tiled.place_all(items(), PlaceOptions::default());
+        let layout = |bin: &Bin<usize>|bin.solution().iter().map(|x|(x.id, x.x0, x.y0, x.rotated())).collect::<Vec<_>>();
+        assert_eq!(layout(&dense), layout(&tiled));
+        assert!(dense.stats().evaluations < tiled.stats().evaluations);
+    }
+    #[test]
+    fn test_regions() {
+        let mut bin = Bin::new(12, 4);
+        let hot = Rectangle { x0: 6, y0: 0, x1: 12, y1: 4 };
+        bin.restrict_to_regions('H', [hot]);
+        bin.restrict_to_regions('C', [Rectangle { x0: 0, y0: 0, x1: 6, y1: 4 }]);
+        let items = [Item::new(4, 4, 'H'), Item::new(2, 2, 'C'), Item::new(2, 2, 'C'), Item::new(2, 4, 'X')];
+        assert!(bin.place_all(items.into_iter(), PlaceOptions::default()));
+        let hot_item = bin.solution().iter().find(|x|x.id == 'H').unwrap();
+        assert!(hot.contains(&hot_item.rect()));
+        assert!(bin.solution().iter().filter(|x|x.id == 'C').all(|x|x.x1 <= 6));
+        // There is no room left for a second 'H' in the hot region
+        assert!(!bin.place_all([Item::new(4, 4, 'H')].into_iter(), PlaceOptions::default()));
+        assert!(bin.verify_regions().is_ok());
+
+        let mut bin = Bin::new(10, 10);
+        let corner = Rectangle { x0: 0, y0: 0, x1: 3, y1: 3 };
+        bin.forbid_regions('F', [corner]);
+        assert!(bin.place_all([Item::new(5, 5, 'F')].into_iter(), PlaceOptions::default()));
+        assert!(!bin.solution()[0].rect().intersects(&corner));
+        // Other items may still use the corner
+        let other = bin.place_in_hole(&Item::new(3, 3, 'O'), &corner).unwrap();
+        assert_eq!((other.x0, other.y0), (0, 0));
+        let mut loaded = Bin::from_solution(10, 10, [other]).unwrap();
+        loaded.forbid_regions('O', [corner]);
+        assert_eq!(loaded.verify_regions(), Err(SolutionError::Region(0)));
+
+        // An item of the same size as a restricted item which failed may still fit
+        let solve = |deduplicate: bool| {
+            let mut bin = Bin::new(20, 10);
+            bin.restrict_to_regions(1, [Rectangle { x0: 0, y0: 0, x1: 5, y1: 5 }]);
+            bin.place_all([Item::new(10, 10, 1), Item::new(10, 10, 2)].into_iter(), PlaceOptions::default().with_deduplication(deduplicate));
+            bin.solution().iter().map(|x|x.id).collect::<Vec<_>>()
+        };
+        assert_eq!(solve(true), vec![2]);
+        assert_eq!(solve(true), solve(false));
+    }
+    #[test]
+    fn test_pack_pass() {
+        let items = [
+            Item::rotatable(2, 10, 'A'),
+            Item::rotatable(3, 5, 'B'),
+        ];
+        let mut best = Bin::new(10, 5);
+        assert!(!best.pack_pass(items.clone(), Strategy::DoNotRotate, PlaceOptions::default()));
+        assert_eq!(best.solution().len(), 1);
+        let mut rotated = Bin::new(10, 5);
+        assert!(rotated.pack_pass(items, Strategy::RotateIfSuitable, PlaceOptions::default()));
+        // Items are placed in the given order
+        assert_eq!(rotated.solution()[0].id, 'A');
+        assert!(best.keep_best(rotated.clone()));
+        assert!(!best.keep_best(Bin::new(10, 5)));
+        assert_eq!(best.solution().len(), 2);
+    }
+    #[test]
+    fn test_watermark() {
+        let mut bin = Bin::new(10, 10);
+        bin.set_watermark(4);
+        bin.set_watermark(2);
+        assert_eq!(bin.watermark(), 4);
+        assert_eq!(bin.get_largest_hole().height, 6);
+        let item = |h| Item::new(10, h, h).with_allow_rotate(false);
+        assert!(!bin.clone().place_all([item(7)].into_iter(), PlaceOptions::default()));
+        assert!(bin.place_all([item(6)].into_iter(), PlaceOptions::default()));
+        assert_eq!(bin.solution()[0].y0, 4);
+    }
+
+    #[test]
+    fn test_limit_extent() {
+        let mut bin = Bin::new(10, 10);
+        bin.set_default_allow_rotate(false);
+        bin.limit_extent(10, 4);
+        assert_eq!(bin.extent_limit(), (10, 4));
+        let mut tall = bin.clone();
+        assert!(!tall.place_all([Item::new(3, 5, 'A')].into_iter(), PlaceOptions::default()));
+        assert_eq!(tall.rejections(), &[('A', RejectReason::TooLarge)]);
+        assert!(bin.place_all([Item::new(4, 4, 'A'), Item::new(6, 4, 'B')].into_iter(), PlaceOptions::default()));
+        assert!(bin.solution().iter().all(|x|x.y1 <= 4));
+        // The holes still cover the rest of the roll
+        assert_eq!(bin.get_largest_hole().height, 6);
+        assert_eq!(bin.utilization(), 0.4);
+    }
+
+    #[test]
+    fn test_reserved_stripes() {
+        let mut bin = Bin::new(10, 10);
+        bin.set_default_allow_rotate(false);
+        bin.reserve_cols(0..1);
+        bin.reserve_every(5, 1);
+        assert_eq!(bin.reserved_areas(), vec![
+            Rectangle { x0: 0, y0: 0, x1: 1, y1: 10 },
+            Rectangle { x0: 0, y0: 0, x1: 10, y1: 1 },
+            Rectangle { x0: 0, y0: 5, x1: 10, y1: 6 },
+        ]);
+        assert_eq!(bin.get_largest_hole().width, 9);
+        assert!(!bin.clone().pack([Item::new(9, 5, 'A')]));
+        assert!(bin.pack([Item::new(9, 4, 'A'), Item::new(4, 4, 'B'), Item::new(5, 4, 'C')]));
+        for item in bin.solution() {
+            assert!(bin.reserved_areas().iter().all(|x|!x.intersects(&item.rect())));
+        }
+    }
+
+    #[test]
+    fn test_item_constructors() {
+        let mut bin = Bin::new(10, 1);
+        bin.set_default_allow_rotate(false);
+        assert!(!bin.clone().pack([Item::new(1, 10, 'A')]));
+        assert!(bin.pack([Item::rotatable(1, 10, 'A')]));
+        assert!(bin.solution()[0].rotated());
+    }
+
+    #[test]
+    fn test_placement_of() {
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.pack([Item::new(5, 5, 'A')]));
+        assert_eq!(bin.placement_of(&'A').map(|x|x.x1), Some(5));
+        assert!(bin.pack([Item::new(5, 5, 'B')]));
+        assert_eq!(bin.placement_of(&'B').map(|x|x.x0), Some(5));
+        assert!(bin.placement_of(&'C').is_none());
+    }
+
+    #[test]
+    fn test_rejections() {
+        let mut bin = Bin::new(10, 10);
+        let items = [Item::new(8, 8, 'A'), Item::new(6, 6, 'B'), Item::new(12, 2, 'C'), Item::new(11, 1, 'D').with_allow_rotate(false)];
+        assert!(!bin.pack(items));
+        let mut rejections = bin.rejections().to_vec();
+        rejections.sort_by_key(|x|x.0);
+        assert_eq!(rejections, vec![('B', RejectReason::NoSpace), ('C', RejectReason::TooLarge), ('D', RejectReason::TooLarge)]);
+        assert_eq!(bin.stats().items_rejected, 3);
+    }
+
+    #[test]
+    fn test_candidate_counts() {
+        let items = || [Item::new(6, 10, 'A'), Item::new(6, 10, 'B').with_x_band(0..1)];
+        let mut bin = Bin::new(10, 10);
+        assert!(!bin.clone().place_all(items().into_iter(), PlaceOptions::default()));
+        assert!(bin.candidate_counts().is_empty());
+        assert!(!bin.place_all(items().into_iter(), PlaceOptions::default().with_candidate_stats(true)));
+        let counts = bin.candidate_counts();
+        assert_eq!(counts.iter().map(|x|x.0).collect::<Vec<_>>(), vec!['A', 'B']);
+        for (_, x) in counts {
+            assert!(x.evaluated > 0);
+            assert!(x.out_of_bounds + x.occupied + x.constrained <= x.evaluated);
+        }
+        assert!(counts[1].1.occupied + counts[1].1.constrained > 0);
+        assert_eq!(counts[1].1.evaluated, counts[1].1.out_of_bounds + counts[1].1.occupied + counts[1].1.constrained);
+    }
+
+    #[test]
+    fn test_free_rects_backend() {
+        let mut bin = Bin::with_backend(1_000_000, 1_000_000, Backend::FreeRects);
+        let items = (0..12).map(|id| Item::new(300_000, 250_000, id));
+        assert!(bin.pack(items));
+        assert!(bin.memory_footprint() < 10_000);
+        let hole = bin.get_largest_hole();
+        assert_eq!((hole.width, hole.height), (100_000, 1_000_000));
+        for (index, a) in bin.solution().iter().enumerate() {
+            for b in &bin.solution()[index + 1..] {
+                assert!(!Rectangle { x0: a.x0, y0: a.y0, x1: a.x1, y1: a.y1 }.intersects(&Rectangle { x0: b.x0, y0: b.y0, x1: b.x1, y1: b.y1 }));
+            }
+        }
+    }
+
+    #[test]
+    fn test_margins() {
+        let mut bin = Bin::new(10, 4);
+        let tool = Item::new(2, 4, 'A').with_allow_rotate(false).with_margins([0, 3, 0, 0]);
+        assert!(bin.pack([tool, Item::new(2, 4, 'B')]));
+        let b = bin.placement_of(&'B').unwrap();
+        assert!(b.x0 >= 5 || b.x1 <= bin.placement_of(&'A').unwrap().x0);
+        assert_eq!(bin.placement_of(&'A').unwrap().margins, [0, 3, 0, 0]);
+
+        let rotated = Item::rotatable(4, 2, 'C').with_margins([1, 2, 3, 4]);
+        assert_eq!(rotated.placed_margins(true), [4, 3, 1, 2]);
+        // A square item with a margin on one side is different when rotated
+        for deduplicate in [true, false] {
+            let mut bin = Bin::new(10, 7);
+            let lead_in = Item { allow_rotate: Some(true), margins: [0, 0, 6, 0], ..Item::new(4, 4, 'B') };
+            assert!(bin.place_all([Item::new(10, 2, 'A'), lead_in].into_iter(), PlaceOptions::default().with_deduplication(deduplicate)));
+            let b = bin.placement_of(&'B').unwrap();
+            assert_eq!((b.rotation, b.margins), (Rotation::Cw90, [0, 6, 0, 0]));
+        }
+
+        let solution = bin.solution().iter().map(|x|PlacedItem { x0: 3, x1: 5, ..x.clone() });
+        assert_eq!(Bin::from_solution(10, 4, solution.take(1).chain([PlacedItem { x0: 6, y0: 0, x1: 8, y1: 4, rotation: Rotation::None, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: 'B' }])).err(), Some(SolutionError::Margin(1)));
+    }
+
+    #[test]
+    fn test_unloading_order() {
+        let mut bin = Bin::new(4, 4);
+        bin.set_unloading_order(Edge::Bottom, |id: &usize|*id);
+        bin.set_default_allow_rotate(false);
+        let options = || PlaceOptions::default().with_scan_order(ScanOrder::Rows(Corner::BottomLeft));
+        assert!(!bin.clone().place_all([Item::new(4, 2, 0), Item::new(4, 2, 1)].into_iter(), PlaceOptions::default()));
+        assert!(bin.place_all([Item::new(4, 2, 0), Item::new(4, 2, 1)].into_iter(), options()));
+        assert!(bin.placement_of(&0).unwrap().y0 > bin.placement_of(&1).unwrap().y0);
+    }
+
+    #[test]
+    fn test_largest_hole_of_mask() {
+        // A scanned sheet with its left 3 columns and a spot at 7, 2 used
+        let hole = largest_hole(10, 6, |x, y|x < 3 || (x, y) == (7, 2));
+        assert_eq!((hole.width, hole.height), (4, 6));
+        assert_eq!(largest_hole(4, 4, |_, _|true).width, 0);
+        assert_eq!(largest_hole(0, 4, |_, _|false).width, 0);
+        let mut bin = Bin::new(10, 6);
+        bin.place(0, 0, &Item::new(3, 6, 'A'), false, 0);
+        bin.update_holes(false);
+        let hole = largest_hole(10, 6, |x, _|x < 3);
+        assert_eq!((hole.width, hole.height), (bin.get_largest_hole().width, bin.get_largest_hole().height));
+    }
+
+    #[test]
+    fn test_bands() {
+        for backend in [Backend::Bitmap, Backend::FreeRects] {
+            let mut bin = Bin::with_backend(10, 10, backend);
+            bin.set_default_allow_rotate(false);
+            // 'B' would go to the top left corner, but must be placed within reach of the print head
+            let items = [Item::new(4, 4, 'A'), Item::new(3, 3, 'B').with_y_band(5..7).with_x_band(2..10)];
+            assert!(bin.place_all(items.into_iter(), PlaceOptions::default()));
+            let placed = bin.placement_of(&'B').unwrap();
+            assert!((5..7).contains(&placed.y0) && placed.x0 >= 2, "{:?}", placed);
+            // A band outside the bin can't be satisfied
+            assert!(!bin.place_all([Item::new(1, 1, 'C').with_x_band(10..20)].into_iter(), PlaceOptions::default()));
+        }
+    }
+
+    #[test]
+    fn test_item_order() {
+        let mut bin = Bin::new(8, 2);
+        bin.set_default_allow_rotate(false);
+        let items = [Item::new(2, 2, 'A'), Item::new(2, 2, 'B'), Item::new(4, 2, 'C'), Item::new(2, 2, 'D')];
+        let order = |bin: &Bin<char>|bin.solution().iter().map(|x|(x.x0, x.id)).collect::<Vec<_>>();
+        // Items of the same size keep their input order
+        let mut default = bin.clone();
+        assert!(!default.place_all(items.clone().into_iter(), PlaceOptions::default()));
+        assert_eq!(order(&default), vec![(0, 'C'), (4, 'A'), (6, 'B')]);
+        bin.set_item_order(|a, b|b.id.cmp(&a.id));
+        assert!(!bin.place_all(items.into_iter(), PlaceOptions::default()));
+        assert_eq!(order(&bin), vec![(0, 'D'), (2, 'C'), (6, 'B')]);
+    }
+
     fn print_solution(bin: &Bin<char>, expect_hole: bool) {
         println!("Solution: {:#?}", bin.solution());
         let places = bin.solution();
@@ -704,4 +3095,189 @@ mod tests {
             println!("|");
         }
     }
+
+    #[test]
+    fn test_tie_break() {
+        let items = [Item::new(2, 4, 'A'), Item::new(2, 2, 'B')];
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all(items.clone().into_iter(), PlaceOptions::default()));
+        assert_eq!(bin.placement_of(&'B').map(|x|(x.x0, x.y0)), Some((2, 0)));
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all(items.into_iter(), PlaceOptions::default().with_tie_break(TieBreak::SmallestBoundingBox)));
+        assert_eq!(bin.placement_of(&'B').map(|x|(x.x0, x.y0)), Some((0, 4)));
+    }
+
+    #[test]
+    fn test_named_metrics() {
+        let mut bin = Bin::new(10, 10);
+        bin.add_metric("width", |hole|hole.width);
+        bin.add_metric("area", |hole|hole.width * hole.height);
+        assert!(bin.place_all([Item::new(4, 10, 'A'), Item::new(6, 4, 'B')].into_iter(), PlaceOptions::default()));
+        assert_eq!(bin.largest_hole_by("width").map(|x|x.width), Some(6));
+        assert_eq!(bin.largest_hole_by("area").map(|x|x.width * x.height), Some(36));
+        assert!(bin.largest_hole_by("height").is_none());
+
+        // A 3 x 8 item only fits in the remaining 10 x 3 hole if it is rotated
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.pack([Item::new(10, 7, 'A')]));
+        let next = Item::new(3, 8, 'B');
+        bin.add_metric("next", bin.metric_for_item(&next));
+        assert!(bin.largest_hole_by("next").unwrap().fits_rotatable(3, 8));
+        let fixed = Item { allow_rotate: Some(false), ..next };
+        assert_eq!(bin.metric_for_item(&fixed)(Hole { width: 10, height: 3 }), 0);
+        assert_eq!(bin.metric_for_item(&Item::new(3, 8, 'C'))(Hole { width: 10, height: 3 }), 30);
+    }
+
+    #[test]
+    fn test_estimate_feasibility() {
+        let bin: Bin<usize> = Bin::new(10, 10);
+        assert_eq!(bin.estimate_feasibility(&[Item::new(5, 5, 0), Item::new(10, 5, 1)]), Feasibility::Possible);
+        assert_eq!(bin.estimate_feasibility(&[Item::new(5, 5, 0), Item::new(11, 5, 1)]), Feasibility::Impossible(Infeasibility::TooLarge(1)));
+        assert_eq!(Bin::new(10, 20).estimate_feasibility(&[Item::rotatable(15, 5, 0)]), Feasibility::Possible);
+        let area = bin.estimate_feasibility(&[Item::new(10, 6, 0), Item::new(10, 5, 1)]);
+        assert_eq!(area, Feasibility::Impossible(Infeasibility::Area { required: 110, available: 100 }));
+        let wide = bin.estimate_feasibility(&[Item::rotatable(6, 6, 0), Item::rotatable(6, 6, 1)]);
+        assert_eq!(wide, Feasibility::Impossible(Infeasibility::WideItems { required: 12, available: 10 }));
+    }
+
+    #[test]
+    fn test_suggested_bin() {
+        let items = || (0..4).map(|id| Item::new(5, 5, id));
+        let mut bin = Bin::new(10, 10);
+        assert_eq!(bin.place_all_with_outcome(items(), PlaceOptions::default()), PackOutcome::AllFit);
+        let mut bin = Bin::new(8, 8);
+        let outcome = bin.place_all_with_outcome(items(), PlaceOptions::default());
+        assert_eq!(outcome, PackOutcome::Partial { suggested_bin: Some((10, 10)) });
+        assert_eq!(bin.solution().len(), 1);
+        let outcome = Bin::new(8, 8).place_all_with_outcome(items(), PlaceOptions::default().with_cancel(|| true));
+        assert_eq!(outcome, PackOutcome::Partial { suggested_bin: None });
+        // Growing the bin would overflow
+        let mut bin = Bin::with_backend(usize::MAX / 4, 10, Backend::FreeRects);
+        let outcome = bin.place_all_with_outcome([Item::new(5, 20, 0).with_allow_rotate(false)].into_iter(), PlaceOptions::default());
+        assert_eq!(outcome, PackOutcome::Partial { suggested_bin: None });
+    }
+
+    #[test]
+    fn test_square_fast_path() {
+        let items: Vec<_> = (0..40).map(|id| Item::new(1 + id * 7 % 5, 1 + id * 7 % 5, id)).collect();
+        for corner in [Corner::TopLeft, Corner::BottomRight] {
+            for spacing in [0, 1] {
+                let solve = |deduplicate: bool| {
+                    let mut bin = Bin::new(23, 17);
+                    bin.set_watermark(2);
+                    let options = PlaceOptions::default().with_deduplication(deduplicate)
+                        .with_spacing(spacing).with_scan_order(ScanOrder::Rows(corner));
+                    bin.place_all(items.iter().cloned(), options);
+                    bin.take_solution().iter().map(|x|(x.x0, x.y0, x.x1, x.y1, x.id)).collect::<Vec<_>>()
+                };
+                assert_eq!(solve(true), solve(false));
+            }
+        }
+    }
+
+    #[test]
+    fn test_place_linked() {
+        let mut bin = Bin::new(10, 10);
+        let connector = |id| Linked::new(Item::new(4, 3, id)).with(1, 4, Item::new(2, 2, id + 10));
+        assert!(bin.place_linked([connector(0), connector(1), Linked::from(Item::new(2, 10, 2))], PlaceOptions::default()));
+        assert_eq!(bin.solution().len(), 5);
+        for id in [0, 1] {
+            let (a, b) = (bin.placement_of(&id).unwrap(), bin.placement_of(&(id + 10)).unwrap());
+            if a.rotated() {
+                assert_eq!((b.x0 + 3 - a.x0, b.y0 - a.y0), (0, 1));
+            } else {
+                assert_eq!((b.x0 - a.x0, b.y0 - a.y0), (1, 4));
+            }
+        }
+        // The gaps next to the holes are free
+        assert_eq!(bin.utilization(), (2 * 12 + 2 * 4 + 20) as f64 / 100.0);
+        // The band of B moves the whole group, which is not rotated
+        let mut bin = Bin::new(10, 10);
+        let group = Linked::new(Item::rotatable(2, 4, 'A')).with(3, 0, Item::rotatable(1, 1, 'B').with_x_band(5..6));
+        assert!(bin.place_linked([group], PlaceOptions::default()));
+        let placed: Vec<_> = bin.solution().iter().map(|x|(x.id, x.x0, x.y0, x.rotated())).collect();
+        assert_eq!(placed, vec![('A', 2, 0, false), ('B', 5, 0, false)]);
+        // A group of optional items is optional
+        let optional = Linked::new(Item::new(20, 1, 'A').with_optional(true)).with(0, 1, Item::new(1, 1, 'B').with_optional(true));
+        assert!(bin.place_linked([optional], PlaceOptions::default()));
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let square = PlacedItem { x0: 4, y0: 0, x1: 6, y1: 2, rotation: Rotation::Cw90, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: 'B' };
+        let other = PlacedItem { x0: 0, y0: 3, x1: 1, y1: 5, rotation: Rotation::Cw90, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: 'A' };
+        let mut bin = Bin::from_solution(6, 5, [square, other]).unwrap();
+        bin.canonicalize();
+        let items: Vec<_> = bin.solution().iter().map(|x|(x.id, x.rotated())).collect();
+        assert_eq!(items, vec![('A', true), ('B', false)]);
+        assert_eq!(bin.placement_of(&'B').map(|x|x.x0), Some(4));
+    }
+
+    #[test]
+    fn test_place_all_streaming() {
+        for size in [4, 5] {
+            let mut streamed = vec![];
+            let mut bin = Bin::new(10, 10);
+            let items = (0..5).map(|id| Item::rotatable(size, 3, id));
+            bin.place_all_streaming(items, PlaceOptions::default(), |placed| streamed.push(placed.id));
+            let solution: Vec<_> = bin.solution().iter().map(|x|x.id).collect();
+            assert_eq!(streamed, solution);
+        }
+    }
+
+    #[test]
+    fn test_extreme_dimensions() {
+        let huge = usize::MAX;
+        let mut margins = Item::rotatable(huge / 3, 4, 'A');
+        margins.margins = [1, huge, 1, huge];
+        let items = [margins, Item::new(huge, huge, 'B'), Item::new(huge - 1, 1, 'C'), Item::new(2, 2, 'D')];
+        // Coordinates near the maximum, and a normal bin with huge margins and spacing
+        for (size, backend) in [(huge, Backend::FreeRects), (huge / 2, Backend::FreeRects), (10, Backend::Bitmap)] {
+            for spacing in [0, huge] {
+                let mut bin = Bin::with_backend(size, size, backend);
+                bin.place_all(items.clone().into_iter(), PlaceOptions::default().with_spacing(spacing).with_shared_cut_weight(1));
+                assert!(!bin.solution().is_empty());
+                assert!(bin.utilization() <= 1.0);
+                bin.cut_length();
+                bin.material_usage(huge);
+                bin.estimate_feasibility(&items);
+                bin.cutting_plan(items.iter().map(|x|(x.clone(), 2)), PlaceOptions::default);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tags() {
+        const RUSH: Tags = Tags::tag(0);
+        const FRAGILE: Tags = Tags::tag(1);
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.pack([Item::new(5, 5, 'A').with_tags(RUSH | FRAGILE), Item::new(5, 5, 'B'), Item::rotatable(5, 3, 'C').with_tags(RUSH)]));
+        let rush: Vec<_> = bin.filter_by_tag(RUSH).map(|x|x.id).collect();
+        assert_eq!(rush, vec!['A', 'C']);
+        assert_eq!(bin.filter_by_tag(FRAGILE).count(), 1);
+        assert_eq!(bin.filter_by_tag(Tags::NONE).count(), 3);
+    }
+
+    #[test]
+    fn test_placed_dimensions() {
+        let mut bin = Bin::new(10, 4);
+        assert!(bin.pack([Item::rotatable(4, 10, 'A')]));
+        let placed = &bin.solution()[0];
+        assert!(placed.rotated());
+        assert_eq!((placed.width(), placed.height()), (10, 4));
+        assert_eq!((placed.original_w(), placed.original_h()), (4, 10));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Placed item #1 covers free cells")]
+    fn test_audit() {
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.pack([Item::new(4, 4, 'A'), Item::new(3, 3, 'B')]));
+        bin.audit();
+        // Corrupt the bitmap, as a buggy placement backend might
+        let b = bin.solution()[1].clone();
+        bin.bitmap.fill(b.x0, b.y0, 1, 1, false);
+        bin.audit();
+    }
 }
@@ -0,0 +1,87 @@
+use std::cmp::Reverse;
+use std::time::Instant;
+
+use crate::{Bin, Item, PackingStats, RejectReason};
+
+/// The length of 'item' along the packing axis, and whether it must be rotated, if it spans
+/// the whole bin across the axis. The axis is horizontal if 'along_x' is true.
+fn orientation<I:Clone>(bin: &Bin<I>, item: &Item<I>, along_x: bool) -> Option<(usize, bool)> {
+    let across = if along_x { bin.height() } else { bin.width() };
+    let span = |w: usize, h: usize| if along_x { (h, w) } else { (w, h) };
+    let mut orientations = vec![(span(item.w, item.h), false)];
+    if bin.allows_rotation(item) {
+        orientations.push((span(item.h, item.w), true));
+    }
+    orientations.into_iter().find(|&((cross, length), _)|cross == across && length > 0).map(|((_, length), rotated)|(length, rotated))
+}
+
+/// See [`Bin::pack_1d`]
+pub(crate) fn pack_1d<I:Clone>(bin: &mut Bin<I>, input: Vec<Item<I>>) -> Option<bool> {
+    if !bin.items.is_empty() || bin.watermark > 0 || bin.extent.is_some() || !bin.reserved.is_empty() || !bin.regions.is_empty() || !bin.forbidden.is_empty() || bin.unloading.is_some() {
+        return None;
+    }
+    if input.iter().any(|x|x.margins != [0; 4] || x.x_band.is_some() || x.y_band.is_some()) {
+        return None;
+    }
+    let along_x = [true, false].into_iter().find(|&along_x|input.iter().all(|x|orientation(bin, x, along_x).is_some()))?;
+    let start = Instant::now();
+    let mut items: Vec<(usize, bool, Item<I>)> = input.into_iter().map(|item| {
+        let (length, rotated) = orientation(bin, &item, along_x).expect("Checked above");
+        (length, rotated, item)
+    }).collect();
+    // First fit decreasing, with the optional items after the mandatory ones
+    items.sort_by_key(|(length, _, item)|(item.optional, Reverse(*length)));
+    bin.stats = PackingStats::default();
+    bin.rejections.clear();
+    let capacity = if along_x { bin.width() } else { bin.height() };
+    let mut used = 0;
+    let mut all_fit = true;
+    for (length, rotated, item) in items {
+        if length <= capacity - used {
+            let (x0, y0) = if along_x { (used, 0) } else { (0, used) };
+            bin.place(x0, y0, &item, rotated, 0);
+            bin.stats.items_placed += 1;
+            used += length;
+        } else {
+            all_fit &= item.optional;
+            bin.stats.items_rejected += 1;
+            let reason = if length > capacity { RejectReason::TooLarge } else { RejectReason::NoSpace };
+            bin.rejections.push((item.id, reason));
+        }
+    }
+    bin.update_holes(false);
+    bin.stats.passes = 1;
+    bin.stats.zone_utilization = bin.zone_utilization();
+    bin.stats.weighted_utilization = bin.weighted_utilization();
+    bin.stats.shared_cut_length = bin.shared_cut_length(0);
+    bin.stats.duration = start.elapsed();
+    Some(all_fit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_1d() {
+        let mut bin = Bin::new(10, 4);
+        let items = [Item::new(3, 4, 'A'), Item::new(4, 6, 'B'), Item::new(4, 4, 'C'), Item::new(2, 4, 'D')];
+        assert_eq!(bin.pack_1d(items.into_iter()), Some(false));
+        // 'B' is rotated to span the height, and fills the bin together with 'C'
+        let placed: Vec<_> = bin.solution().iter().map(|x|(x.id, x.x0, x.rotated())).collect();
+        assert_eq!(placed, vec![('B', 0, true), ('C', 6, false)]);
+        assert_eq!(bin.rejections(), &[('A', RejectReason::NoSpace), ('D', RejectReason::NoSpace)]);
+        assert_eq!(bin.stats().items_placed, 2);
+        assert_eq!(bin.utilization(), 1.0);
+
+        // Items spanning the width are stacked vertically
+        let mut bin = Bin::new(4, 10);
+        assert_eq!(bin.pack_1d([Item::new(4, 5, 'A'), Item::new(4, 5, 'B')].into_iter()), Some(true));
+        assert_eq!(bin.solution()[1].y0, 5);
+
+        // Not a 1D instance
+        let mut bin = Bin::new(10, 4);
+        assert_eq!(bin.pack_1d([Item::new(3, 3, 'A')].into_iter()), None);
+        assert!(bin.solution().is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use std::ops::Range;
+
+use crate::{Item, PlacedItem, Rectangle, Rotation, Tags};
+
+/// A group of items which are placed as one rigid unit, at fixed offsets from each other,
+/// such as a connector and its strain relief hole. See [`crate::Bin::place_linked`].
+///
+/// The group may only be rotated if all its items may be rotated and none of them has a band,
+/// in which case the whole group is rotated. The bands of the items (see 'Item::x_band')
+/// apply to where each item ends up. The group is optional if all its items are, and has the
+/// tags which all its items have. Margins of the items are ignored.
+#[derive(Debug,Clone)]
+pub struct Linked<I> {
+    // Items with the offset of their top left corner from that of the first item
+    parts: Vec<(isize, isize, Item<I>)>,
+}
+
+impl<I> From<Item<I>> for Linked<I> {
+    fn from(item: Item<I>) -> Self {
+        Linked::new(item)
+    }
+}
+
+impl<I> Linked<I> {
+    /// A group containing only 'item'
+    pub fn new(item: Item<I>) -> Linked<I> {
+        Linked {
+            parts: vec![(0, 0, item)],
+        }
+    }
+
+    /// Add 'item' to the group, with its top left corner at 'dx', 'dy' relative to the
+    /// top left corner of the first item, in the unrotated orientation.
+    /// Panics if the item overlaps an item already in the group.
+    pub fn with(mut self, dx: isize, dy: isize, item: Item<I>) -> Linked<I> {
+        // In i128, which can hold any offset plus any size
+        let overlaps = |(x0, y0, a): &(isize, isize, Item<I>)| {
+            let (x0, y0, dx, dy) = (*x0 as i128, *y0 as i128, dx as i128, dy as i128);
+            x0 + a.w as i128 > dx && dx + item.w as i128 > x0 && y0 + a.h as i128 > dy && dy + item.h as i128 > y0
+        };
+        if self.parts.iter().any(overlaps) {
+            panic!("Linked items must not overlap");
+        }
+        self.parts.push((dx, dy, item));
+        self
+    }
+
+    /// The items of the group
+    pub fn items(&self) -> impl Iterator<Item=&Item<I>> {
+        self.parts.iter().map(|(_, _, item)|item)
+    }
+
+    /// The size of the bounding box of the group, and the position of each item within it
+    pub(crate) fn layout(&self) -> (usize, usize, Vec<Rectangle>) {
+        let min_x = self.parts.iter().map(|x|x.0).min().unwrap_or(0);
+        let min_y = self.parts.iter().map(|x|x.1).min().unwrap_or(0);
+        let rects: Vec<_> = self.parts.iter().map(|(dx, dy, item)| {
+            let (x0, y0) = (dx.abs_diff(min_x), dy.abs_diff(min_y));
+            Rectangle { x0, y0, x1: x0.saturating_add(item.w), y1: y0.saturating_add(item.h) }
+        }).collect();
+        let width = rects.iter().map(|x|x.x1).max().unwrap_or(0);
+        let height = rects.iter().map(|x|x.y1).max().unwrap_or(0);
+        (width, height, rects)
+    }
+}
+
+impl<I:Clone> Linked<I> {
+    /// The item the group is packed as, the size of its bounding box, with the id of its first item
+    pub(crate) fn unit(&self, allow_rotate: bool) -> Item<I> {
+        let (w, h, rects) = self.layout();
+        let first = &self.parts[0].2;
+        // The range of positions of the group which put each banded item in its band
+        let band = |band: fn(&Item<I>) -> &Option<Range<usize>>, offset: fn(&Rectangle) -> usize| {
+            rects.iter().zip(&self.parts)
+                .filter_map(|(rect, (_, _, item))|band(item).as_ref().map(|x|x.start.saturating_sub(offset(rect))..x.end.saturating_sub(offset(rect))))
+                .reduce(|a, b|a.start.max(b.start)..a.end.min(b.end))
+        };
+        let (x_band, y_band) = (band(|x|&x.x_band, |x|x.x0), band(|x|&x.y_band, |x|x.y0));
+        let mut unit = Item::new(w, h, first.id.clone())
+            .with_allow_rotate(allow_rotate && x_band.is_none() && y_band.is_none())
+            .with_tags(Tags(self.items().fold(u64::MAX, |tags, x|tags & x.tags.0)))
+            .with_optional(self.items().all(|x|x.optional));
+        (unit.x_band, unit.y_band) = (x_band, y_band);
+        unit
+    }
+
+    /// The items of the group, when its bounding box is placed with its top left corner at 'x0', 'y0'
+    pub(crate) fn placed_items(&self, x0: usize, y0: usize, rotated: bool) -> Vec<PlacedItem<I>> {
+        let (_, height, rects) = self.layout();
+        rects.iter().zip(&self.parts).map(|(rect, (_, _, item))| {
+            // Rotating the group turns it clockwise, like a rotated item
+            let rect = if rotated { Rectangle { x0: height - rect.y1, y0: rect.x0, x1: height - rect.y0, y1: rect.x1 } } else { *rect };
+            PlacedItem {
+                x0: x0 + rect.x0,
+                y0: y0 + rect.y0,
+                x1: x0 + rect.x1,
+                y1: y0 + rect.y1,
+                rotation: Rotation::quarter_turn(rotated),
+                overlap: 0,
+                margins: [0; 4],
+                tags: item.tags,
+                id: item.id.clone(),
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linked_layout() {
+        let group = Linked::new(Item::new(4, 2, 'A')).with(-2, 0, Item::new(1, 1, 'B'));
+        let (width, height, _) = group.layout();
+        assert_eq!((width, height), (6, 2));
+        let placed = group.placed_items(10, 20, true);
+        let rects: Vec<_> = placed.iter().map(|x|(x.id, x.x0, x.y0, x.x1, x.y1)).collect();
+        assert_eq!(rects, vec![('A', 10, 22, 12, 26), ('B', 11, 20, 12, 21)]);
+    }
+
+    #[test]
+    fn test_linked_rotation() {
+        // An L shape: B is below the left end of A. Turned clockwise, B ends up at the top left.
+        let group = Linked::new(Item::new(3, 1, 'A')).with(0, 1, Item::new(1, 1, 'B'));
+        let placed = group.placed_items(0, 0, true);
+        let rects: Vec<_> = placed.iter().map(|x|(x.id, x.x0, x.y0, x.x1, x.y1, x.rotation)).collect();
+        assert_eq!(rects, vec![('A', 1, 0, 2, 3, Rotation::Cw90), ('B', 0, 0, 1, 1, Rotation::Cw90)]);
+    }
+}
@@ -0,0 +1,333 @@
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::panic;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use crate::stepwise::{sort_items, PackIterator};
+use crate::{Bin, BinSelection, Item, PackingStats, PlaceOptions, PlacedItem};
+
+/// An item placed in one of the bins of a [`MultiBin`].
+#[derive(Debug,Clone)]
+pub struct Assignment<B, I:Clone> {
+    /// The label of the bin the item was placed in
+    pub bin: B,
+    /// Where the item was placed, within that bin
+    pub placed: PlacedItem<I>,
+}
+
+/// A set of bins, each with a user supplied label of type 'B', such as the
+/// id of a physical sheet or pallet.
+///
+/// Items are packed into the bins in the order the bins were added. Items which
+/// don't fit in one bin are tried in the next.
+#[derive(Clone)]
+pub struct MultiBin<B, I:Clone> {
+    bins: Vec<(B, Bin<I>)>,
+}
+
+impl<B, I:Clone> Default for MultiBin<B, I> {
+    fn default() -> Self {
+        MultiBin {
+            bins: vec![],
+        }
+    }
+}
+
+impl<B, I:Clone> MultiBin<B, I> {
+    /// Create an empty set of bins
+    pub fn new() -> MultiBin<B, I> {
+        MultiBin::default()
+    }
+
+    /// Add a bin, with the given label. The bin may already contain items.
+    pub fn add_bin(&mut self, label: B, bin: Bin<I>) {
+        self.bins.push((label, bin));
+    }
+
+    /// The bins, with their labels, in the order they were added
+    pub fn bins(&self) -> &[(B, Bin<I>)] {
+        &self.bins
+    }
+
+    /// The bin with the given label, if any
+    pub fn bin(&self, label: &B) -> Option<&Bin<I>> where B: PartialEq {
+        self.bins.iter().find(|(x, _)| x == label).map(|(_, bin)| bin)
+    }
+
+    /// Pack items into the bins. Each bin is packed like with [`Bin::place_all`], using
+    /// the items which did not fit in any of the previous bins, in the order set for the bin
+    /// using `Bin::set_item_order`, if any.
+    ///
+    /// With `BinSelection::BestFit` or `BinSelection::WorstFit` (see
+    /// `PlaceOptions::with_bin_selection`), the items are instead placed one at a time, largest
+    /// first, each in the bin with the least (or most) free area in which it fits, using the
+    /// first strategy of the options which fits it, in a single pass.
+    ///
+    /// Returns the items which did not fit in any bin. If packing is cancelled, the
+    /// remaining bins (or items) are not packed.
+    pub fn place_all(&mut self, input: impl Iterator<Item=Item<I>>, options: PlaceOptions) -> Vec<Item<I>> {
+        let mut options = options;
+        let mut items: Vec<Item<I>> = input.collect();
+        sort_items(&mut items, &mut options, None);
+        match options.bin_selection() {
+            BinSelection::FirstFit => {},
+            BinSelection::BestFit => return self.place_by_free_area(items, options, false),
+            BinSelection::WorstFit => return self.place_by_free_area(items, options, true),
+        }
+        for (_, bin) in &mut self.bins {
+            if items.is_empty() || options.is_cancelled() {
+                break;
+            }
+            if let Some(compare) = &bin.item_order {
+                items.sort_by(|a, b|compare(a, b));
+            }
+            let count = items.len();
+            let mut packer = PackIterator::new(bin, items, options, false);
+            packer.by_ref().for_each(drop);
+            // Whether each item was placed, in the pass whose layout was kept
+            let mut placed = packer.placed().to_vec();
+            placed.resize(count, false);
+            let (returned_options, packed_items) = packer.into_parts();
+            options = returned_options;
+            items = packed_items.into_iter().zip(placed).filter(|(_, placed)| !placed).map(|(item, _)| item).collect();
+        }
+        items
+    }
+
+    /// Place each of the sorted 'items' in the bin with the least free area in which it fits,
+    /// or the most if 'most_free' is true. Ties go to the bin added first.
+    fn place_by_free_area(&mut self, items: Vec<Item<I>>, mut options: PlaceOptions, most_free: bool) -> Vec<Item<I>> {
+        let start = Instant::now();
+        for (_, bin) in &mut self.bins {
+            bin.stats = PackingStats::default();
+            bin.rejections.clear();
+        }
+        let free_area = |bin: &Bin<I>|bin.width() * bin.height() - bin.bitmap.count_occupied(0, 0, bin.width(), bin.height());
+        let mut free: Vec<usize> = self.bins.iter().map(|(_, bin)|free_area(bin)).collect();
+        let mut left = vec![];
+        for item in items {
+            if options.is_cancelled() {
+                left.push(item);
+                continue;
+            }
+            let mut order: Vec<usize> = (0..self.bins.len()).collect();
+            if most_free {
+                order.sort_by_key(|&index|Reverse(free[index]));
+            } else {
+                order.sort_by_key(|&index|free[index]);
+            }
+            let chosen = order.into_iter().find(|&index|place_item(&mut self.bins[index].1, &item, &mut options));
+            match chosen {
+                Some(index) => free[index] = free_area(&self.bins[index].1),
+                None => left.push(item),
+            }
+        }
+        let cancelled = options.was_cancelled();
+        for (_, bin) in &mut self.bins {
+            bin.update_holes(false);
+            bin.stats.duration = start.elapsed();
+            bin.stats.cancelled = cancelled;
+        }
+        left
+    }
+
+    /// Like 'place_all', but packs several bins at the same time, using up to 'threads'
+    /// threads. Panics if 'threads' is 0.
+    ///
+    /// The items are dealt to the threads, largest first. Each thread packs one bin at a
+    /// time, taking items from its own share and stealing from the other threads when its
+    /// share runs out. Items which don't fit in a bin are handed back, to be tried in the
+    /// bins packed later. Finally, the items which are left are tried in every bin, in the
+    /// order the bins were added.
+    ///
+    /// Since options can't be sent between threads, 'options' is called to create the
+    /// options on each thread. Each item is placed using the first strategy of the options which fits it, in a single pass, so the result generally
+    /// differs from that of 'place_all', and between runs.
+    ///
+    /// Returns the items which did not fit in any bin.
+    pub fn place_all_parallel<'o>(&mut self, input: impl Iterator<Item=Item<I>>, threads: usize, options: impl Fn() -> PlaceOptions<'o> + Sync) -> Vec<Item<I>> where I: Send {
+        if threads == 0 {
+            panic!("Packing needs at least one thread");
+        }
+        let mut items: Vec<Item<I>> = input.collect();
+        let mut main_options = options();
+        sort_items(&mut items, &mut main_options, None);
+        let bins: VecDeque<(usize, Bin<I>)> = self.bins.iter().enumerate().map(|(index, (_, bin))|(index, bin.clone())).collect();
+        let threads = threads.min(bins.len()).max(1);
+        let mut shares: Vec<VecDeque<Item<I>>> = (0..threads).map(|_|VecDeque::new()).collect();
+        for (index, item) in items.into_iter().enumerate() {
+            shares[index % threads].push_back(item);
+        }
+        let work = Work {
+            bins: Mutex::new(bins),
+            shares: shares.into_iter().map(Mutex::new).collect(),
+        };
+        let packed: Vec<(usize, Bin<I>)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads).map(|worker| {
+                let (work, options) = (&work, &options);
+                scope.spawn(move || work.run(worker, options()))
+            }).collect();
+            workers.into_iter().flat_map(|x|x.join().unwrap_or_else(|e|panic::resume_unwind(e))).collect()
+        });
+        for (index, bin) in packed {
+            self.bins[index].1 = bin;
+        }
+        // Reconciliation: try the items which were left in all bins, since they may have
+        // been handed back after the bins they would fit in were finished
+        let mut items: Vec<Item<I>> = work.shares.into_iter().flat_map(|x|x.into_inner().expect("threads have finished")).collect();
+        sort_items(&mut items, &mut main_options, None);
+        for (_, bin) in &mut self.bins {
+            if items.is_empty() || main_options.is_cancelled() {
+                break;
+            }
+            let start = Instant::now();
+            items.retain(|item|!place_item(bin, item, &mut main_options));
+            bin.stats.duration += start.elapsed();
+            bin.stats.cancelled |= main_options.was_cancelled();
+        }
+        items
+    }
+
+    /// All items placed in any of the bins, labeled with the bin they were placed in
+    pub fn assignments(&self) -> Vec<Assignment<B, I>> where B: Clone {
+        self.bins.iter().flat_map(|(label, bin)| {
+            bin.solution().iter().map(move |placed| Assignment {
+                bin: label.clone(),
+                placed: placed.clone(),
+            })
+        }).collect()
+    }
+}
+
+/// Place 'item' using the first strategy of 'options' which fits it
+fn place_item<I:Clone>(bin: &mut Bin<I>, item: &Item<I>, options: &mut PlaceOptions) -> bool {
+    let strategies = options.strategies().to_vec();
+    if strategies.into_iter().any(|strategy|bin.add_to_best_fit(item, strategy, options)) {
+        bin.stats.items_placed += 1;
+        true
+    } else {
+        false
+    }
+}
+
+/// The state shared by the threads of 'place_all_parallel'
+struct Work<I:Clone> {
+    // Copies of the bins which have not been started yet, with their index in the 'MultiBin'
+    bins: Mutex<VecDeque<(usize, Bin<I>)>>,
+    // The items not yet taken by each thread, largest first
+    shares: Vec<Mutex<VecDeque<Item<I>>>>,
+}
+
+impl<I:Clone> Work<I> {
+    /// Take an item from the front of the share of 'worker', or steal one from the back
+    /// of the share of another thread
+    fn take(&self, worker: usize) -> Option<Item<I>> {
+        let share = |index: usize|self.shares[index].lock().expect("threads don't panic while holding a lock");
+        // Only one lock is held at a time, since two threads stealing from each other would deadlock
+        let own = share(worker).pop_front();
+        own.or_else(|| {
+            (1..self.shares.len()).find_map(|offset|share((worker + offset) % self.shares.len()).pop_back())
+        })
+    }
+
+    fn run(&self, worker: usize, mut options: PlaceOptions) -> Vec<(usize, Bin<I>)> {
+        let mut packed = vec![];
+        loop {
+            let next = self.bins.lock().expect("threads don't panic while holding a lock").pop_front();
+            let Some((index, mut bin)) = next else {
+                return packed;
+            };
+            let start = Instant::now();
+            bin.stats = PackingStats::default();
+            bin.rejections.clear();
+            let mut rejected = vec![];
+            while let Some(item) = self.take(worker) {
+                if options.is_cancelled() {
+                    rejected.push(item);
+                    break;
+                }
+                if !place_item(&mut bin, &item, &mut options) {
+                    rejected.push(item);
+                }
+            }
+            bin.update_holes(false);
+            bin.stats.passes = 1;
+            bin.stats.cancelled = options.was_cancelled();
+            bin.stats.zone_utilization = bin.zone_utilization();
+            bin.stats.weighted_utilization = bin.weighted_utilization();
+            bin.stats.kerf_area = bin.kerf_area(options.spacing());
+            bin.stats.shared_cut_length = bin.shared_cut_length(options.spacing());
+            bin.stats.duration = start.elapsed();
+            // Hand the items back, keeping the largest first
+            let mut share = self.shares[worker].lock().expect("threads don't panic while holding a lock");
+            rejected.into_iter().rev().for_each(|item|share.push_front(item));
+            drop(share);
+            packed.push((index, bin));
+            if options.was_cancelled() {
+                return packed;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_bin() {
+        let mut bins = MultiBin::new();
+        bins.add_bin("sheet 1", Bin::new(10, 10));
+        bins.add_bin("sheet 2", Bin::new(10, 10));
+        let items = (0..5).map(|id| Item::new(6, 6, id));
+        let left = bins.place_all(items, PlaceOptions::default());
+        assert_eq!(left.len(), 3);
+        let assignments = bins.assignments();
+        let labels: Vec<_> = assignments.iter().map(|x| x.bin).collect();
+        assert_eq!(labels, vec!["sheet 1", "sheet 2"]);
+        assert_eq!(bins.bin(&"sheet 2").unwrap().solution().len(), 1);
+    }
+
+    #[test]
+    fn test_bin_selection() {
+        let mut bins = MultiBin::new();
+        bins.add_bin(0, Bin::new(10, 10));
+        bins.add_bin(1, Bin::new(10, 10));
+        let items = || [Item::new(6, 6, 'A'), Item::new(4, 4, 'B'), Item::new(3, 3, 'C')].into_iter();
+        let labels = |bins: &MultiBin<usize, char>|bins.assignments().iter().map(|x|(x.placed.id, x.bin)).collect::<Vec<_>>();
+        // Best fit puts the small items next to the large one, worst fit in the emptier bin
+        let mut best = bins.clone();
+        assert!(best.place_all(items(), PlaceOptions::default().with_bin_selection(BinSelection::BestFit)).is_empty());
+        assert_eq!(labels(&best), vec![('A', 0), ('B', 0), ('C', 0)]);
+        assert_eq!(best.bins()[0].1.stats().items_placed, 3);
+        let mut worst = bins.clone();
+        assert!(worst.place_all(items(), PlaceOptions::default().with_bin_selection(BinSelection::WorstFit)).is_empty());
+        assert_eq!(labels(&worst), vec![('A', 0), ('B', 1), ('C', 1)]);
+        // Items which fit nowhere are returned
+        let left = bins.place_all([Item::new(11, 1, 'D')].into_iter(), PlaceOptions::default().with_bin_selection(BinSelection::BestFit));
+        assert_eq!(left.len(), 1);
+    }
+
+    #[test]
+    fn test_place_all_parallel() {
+        let mut bins = MultiBin::new();
+        for label in 0..4 {
+            bins.add_bin(label, Bin::new(10, 10));
+        }
+        let mut sequenced = Bin::new(10, 10);
+        sequenced.set_unloading_order(crate::Edge::Bottom, |id: &usize|*id);
+        bins.add_bin(4, sequenced);
+        let items = (0..25).map(|id| Item::new(5, 5, id));
+        let left = bins.place_all_parallel(items, 3, PlaceOptions::default);
+        for (_, bin) in &bins.bins()[..4] {
+            assert_eq!(bin.solution().len(), 4);
+            assert_eq!(bin.stats().items_placed, 4);
+        }
+        // Every item is either placed once or returned
+        let mut ids: Vec<_> = bins.assignments().iter().map(|x|x.placed.id).chain(left.iter().map(|x|x.id)).collect();
+        ids.sort();
+        assert_eq!(ids, (0..25).collect::<Vec<_>>());
+    }
+}
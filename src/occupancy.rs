@@ -0,0 +1,78 @@
+use crate::{Bin, Rectangle, Reservation};
+
+/// See [`Bin::from_occupancy_bitmap`]
+pub(crate) fn from_occupancy_bitmap<I:Clone>(width: usize, height: usize, bits: &[bool]) -> Bin<I> {
+    let mut bin = Bin::new(width, height);
+    if bits.len() != width * height {
+        panic!("The occupancy bitmap has {} cells, expected {} x {}", bits.len(), width, height);
+    }
+    // The occupied runs of each row, merged with the run above if it covers the same columns
+    let mut rects: Vec<Rectangle> = vec![];
+    let mut above: Vec<usize> = vec![];
+    for (y, row) in bits.chunks(width).enumerate() {
+        let mut current = vec![];
+        let mut x = 0;
+        while x < width {
+            if !row[x] {
+                x += 1;
+                continue;
+            }
+            let x0 = x;
+            while x < width && row[x] {
+                x += 1;
+            }
+            match above.iter().find(|&&index|rects[index].x0 == x0 && rects[index].x1 == x) {
+                Some(&index) => {
+                    rects[index].y1 = y + 1;
+                    current.push(index);
+                },
+                None => {
+                    current.push(rects.len());
+                    rects.push(Rectangle { x0, y0: y, x1: x, y1: y + 1 });
+                },
+            }
+        }
+        above = current;
+    }
+    bin.reserve(Reservation::Mask(rects));
+    bin.update_holes(false);
+    bin
+}
+
+/// See [`Bin::from_luma_image`]
+#[cfg(feature = "image")]
+pub(crate) fn from_luma_image<I:Clone>(image: &image::DynamicImage, threshold: u8) -> Bin<I> {
+    let luma = image.to_luma8();
+    let (width, height) = (luma.width() as usize, luma.height() as usize);
+    let bits: Vec<bool> = luma.pixels().map(|x|x.0[0] < threshold).collect();
+    from_occupancy_bitmap(width, height, &bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{largest_hole, Item, PlaceOptions};
+
+    #[test]
+    fn test_from_occupancy_bitmap() {
+        // A sheet with an L shaped used part in the top left corner
+        let (width, height) = (8, 6);
+        let used = |x: usize, y: usize|(x < 5 && y < 2) || (x < 2 && y < 4);
+        let bits: Vec<bool> = (0..width * height).map(|i|used(i % width, i / width)).collect();
+        let mut bin: Bin<char> = Bin::from_occupancy_bitmap(width, height, &bits);
+        assert_eq!(bin.reserved_areas(), vec![Rectangle { x0: 0, y0: 0, x1: 5, y1: 2 }, Rectangle { x0: 0, y0: 2, x1: 2, y1: 4 }]);
+        let hole = largest_hole(width, height, used);
+        assert_eq!((bin.get_largest_hole().width, bin.get_largest_hole().height), (hole.width, hole.height));
+        // The used part stays occupied in every pass
+        assert!(bin.place_all([Item::new(6, 4, 'A'), Item::new(3, 2, 'B')].into_iter(), PlaceOptions::default()));
+        assert!(bin.solution().iter().all(|x|!x.rect().intersects(&Rectangle { x0: 0, y0: 0, x1: 5, y1: 2 })));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_from_luma_image() {
+        let image = image::GrayImage::from_fn(4, 3, |x, _|image::Luma([if x == 0 { 10 } else { 200 }]));
+        let bin: Bin<char> = Bin::from_luma_image(&image.into(), 128);
+        assert_eq!(bin.reserved_areas(), vec![Rectangle { x0: 0, y0: 0, x1: 1, y1: 3 }]);
+    }
+}
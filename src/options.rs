@@ -0,0 +1,664 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "rand_core")]
+use rand_core::RngCore;
+
+#[cfg(feature = "gpu")]
+use crate::GpuEvaluator;
+use crate::{ScanOrder, Strategy, Tags};
+
+/// The final search used by [`Quality::Best`]
+const BEST_SEARCH_ITEMS: usize = 5;
+const BEST_SEARCH_BUDGET: Duration = Duration::from_secs(1);
+
+/// How candidate positions for an item are scored.
+/// The position with the best (lowest) score is chosen.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub enum Heuristic {
+    /// Prefer positions where the item touches as many occupied cells and bin
+    /// edges as possible. This is the default.
+    #[default]
+    MaxContact,
+    /// Place each item at the first free position, in scan order (see [`ScanOrder`]).
+    /// With the default scan order, this means as far up as possible, and then as far left as possible.
+    FirstFit,
+    /// Like `MaxContact`, but with configurable penalties. This makes it possible to,
+    /// for instance, prefer positions in corners over positions along a single edge.
+    WeightedContact(ContactWeights),
+}
+
+/// How to treat placements which leave narrow strips of free space (slivers) next to
+/// the item, between it and another item or the edge of the bin. Such strips are often
+/// useless, or even impossible to cut safely.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub enum SliverPolicy {
+    /// Slivers are allowed. This is the default.
+    #[default]
+    Allow,
+    /// Add 'penalty' to the score of a position for each side of the item with a
+    /// free strip narrower than 'min_width'.
+    Penalize {
+        /// Free strips narrower than this are considered slivers
+        min_width: usize,
+        /// Penalty per side of the item with a sliver
+        penalty: usize,
+    },
+    /// Never place items such that a free strip narrower than 'min_width' is left next to them.
+    Forbid {
+        /// Free strips narrower than this are considered slivers
+        min_width: usize,
+    },
+}
+
+/// Where the spacing set using [`PlaceOptions::with_spacing`] is required. With tabs (bridges)
+/// holding the parts, laser cut parts may share a straight edge, which is cut once, while
+/// the kerf must be kept everywhere else.
+///
+/// Items with margins (see 'Item::margins') always keep them, and never share edges.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub enum SpacingPolicy {
+    /// The spacing is kept between all items. This is the default.
+    #[default]
+    Uniform,
+    /// Items may be placed directly against each other, sharing (part of) an edge. The spacing
+    /// is still kept from all other items, including those only touching at a corner.
+    SharedEdgeAllowed,
+    /// Like `SharedEdgeAllowed`, but only between items which both have all of the given tags
+    /// (see 'Item::with_tags'), such as the parts held by tabs. Other items keep the spacing.
+    SharedEdgeTagged(Tags),
+}
+
+/// How to choose between candidate positions with the same score.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub enum TieBreak {
+    /// The position found first in scan order (see [`ScanOrder`]) wins. This is the default.
+    #[default]
+    ScanOrder,
+    /// The position which least increases the area of the bounding box of all placed
+    /// items wins, with remaining ties resolved in scan order. This tends to give more
+    /// compact layouts, for instance for texture atlases.
+    SmallestBoundingBox,
+}
+
+/// How [`crate::MultiBin::place_all`] chooses the bin for each item.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub enum BinSelection {
+    /// Fill the bins one after the other, in the order they were added. Each bin is packed
+    /// using all passes of the options. This is the default.
+    #[default]
+    FirstFit,
+    /// Place each item in the bin with the least free area in which it fits, which tends to
+    /// fill some bins completely and leave others empty
+    BestFit,
+    /// Place each item in the bin with the most free area in which it fits, which tends to
+    /// spread the items evenly over the bins
+    WorstFit,
+}
+
+/// A trade-off between packing time and quality, see [`PlaceOptions::with_quality`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Quality {
+    /// A single pass, which rotates items where this fits better
+    Fast,
+    /// The default passes
+    Balanced,
+    /// The default passes, followed by a final search for the last few items
+    /// (see [`PlaceOptions::with_final_search`])
+    Best,
+}
+
+/// How many cells of an item may overlap previously placed items.
+/// This is useful when items are raster approximations of non-rectangular shapes, where
+/// a small overlap of the bounding boxes is acceptable.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum OverlapTolerance {
+    /// At most this many cells of each item may overlap
+    Cells(usize),
+    /// At most this fraction of the area of each item may overlap (rounded down)
+    Fraction(f64),
+}
+
+impl Default for OverlapTolerance {
+    fn default() -> Self {
+        OverlapTolerance::Cells(0)
+    }
+}
+
+impl OverlapTolerance {
+    pub(crate) fn allowed_cells(&self, area: usize) -> usize {
+        match *self {
+            OverlapTolerance::Cells(cells) => cells,
+            OverlapTolerance::Fraction(fraction) => (area as f64 * fraction.clamp(0.0, 1.0)) as usize,
+        }
+    }
+}
+
+/// Penalties used by [`Heuristic::WeightedContact`]. The score of a position is the sum of
+/// all penalties, and the position with the lowest score is chosen.
+///
+/// A side of an item is considered to be in contact if it is at the edge of the bin, or if
+/// any neighboring cell on that side is occupied.
+///
+/// The default weights give the same result as [`Heuristic::MaxContact`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct ContactWeights {
+    /// Penalty for each free cell directly adjacent to the item
+    pub free_cell: usize,
+    /// Penalty for each side of the item which has no contact
+    pub open_side: usize,
+    /// Penalty if there are no two adjacent sides of the item which are both in contact,
+    /// i.e. if the item is not placed in a corner.
+    pub no_corner: usize,
+}
+
+impl Default for ContactWeights {
+    fn default() -> Self {
+        ContactWeights {
+            free_cell: 1,
+            open_side: 0,
+            no_corner: 0,
+        }
+    }
+}
+
+impl Heuristic {
+    /// Score a position, given the number of free neighboring cells on each side
+    /// (left, right, top, bottom), and whether each side is in contact. Saturates at 'usize::MAX'.
+    pub(crate) fn contact_score(&self, free: [usize; 4], touching: [bool; 4]) -> usize {
+        let free_cells = free.into_iter().fold(0, usize::saturating_add);
+        match self {
+            Heuristic::WeightedContact(weights) => {
+                let [left, right, top, bottom] = touching;
+                let open_sides = touching.iter().filter(|x|!**x).count();
+                let corner = (left || right) && (top || bottom);
+                weights.free_cell.saturating_mul(free_cells)
+                    .saturating_add(weights.open_side.saturating_mul(open_sides))
+                    .saturating_add(if corner { 0 } else { weights.no_corner })
+            }
+            _ => free_cells,
+        }
+    }
+}
+
+/// Options for a single call to [`crate::Bin::place_all`].
+///
+/// Use `PlaceOptions::default()` and adjust with the `with_*`-methods:
+///
+/// ```rust
+/// use bin_packing_2d::{Heuristic, PlaceOptions};
+/// let options = PlaceOptions::default()
+///     .with_spacing(1)
+///     .with_heuristic(Heuristic::FirstFit)
+///     .with_cancel(|| false);
+/// ```
+pub struct PlaceOptions<'a> {
+    strategies: Vec<Strategy>,
+    cancel: Option<Box<dyn FnMut() -> bool + 'a>>,
+    deadline: Option<Instant>,
+    soft_deadline: Option<Instant>,
+    spacing: usize,
+    spacing_policy: SpacingPolicy,
+    heuristic: Heuristic,
+    seed: Option<u64>,
+    deduplicate: bool,
+    validate: bool,
+    candidate_stats: bool,
+    scan_order: ScanOrder,
+    sliver_policy: SliverPolicy,
+    min_offcut: Option<usize>,
+    overlap_tolerance: OverlapTolerance,
+    tie_break: TieBreak,
+    bin_selection: BinSelection,
+    shared_cut_weight: usize,
+    final_search: Option<(usize, Duration)>,
+    utilization_target: Option<f64>,
+    #[cfg(feature = "gpu")]
+    gpu: Option<&'a GpuEvaluator>,
+    #[cfg(feature = "rand_core")]
+    rng: Option<Box<dyn RngCore + 'a>>,
+    cancelled: bool,
+}
+
+impl Default for PlaceOptions<'_> {
+    fn default() -> Self {
+        PlaceOptions {
+            strategies: vec![Strategy::DoNotRotate, Strategy::Rotate, Strategy::RotateIfSuitable],
+            cancel: None,
+            deadline: None,
+            soft_deadline: None,
+            spacing: 0,
+            spacing_policy: SpacingPolicy::default(),
+            heuristic: Heuristic::default(),
+            seed: None,
+            deduplicate: true,
+            validate: false,
+            candidate_stats: false,
+            scan_order: ScanOrder::default(),
+            sliver_policy: SliverPolicy::default(),
+            min_offcut: None,
+            overlap_tolerance: OverlapTolerance::default(),
+            tie_break: TieBreak::default(),
+            bin_selection: BinSelection::default(),
+            shared_cut_weight: 0,
+            final_search: None,
+            utilization_target: None,
+            #[cfg(feature = "gpu")]
+            gpu: None,
+            #[cfg(feature = "rand_core")]
+            rng: None,
+            cancelled: false,
+        }
+    }
+}
+
+impl fmt::Debug for PlaceOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("PlaceOptions");
+        debug
+            .field("strategies", &self.strategies)
+            .field("cancel", &self.cancel.is_some())
+            .field("deadline", &self.deadline)
+            .field("soft_deadline", &self.soft_deadline)
+            .field("spacing", &self.spacing)
+            .field("spacing_policy", &self.spacing_policy)
+            .field("heuristic", &self.heuristic)
+            .field("seed", &self.seed)
+            .field("deduplicate", &self.deduplicate)
+            .field("validate", &self.validate)
+            .field("candidate_stats", &self.candidate_stats)
+            .field("scan_order", &self.scan_order)
+            .field("sliver_policy", &self.sliver_policy)
+            .field("min_offcut", &self.min_offcut)
+            .field("overlap_tolerance", &self.overlap_tolerance)
+            .field("tie_break", &self.tie_break)
+            .field("bin_selection", &self.bin_selection)
+            .field("shared_cut_weight", &self.shared_cut_weight)
+            .field("final_search", &self.final_search)
+            .field("utilization_target", &self.utilization_target);
+        #[cfg(feature = "gpu")]
+        debug.field("gpu", &self.gpu.is_some());
+        #[cfg(feature = "rand_core")]
+        debug.field("rng", &self.rng.is_some());
+        debug.finish()
+    }
+}
+
+impl<'a> PlaceOptions<'a> {
+    /// The passes to attempt, in order. Each pass starts over from an empty bin,
+    /// and packing stops at the first pass that places all items.
+    ///
+    /// Default is `[DoNotRotate, Rotate, RotateIfSuitable]`.
+    pub fn with_strategies(mut self, strategies: impl IntoIterator<Item=Strategy>) -> Self {
+        self.strategies = strategies.into_iter().collect();
+        self
+    }
+    /// A callback which is polled regularly during packing. If it returns true,
+    /// packing is aborted and `place_all` returns false.
+    ///
+    /// The bin is then left with the best layout found so far: if an earlier pass placed
+    /// more than the interrupted one, the layout of that pass is restored. To interrupt
+    /// packing from another thread, the callback can read a shared `Arc<AtomicBool>`.
+    pub fn with_cancel(mut self, cancel: impl FnMut() -> bool + 'a) -> Self {
+        self.cancel = Some(Box::new(cancel));
+        self
+    }
+    /// Abort packing when this point in time has passed.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+    /// Lower the quality when this point in time has passed, instead of aborting: the current
+    /// pass is finished, but no further passes or final search are started. Since a pass is
+    /// always finished, packing may take longer than this. `PackingStats::degraded` tells
+    /// whether the quality was lowered.
+    pub fn with_soft_deadline(mut self, deadline: Instant) -> Self {
+        self.soft_deadline = Some(deadline);
+        self
+    }
+    /// Set the passes and final search according to 'quality'. 'Fast' always runs a single
+    /// pass, while 'Balanced', the default, runs up to three if not all items fit. 'Best' may
+    /// take up to a second longer than 'Balanced'. Later calls to 'with_strategies' or
+    /// 'with_final_search' override this.
+    pub fn with_quality(mut self, quality: Quality) -> Self {
+        let defaults = PlaceOptions::default();
+        (self.strategies, self.final_search) = match quality {
+            Quality::Fast => (vec![Strategy::RotateIfSuitable], None),
+            Quality::Balanced => (defaults.strategies, None),
+            Quality::Best => (defaults.strategies, Some((BEST_SEARCH_ITEMS, BEST_SEARCH_BUDGET))),
+        };
+        self
+    }
+    /// Minimum number of free cells between any two placed items.
+    /// Items may still be placed directly against the edges of the bin.
+    /// Default is 0.
+    pub fn with_spacing(mut self, spacing: usize) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    /// Where the spacing is required, for instance to let parts held by tabs share edges.
+    /// Default is [`SpacingPolicy::Uniform`].
+    pub fn with_spacing_policy(mut self, spacing_policy: SpacingPolicy) -> Self {
+        self.spacing_policy = spacing_policy;
+        self
+    }
+    /// How candidate positions are scored. Default is [`Heuristic::MaxContact`].
+    pub fn with_heuristic(mut self, heuristic: Heuristic) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+    /// Seed used to shuffle the order of equally sized items.
+    /// Default is `None`, which keeps equally sized items in input order.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+    /// Random number generator used to shuffle the order of equally sized items, instead of
+    /// the generator seeded using 'with_seed', for instance to use a fixed sequence in tests.
+    /// Each call to 'place_all' (or similar) with these options draws new numbers from it.
+    #[cfg(feature = "rand_core")]
+    pub fn with_rng(mut self, rng: impl RngCore + 'a) -> Self {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+    /// Recognize identical items (same width, height and `allow_rotate`), and
+    /// don't search for a position for an item if an identical item has already
+    /// failed to fit in the same pass. Also avoids evaluating both orientations of square items,
+    /// and uses a faster search for positions of square items.
+    /// This does not change the result, only the speed.
+    ///
+    /// Default is true.
+    pub fn with_deduplication(mut self, deduplicate: bool) -> Self {
+        self.deduplicate = deduplicate;
+        self
+    }
+    /// Check each item using `Item::validate` before placing it. Items which are empty or
+    /// so large that their coordinates overflow are then rejected with `RejectReason::Invalid`,
+    /// instead of causing a panic.
+    ///
+    /// Default is false.
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+    /// Count how the candidate positions evaluated for each item turned out, for tuning
+    /// heuristics. The counts are available using `Bin::candidate_counts` after packing.
+    ///
+    /// Default is false.
+    pub fn with_candidate_stats(mut self, candidate_stats: bool) -> Self {
+        self.candidate_stats = candidate_stats;
+        self
+    }
+    /// The order in which candidate positions are searched, which determines where in
+    /// the bin packing starts. Default is `ScanOrder::Rows(Corner::TopLeft)`.
+    pub fn with_scan_order(mut self, scan_order: ScanOrder) -> Self {
+        self.scan_order = scan_order;
+        self
+    }
+    /// How to treat placements which leave narrow strips of free space next to the item.
+    /// Default is [`SliverPolicy::Allow`].
+    pub fn with_sliver_policy(mut self, sliver_policy: SliverPolicy) -> Self {
+        self.sliver_policy = sliver_policy;
+        self
+    }
+    /// Guarantee that every leftover free area is reusable: after each placement, every
+    /// maximal free rectangle (see `Bin::free_rectangles`) must be at least 'min_offcut'
+    /// in both width and height. Items which cannot be placed without violating this are
+    /// not placed, and counted in `PackingStats::offcut_rejections`.
+    ///
+    /// This is considerably slower than normal packing, and only the 256 best
+    /// positions for each item are tried. Default is no minimum.
+    pub fn with_min_offcut(mut self, min_offcut: usize) -> Self {
+        self.min_offcut = Some(min_offcut);
+        self
+    }
+    /// Allow items to overlap previously placed items by a limited number of cells.
+    /// Positions with less overlap are preferred. The overlap of each item is reported in
+    /// `PlacedItem::overlap`. The spacing around items (see 'with_spacing') must still be free.
+    ///
+    /// Default is `OverlapTolerance::Cells(0)`, i.e. no overlap.
+    pub fn with_overlap_tolerance(mut self, overlap_tolerance: OverlapTolerance) -> Self {
+        self.overlap_tolerance = overlap_tolerance;
+        self
+    }
+    /// How to choose between positions with the same score. Default is [`TieBreak::ScanOrder`].
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+    /// How `MultiBin::place_all` chooses the bin for each item. Has no effect on a single bin.
+    /// Default is [`BinSelection::FirstFit`].
+    pub fn with_bin_selection(mut self, bin_selection: BinSelection) -> Self {
+        self.bin_selection = bin_selection;
+        self
+    }
+    /// Prefer positions where the item shares cut lines with items placed earlier (common-line
+    /// cutting), which reduces the number of cuts on a panel saw: 'weight' is added to the
+    /// score of a position for each unit of the perimeter of the item which does not face the
+    /// edge of another item across the spacing (see `Bin::shared_cut_length`).
+    ///
+    /// Default is 0, which disables this.
+    pub fn with_shared_cut_weight(mut self, weight: usize) -> Self {
+        self.shared_cut_weight = weight;
+        self
+    }
+    /// If 'place_all' leaves at most 'max_items' items unplaced, make a final attempt to place
+    /// them: the unplaced items and the last items placed are removed, and every order and
+    /// orientation of them is tried, until all fit or 'budget' has passed. The number of
+    /// combinations grows very quickly with 'max_items', so it should be small, such as 5.
+    /// Default is no final search.
+    pub fn with_final_search(mut self, max_items: usize, budget: Duration) -> Self {
+        self.final_search = Some((max_items, budget));
+        self
+    }
+    /// Stop once a pass has placed every item which is not 'Item::optional', and the utilization
+    /// of the bin is at least 'target' (between 0 and 1). Otherwise, further passes are tried
+    /// as long as optional items are left, to fit as many of them as possible.
+    /// Default is no target.
+    pub fn with_utilization_target(mut self, target: f64) -> Self {
+        self.utilization_target = Some(target);
+        self
+    }
+    /// Search for the best position of each item on the GPU, using 'gpu', which is much faster
+    /// for large bins. Unlike the normal search, which stops early once positions have been
+    /// found, all positions are evaluated, so the result may differ (and is usually slightly better).
+    ///
+    /// This is experimental, and only supports the 'MaxContact' heuristic and the default scan
+    /// order and tie-break, without spacing, sliver policy, shared cut weight, overlap tolerance,
+    /// margins (of the item or of placed items), bands, extent limit, zones, regions or forbidden
+    /// areas, unloading order or placed 'prefer_near' partners. Items for which any of these are in
+    /// use, and all items on bins that don't use the dense backend or are too large for the GPU
+    /// (see [`crate::Backend`]), are placed by the normal search.
+    /// Positions evaluated on the GPU are not included in 'Bin::candidate_counts'.
+    /// Default is no GPU.
+    #[cfg(feature = "gpu")]
+    pub fn with_gpu(mut self, gpu: &'a GpuEvaluator) -> Self {
+        self.gpu = Some(gpu);
+        self
+    }
+
+    pub(crate) fn strategies(&self) -> &[Strategy] {
+        &self.strategies
+    }
+    pub(crate) fn spacing(&self) -> usize {
+        self.spacing
+    }
+    pub(crate) fn spacing_policy(&self) -> SpacingPolicy {
+        self.spacing_policy
+    }
+    pub(crate) fn heuristic(&self) -> Heuristic {
+        self.heuristic
+    }
+    pub(crate) fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    /// True if a random number generator is set
+    pub(crate) fn has_rng(&self) -> bool {
+        #[cfg(feature = "rand_core")]
+        return self.rng.is_some();
+        #[cfg(not(feature = "rand_core"))]
+        false
+    }
+    /// Shuffle 'items' using the random number generator, or the seed, if set
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        #[cfg(feature = "rand_core")]
+        if let Some(rng) = &mut self.rng {
+            shuffle_with(items, || rng.next_u64());
+            return;
+        }
+        if let Some(seed) = self.seed {
+            shuffle(items, seed);
+        }
+    }
+    pub(crate) fn deduplicate(&self) -> bool {
+        self.deduplicate
+    }
+    pub(crate) fn validate(&self) -> bool {
+        self.validate
+    }
+    pub(crate) fn candidate_stats(&self) -> bool {
+        self.candidate_stats
+    }
+    pub(crate) fn scan_order(&self) -> ScanOrder {
+        self.scan_order
+    }
+    pub(crate) fn sliver_policy(&self) -> SliverPolicy {
+        self.sliver_policy
+    }
+    pub(crate) fn min_offcut(&self) -> Option<usize> {
+        self.min_offcut
+    }
+    pub(crate) fn overlap_tolerance(&self) -> OverlapTolerance {
+        self.overlap_tolerance
+    }
+    pub(crate) fn tie_break(&self) -> TieBreak {
+        self.tie_break
+    }
+    pub(crate) fn bin_selection(&self) -> BinSelection {
+        self.bin_selection
+    }
+    pub(crate) fn shared_cut_weight(&self) -> usize {
+        self.shared_cut_weight
+    }
+    pub(crate) fn final_search(&self) -> Option<(usize, Duration)> {
+        self.final_search
+    }
+    pub(crate) fn utilization_target(&self) -> Option<f64> {
+        self.utilization_target
+    }
+    pub(crate) fn soft_deadline(&self) -> Option<Instant> {
+        self.soft_deadline
+    }
+    /// True if the soft deadline has passed
+    pub(crate) fn should_degrade(&self) -> bool {
+        self.soft_deadline.is_some_and(|deadline|Instant::now() >= deadline)
+    }
+    #[cfg(feature = "gpu")]
+    pub(crate) fn gpu(&self) -> Option<&'a GpuEvaluator> {
+        self.gpu
+    }
+
+    /// True if the user has requested cancellation, or the deadline has passed.
+    /// Once cancelled, this keeps returning true.
+    pub(crate) fn is_cancelled(&mut self) -> bool {
+        if self.cancelled {
+            return true;
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                self.cancelled = true;
+            }
+        }
+        if let Some(cancel) = &mut self.cancel {
+            self.cancelled |= cancel();
+        }
+        self.cancelled
+    }
+
+    /// True if 'is_cancelled' has returned true
+    pub(crate) fn was_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Feed all options which affect the result of packing into 'state'.
+    pub(crate) fn hash_config<H: Hasher>(&self, state: &mut H) {
+        self.strategies.hash(state);
+        self.spacing.hash(state);
+        self.spacing_policy.hash(state);
+        self.heuristic.hash(state);
+        self.seed.hash(state);
+        self.scan_order.hash(state);
+        self.sliver_policy.hash(state);
+        self.min_offcut.hash(state);
+        self.tie_break.hash(state);
+        self.shared_cut_weight.hash(state);
+        // The time budget only limits how long the search runs
+        self.final_search.map(|(max_items, _)|max_items).hash(state);
+        self.utilization_target.map(f64::to_bits).hash(state);
+        // Only hashed if set, so that keys don't depend on the enabled features
+        #[cfg(feature = "gpu")]
+        if self.gpu.is_some() {
+            "gpu".hash(state);
+        }
+        match self.overlap_tolerance {
+            OverlapTolerance::Cells(cells) => (0u8, cells as u64).hash(state),
+            OverlapTolerance::Fraction(fraction) => (1u8, fraction.to_bits()).hash(state),
+        }
+    }
+}
+
+/// Deterministic Fisher-Yates shuffle, using the splitmix64 generator.
+pub(crate) fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    shuffle_with(items, || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    });
+}
+
+/// Fisher-Yates shuffle, using 'next' for random numbers
+fn shuffle_with<T>(items: &mut [T], mut next: impl FnMut() -> u64) {
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_score() {
+        let weights = ContactWeights { free_cell: 1, open_side: 5, no_corner: 100 };
+        let heuristic = Heuristic::WeightedContact(weights);
+        // Contact on top and bottom only
+        assert_eq!(heuristic.contact_score([2, 2, 0, 0], [false, false, true, true]), 4 + 10 + 100);
+        // Contact on top and left
+        assert_eq!(heuristic.contact_score([0, 2, 0, 2], [true, false, true, false]), 4 + 10);
+        let default = Heuristic::WeightedContact(ContactWeights::default());
+        assert_eq!(default.contact_score([1, 2, 3, 4], [false; 4]), Heuristic::MaxContact.contact_score([1, 2, 3, 4], [false; 4]));
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn test_rng() {
+        use crate::stepwise::sort_items;
+        use crate::Item;
+        struct Zeros;
+        impl RngCore for Zeros {
+            fn next_u32(&mut self) -> u32 { 0 }
+            fn next_u64(&mut self) -> u64 { 0 }
+            fn fill_bytes(&mut self, dest: &mut [u8]) { dest.fill(0) }
+        }
+        let mut items: Vec<Item<usize>> = (0..4).map(|x|Item::new(2, 2, x)).collect();
+        // The generator takes precedence over the seed
+        let mut options = PlaceOptions::default().with_seed(1).with_rng(Zeros);
+        sort_items(&mut items, &mut options, None);
+        assert_eq!(items.iter().map(|x|x.id).collect::<Vec<_>>(), vec![1, 2, 3, 0]);
+    }
+}
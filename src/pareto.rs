@@ -0,0 +1,109 @@
+use crate::{adjacency, Bin, Corner, Heuristic, Item, PlaceOptions, PlacedItem, ScanOrder, Strategy};
+
+/// The objectives a layout is judged by, see [`Bin::pareto_layouts`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct LayoutScore {
+    /// The total area of the placed items. Higher is better.
+    pub placed_area: usize,
+    /// The total length of cuts needed to separate the placed items from each other and from
+    /// the rest of the bin (see `Bin::cut_length`). Lower is better.
+    pub cut_length: usize,
+    /// The number of rotated items. Lower is better.
+    pub rotated: usize,
+}
+
+impl LayoutScore {
+    /// True if this score is at least as good as 'other' in every objective, and better in at least one
+    pub fn dominates(&self, other: &LayoutScore) -> bool {
+        let at_least_as_good = self.placed_area >= other.placed_area && self.cut_length <= other.cut_length && self.rotated <= other.rotated;
+        at_least_as_good && self != other
+    }
+}
+
+/// One of the layouts returned by [`Bin::pareto_layouts`].
+#[derive(Debug,Clone)]
+pub struct ParetoLayout<I:Clone> {
+    /// The placed items, including any items which were already in the bin
+    pub solution: Vec<PlacedItem<I>>,
+    /// True if all items were placed
+    pub all_fit: bool,
+    /// The score of the layout
+    pub score: LayoutScore,
+}
+
+/// The total length of the edges of 'items' which do not lie on the border of the bin,
+/// counting edges shared by two items once. Saturates at 'usize::MAX'.
+pub(crate) fn cut_length<I:Clone>(items: &[PlacedItem<I>], width: usize, height: usize) -> usize {
+    let edges = items.iter().map(|item| {
+        let (w, h) = (item.width(), item.height());
+        let horizontal = if item.y0 == 0 { 0 } else { w }.saturating_add(if item.y1 == height { 0 } else { w });
+        let vertical = if item.x0 == 0 { 0 } else { h }.saturating_add(if item.x1 == width { 0 } else { h });
+        horizontal.saturating_add(vertical)
+    }).fold(0, usize::saturating_add);
+    let shared = adjacency::adjacency(items).iter().map(|x|x.length).fold(0, usize::saturating_add);
+    edges.saturating_sub(shared)
+}
+
+/// Pack 'items' into copies of 'bin' with a range of settings, and keep the non-dominated layouts.
+pub(crate) fn pareto_layouts<'o, I:Clone>(bin: &Bin<I>, items: &[Item<I>], options: &dyn Fn() -> PlaceOptions<'o>, seeds: u64) -> Vec<ParetoLayout<I>> {
+    let strategies = [Strategy::DoNotRotate, Strategy::RotateIfSuitable, Strategy::Rotate];
+    let heuristics = [Heuristic::MaxContact, Heuristic::FirstFit];
+    let scan_orders = [ScanOrder::Rows(Corner::TopLeft), ScanOrder::Columns(Corner::TopLeft)];
+    let mut front: Vec<ParetoLayout<I>> = vec![];
+    for seed in (0..=seeds).map(|x|x.checked_sub(1)) {
+        for strategy in strategies {
+            for heuristic in heuristics {
+                for scan_order in scan_orders {
+                    let mut options = options()
+                        .with_strategies([strategy])
+                        .with_heuristic(heuristic)
+                        .with_scan_order(scan_order);
+                    if let Some(seed) = seed {
+                        options = options.with_seed(seed);
+                    }
+                    let mut attempt = bin.clone();
+                    attempt.autosave = None;
+                    let all_fit = attempt.place_all(items.iter().cloned(), options);
+                    if attempt.stats().cancelled {
+                        return front;
+                    }
+                    let solution = attempt.take_solution();
+                    let score = LayoutScore {
+                        placed_area: solution.iter().map(|x|x.rect().area()).fold(0, usize::saturating_add),
+                        cut_length: cut_length(&solution, bin.width(), bin.height()),
+                        rotated: solution.iter().filter(|x|x.rotated()).count(),
+                    };
+                    // Layouts with the same score as one already found are not added
+                    if front.iter().any(|x|x.score.dominates(&score) || x.score == score) {
+                        continue;
+                    }
+                    front.retain(|x|!score.dominates(&x.score));
+                    front.push(ParetoLayout { solution, all_fit, score });
+                }
+            }
+        }
+    }
+    front.sort_by_key(|x|(std::cmp::Reverse(x.score.placed_area), x.score.cut_length, x.score.rotated));
+    front
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rotation, Tags};
+
+    #[test]
+    fn test_pareto_layouts() {
+        let bin = Bin::new(10, 10);
+        assert_eq!(cut_length(&[PlacedItem { x0: 0, y0: 0, x1: 5, y1: 10, rotation: Rotation::None, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: 0 }], 10, 10), 10);
+        let items: Vec<_> = (0..6).map(|id| Item::rotatable(6, 3, id)).collect();
+        let front = bin.pareto_layouts(&items, PlaceOptions::default, 2);
+        assert!(!front.is_empty());
+        for a in &front {
+            assert!(!front.iter().any(|b|b.score.dominates(&a.score)));
+        }
+        // Without rotation, only three items fit, and none are rotated
+        assert!(front.iter().any(|x|x.score.rotated == 0));
+        assert!(front.windows(2).all(|x|x[0].score.placed_area >= x[1].score.placed_area));
+    }
+}
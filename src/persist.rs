@@ -0,0 +1,342 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::{PackConfig, PlacedItem, Rotation, Tags};
+
+/// The reason a set of placed items could not be loaded into a bin.
+/// See [`crate::Bin::from_solution`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum SolutionError {
+    /// The item with the given index has zero width or height
+    Empty(usize),
+    /// The item with the given index extends outside the bin
+    OutOfBounds(usize),
+    /// The item with the given index overlaps an earlier item
+    Overlap(usize),
+    /// The item with the given index lies within the margins of an earlier item, or
+    /// an earlier item lies within its margins
+    Margin(usize),
+    /// The item with the given index lies outside the regions it is restricted to, or
+    /// intersects a region forbidden to it (see `Bin::verify_regions`)
+    Region(usize),
+    /// The item with the given index does not match any of the items to be placed, by id
+    /// and size (see `Bin::place_all_seeded`)
+    Unknown(usize),
+}
+
+impl Display for SolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolutionError::Empty(index) => write!(f, "placed item #{} has zero size", index),
+            SolutionError::OutOfBounds(index) => write!(f, "placed item #{} extends outside the bin", index),
+            SolutionError::Overlap(index) => write!(f, "placed item #{} overlaps another item", index),
+            SolutionError::Margin(index) => write!(f, "placed item #{} violates the margins of another item", index),
+            SolutionError::Region(index) => write!(f, "placed item #{} violates its region constraints", index),
+            SolutionError::Unknown(index) => write!(f, "placed item #{} is not one of the items to place", index),
+        }
+    }
+}
+
+impl Error for SolutionError {}
+
+/// The version of the format written by [`write_solution`]. Version 1 had no header line and
+/// did not include the tags, versions 1 and 2 gave the rotation as 0 or 1 instead of in
+/// degrees, and versions before 4 could not include the configuration. Files of older versions
+/// can still be read, and can be converted using [`migrate_solution`].
+pub const SOLUTION_FORMAT_VERSION: u32 = 4;
+
+/// The start of the header line giving the version of the format
+const HEADER: &str = "# bin_packing_2d solution ";
+
+/// The start of a line giving an option of the configuration
+const CONFIG: &str = "# config ";
+
+/// A line of a solution file
+enum Entry<I:Clone> {
+    Item(PlacedItem<I>),
+    Config(String, String),
+}
+
+/// Write placed items in a simple line based text format, which can be read back
+/// using [`read_solution`]. The first line is a header giving the version of the format
+/// (see [`SOLUTION_FORMAT_VERSION`]). Each item is then written on its own line, as the
+/// tab-separated fields `x0 y0 x1 y1 rotation overlap left right top bottom tags id`, where
+/// 'rotation' is in degrees (see `Rotation::degrees`), 'left' to 'bottom' are the margins and
+/// 'tags' are the bits of the tags.
+///
+/// The `Display` representation of the id must not contain line breaks.
+///
+/// The items are written as they are taken from 'items', so a large solution can be
+/// exported in parts, for instance using `chunks` on the solution, without formatting all
+/// of it in memory first. Each part starts with the header, which is allowed to be repeated.
+pub fn write_solution<'a, I:Clone+Display+'a>(mut writer: impl Write, items: impl IntoIterator<Item=&'a PlacedItem<I>>) -> io::Result<()> {
+    writeln!(writer, "{}{}", HEADER, SOLUTION_FORMAT_VERSION)?;
+    write_items(writer, items)
+}
+
+/// Like [`write_solution`], but also write 'config', the configuration the items were packed
+/// with, after the header, as lines of the form `# config key<tab>value`. The name of the
+/// configuration must not contain line breaks.
+pub fn write_solution_with_config<'a, I:Clone+Display+'a>(mut writer: impl Write, config: &PackConfig, items: impl IntoIterator<Item=&'a PlacedItem<I>>) -> io::Result<()> {
+    writeln!(writer, "{}{}", HEADER, SOLUTION_FORMAT_VERSION)?;
+    for (key, value) in config.fields() {
+        writeln!(writer, "{}{}\t{}", CONFIG, key, value)?;
+    }
+    write_items(writer, items)
+}
+
+fn write_items<'a, I:Clone+Display+'a>(mut writer: impl Write, items: impl IntoIterator<Item=&'a PlacedItem<I>>) -> io::Result<()> {
+    for item in items {
+        let [left, right, top, bottom] = item.margins;
+        writeln!(writer, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}", item.x0, item.y0, item.x1, item.y1, item.rotation.degrees(), item.overlap,
+            left, right, top, bottom, item.tags.0, item.id)?;
+    }
+    writer.flush()
+}
+
+/// Read placed items written by [`write_solution`], in any version of the format. The
+/// configuration, if any, is skipped.
+pub fn read_solution<I:Clone+FromStr>(reader: impl BufRead) -> io::Result<Vec<PlacedItem<I>>> {
+    read_solution_iter(reader).collect()
+}
+
+/// Read placed items written by [`write_solution`] one at a time, so that a large solution
+/// can be processed without holding all of it in memory. Reading stops after the first error.
+pub fn read_solution_iter<I:Clone+FromStr>(reader: impl BufRead) -> impl Iterator<Item=io::Result<PlacedItem<I>>> {
+    read_entries(reader).filter_map(|entry| match entry {
+        Ok(Entry::Item(item)) => Some(Ok(item)),
+        Ok(Entry::Config(..)) => None,
+        Err(error) => Some(Err(error)),
+    })
+}
+
+/// Read placed items written by [`write_solution_with_config`], together with the configuration
+/// they were packed with, or None if the solution does not include it. Fails if the
+/// configuration includes unknown options, for instance because it was written by a newer release.
+pub fn read_solution_with_config<I:Clone+FromStr>(reader: impl BufRead) -> io::Result<(Option<PackConfig>, Vec<PlacedItem<I>>)> {
+    let (mut config, mut items) = (None, vec![]);
+    for entry in read_entries(reader) {
+        match entry? {
+            Entry::Item(item) => items.push(item),
+            Entry::Config(key, value) => {
+                config.get_or_insert_with(||PackConfig::new("")).set(&key, &value).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("invalid configuration option '{}' with value '{}'", key, value))
+                })?;
+            },
+        }
+    }
+    Ok((config, items))
+}
+
+/// Read placed items written by [`write_solution_with_config`], for resuming packing using
+/// 'config', for instance using `Bin::from_solution`. Fails if the solution does not include
+/// its configuration, or was packed using a configuration which differs from 'config' (see
+/// `PackConfig::difference`), since the result could then not be reproduced.
+pub fn read_solution_for_config<I:Clone+FromStr>(reader: impl BufRead, config: &PackConfig) -> io::Result<Vec<PlacedItem<I>>> {
+    let mismatch = |message: String|io::Error::new(io::ErrorKind::InvalidData, message);
+    match read_solution_with_config(reader)? {
+        (None, _) => Err(mismatch("the solution does not include its configuration".to_string())),
+        (Some(saved), items) => match saved.difference(config) {
+            Some(option) => Err(mismatch(format!("the solution was packed using configuration '{}', which differs from '{}' in option '{}'", saved.name, config.name, option))),
+            None => Ok(items),
+        },
+    }
+}
+
+/// The items and configuration lines of a solution. Reading stops after the first error.
+fn read_entries<I:Clone+FromStr>(reader: impl BufRead) -> impl Iterator<Item=io::Result<Entry<I>>> {
+    let mut failed = false;
+    // Files without a header are of version 1
+    let mut version = 1;
+    reader.lines().enumerate().filter(|(_, line)|!line.as_ref().is_ok_and(|x|x.is_empty())).map_while(move |(line_number, line)| {
+        if failed {
+            return None;
+        }
+        let entry = line.and_then(|line| match parse_header(&line, line_number) {
+            Some(header) => header.map(|header| {
+                version = header;
+                None
+            }),
+            None => match parse_config(&line, version) {
+                Some((key, value)) => Ok(Some(Entry::Config(key.to_string(), value.to_string()))),
+                None => parse_line(&line, line_number, version).map(|x|Some(Entry::Item(x))),
+            },
+        });
+        failed = entry.is_err();
+        // Header lines give no entry
+        Some(entry.transpose())
+    }).flatten()
+}
+
+/// Convert a solution written by any version of [`write_solution`] to the current version,
+/// without parsing the ids. Returns the version the solution was written in.
+pub fn migrate_solution(reader: impl BufRead, mut writer: impl Write) -> io::Result<u32> {
+    let mut from_version = None;
+    let mut version = 1;
+    writeln!(writer, "{}{}", HEADER, SOLUTION_FORMAT_VERSION)?;
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = parse_header(&line, line_number) {
+            version = header?;
+            from_version.get_or_insert(version);
+            continue;
+        }
+        from_version.get_or_insert(version);
+        if parse_config(&line, version).is_some() {
+            writeln!(writer, "{}", line)?;
+            continue;
+        }
+        // Check the line, so that invalid files are not silently passed on
+        parse_line::<String>(&line, line_number, version)?;
+        let mut fields: Vec<&str> = line.splitn(if version == 1 { 11 } else { 12 }, '\t').collect();
+        if version == 1 {
+            // Insert the tags before the id
+            fields.insert(10, "0");
+        }
+        if version <= 2 && fields[4] == "1" {
+            fields[4] = "90";
+        }
+        writeln!(writer, "{}", fields.join("\t"))?;
+    }
+    writer.flush()?;
+    Ok(from_version.unwrap_or(SOLUTION_FORMAT_VERSION))
+}
+
+/// The version given by 'line', if it is a header line
+fn parse_header(line: &str, line_number: usize) -> Option<io::Result<u32>> {
+    let version = line.strip_prefix(HEADER)?;
+    Some(match version.parse() {
+        Ok(version) if (1..=SOLUTION_FORMAT_VERSION).contains(&version) => Ok(version),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported solution format version '{}' on line {}", version, line_number + 1))),
+    })
+}
+
+/// The key and value given by 'line', if it is a configuration line
+fn parse_config(line: &str, version: u32) -> Option<(&str, &str)> {
+    if version < 4 {
+        return None;
+    }
+    line.strip_prefix(CONFIG)?.split_once('\t')
+}
+
+/// Parse a single non-empty line of the given version of the solution format
+fn parse_line<I:Clone+FromStr>(line: &str, line_number: usize, version: u32) -> io::Result<PlacedItem<I>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid solution on line {}", line_number + 1));
+    let mut fields = line.splitn(if version == 1 { 11 } else { 12 }, '\t');
+    let number = |field: Option<&str>| -> io::Result<usize> {
+        field.and_then(|x|x.parse().ok()).ok_or_else(invalid)
+    };
+    let (x0, y0) = (number(fields.next())?, number(fields.next())?);
+    let (x1, y1) = (number(fields.next())?, number(fields.next())?);
+    let rotation = match (fields.next(), version) {
+        (Some("0"), _) => Rotation::None,
+        (Some("1"), 1..=2) => Rotation::Cw90,
+        (Some(degrees), 3..) => degrees.parse().ok().and_then(Rotation::from_degrees).ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+    };
+    let overlap = number(fields.next())?;
+    let mut margins = [0; 4];
+    for margin in &mut margins {
+        *margin = number(fields.next())?;
+    }
+    let tags = if version == 1 {
+        Tags::NONE
+    } else {
+        Tags(fields.next().and_then(|x|x.parse().ok()).ok_or_else(invalid)?)
+    };
+    let id = fields.next().and_then(|x|x.parse().ok()).ok_or_else(invalid)?;
+    Ok(PlacedItem { x0, y0, x1, y1, rotation, overlap, margins, tags, id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let items = vec![
+            PlacedItem { x0: 0, y0: 1, x1: 2, y1: 3, rotation: Rotation::Cw90, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: "first item".to_string() },
+            PlacedItem { x0: 4, y0: 5, x1: 6, y1: 7, rotation: Rotation::None, overlap: 2, margins: [1, 2, 3, 4], tags: Tags::NONE, id: "second".to_string() },
+        ];
+        let mut buffer = vec![];
+        write_solution(&mut buffer, &items).unwrap();
+        let read: Vec<PlacedItem<String>> = read_solution(&buffer[..]).unwrap();
+        assert_eq!(format!("{:?}", read), format!("{:?}", items));
+        assert!(read_solution::<String>(&b"1\t2\tx"[..]).is_err());
+    }
+
+    #[test]
+    fn test_versions() {
+        let items = vec![PlacedItem { x0: 0, y0: 1, x1: 2, y1: 3, rotation: Rotation::Cw270, overlap: 0, margins: [0; 4], tags: Tags(5), id: "a\tb".to_string() }];
+        let mut buffer = vec![];
+        write_solution(&mut buffer, &items).unwrap();
+        assert_eq!(buffer, b"# bin_packing_2d solution 4\n0\t1\t2\t3\t270\t0\t0\t0\t0\t0\t5\ta\tb\n");
+        let read: Vec<PlacedItem<String>> = read_solution(&buffer[..]).unwrap();
+        assert_eq!(format!("{:?}", read), format!("{:?}", items));
+        // Version 1 had no header and no tags
+        let old = b"0\t1\t2\t3\t1\t0\t0\t0\t0\t0\ta\tb\n";
+        let read: Vec<PlacedItem<String>> = read_solution(&old[..]).unwrap();
+        assert_eq!((read[0].id.as_str(), read[0].tags, read[0].rotation), ("a\tb", Tags::NONE, Rotation::Cw90));
+        let mut migrated = vec![];
+        assert_eq!(migrate_solution(&old[..], &mut migrated).unwrap(), 1);
+        assert_eq!(migrated, b"# bin_packing_2d solution 4\n0\t1\t2\t3\t90\t0\t0\t0\t0\t0\t0\ta\tb\n");
+        // Version 2 gave the rotation as 0 or 1
+        let old = b"# bin_packing_2d solution 2\n0\t1\t2\t3\t1\t0\t0\t0\t0\t0\t5\ta\n";
+        let mut migrated = vec![];
+        assert_eq!(migrate_solution(&old[..], &mut migrated).unwrap(), 2);
+        assert_eq!(migrated, b"# bin_packing_2d solution 4\n0\t1\t2\t3\t90\t0\t0\t0\t0\t0\t5\ta\n");
+        let mut current = vec![];
+        assert_eq!(migrate_solution(&buffer[..], &mut current).unwrap(), 4);
+        assert_eq!(current, buffer);
+        // Versions written by newer releases are rejected
+        assert!(read_solution::<String>(&b"# bin_packing_2d solution 5\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_chunked_roundtrip() {
+        let items: Vec<PlacedItem<usize>> = (0..1000).map(|x|PlacedItem { x0: x, y0: 0, x1: x + 1, y1: 1, rotation: Rotation::None, overlap: 0, margins: [0; 4], tags: Tags::NONE, id: x }).collect();
+        let mut buffer = vec![];
+        for chunk in items.chunks(300) {
+            write_solution(&mut buffer, chunk).unwrap();
+        }
+        let read: Vec<PlacedItem<usize>> = read_solution_iter(&buffer[..]).map(Result::unwrap).collect();
+        assert_eq!(read.len(), 1000);
+        assert_eq!(read[999].x0, 999);
+        // Reading stops at the first invalid line
+        let read: Vec<_> = read_solution_iter::<usize>(&b"0\t0\t1\t1\t0\t0\t0\t0\t0\t0\t5\n\nx\n0\t0\t1\t1\t0\t0\t0\t0\t0\t0\t6\n"[..]).collect();
+        assert_eq!(read.len(), 2);
+        assert!(read[1].is_err());
+    }
+
+    #[test]
+    fn test_config() {
+        use crate::{Bin, Item};
+        let config = PackConfig::new("sheets").with_options(|x|x.with_spacing(1).with_seed(3));
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all([Item::new(4, 4, 'A'), Item::new(3, 3, 'B')].into_iter(), config.options()));
+        let mut buffer = vec![];
+        write_solution_with_config(&mut buffer, &config, bin.solution()).unwrap();
+        let (read, items) = read_solution_with_config::<char>(&buffer[..]).unwrap();
+        assert_eq!(read.as_ref(), Some(&config));
+        assert_eq!(items.len(), 2);
+        assert_eq!(read_solution::<char>(&buffer[..]).unwrap().len(), 2);
+        // Resuming requires the same options, but not the same name
+        let items = read_solution_for_config::<char>(&buffer[..], &PackConfig { name: "renamed".to_string(), ..config.clone() }).unwrap();
+        let mut resumed = Bin::from_solution(10, 10, items).unwrap();
+        assert!(resumed.place_all([Item::new(2, 2, 'C')].into_iter(), config.options()));
+        let error = read_solution_for_config::<char>(&buffer[..], &config.clone().with_options(|x|x.with_spacing(2))).unwrap_err();
+        assert!(error.to_string().contains("option 'spacing'"), "{}", error);
+        let mut plain = vec![];
+        write_solution(&mut plain, bin.solution()).unwrap();
+        assert!(read_solution_for_config::<char>(&plain[..], &config).is_err());
+        // Configuration lines are kept when migrating
+        let mut migrated = vec![];
+        assert_eq!(migrate_solution(&buffer[..], &mut migrated).unwrap(), 4);
+        assert_eq!(migrated, buffer);
+    }
+}
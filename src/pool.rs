@@ -0,0 +1,211 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::{Backend, Bin, Item, PackingStats, PlaceOptions, PlacedItem, RejectReason};
+
+type OptionsFn = Box<dyn FnOnce() -> PlaceOptions<'static> + Send>;
+type SetupFn<I> = Box<dyn FnOnce(&mut Bin<I>) + Send>;
+
+/// A packing job, to be run by a [`PackerPool`].
+///
+/// Since options can't be sent between threads, the job describes how to create them
+/// on the worker thread.
+pub struct PackJob<I:Clone> {
+    width: usize,
+    height: usize,
+    backend: Backend,
+    items: Vec<Item<I>>,
+    options: OptionsFn,
+    setup: SetupFn<I>,
+}
+
+impl<I:Clone> PackJob<I> {
+    /// A job packing 'items' into an empty bin of the given size, using the default options
+    pub fn new(width: usize, height: usize, items: Vec<Item<I>>) -> PackJob<I> {
+        PackJob {
+            width,
+            height,
+            backend: Backend::default(),
+            items,
+            options: Box::new(PlaceOptions::default),
+            setup: Box::new(|_|{}),
+        }
+    }
+
+    /// Create the options on the worker thread using 'options'. A cancel callback set
+    /// by 'options' is replaced by the cancellation of the job handle.
+    pub fn with_options(mut self, options: impl FnOnce() -> PlaceOptions<'static> + Send + 'static) -> PackJob<I> {
+        self.options = Box::new(options);
+        self
+    }
+
+    /// Use the given backend for the bin, see `Bin::with_backend`
+    pub fn with_backend(mut self, backend: Backend) -> PackJob<I> {
+        self.backend = backend;
+        self
+    }
+
+    /// Configure the bin on the worker thread before packing, for instance to add zones
+    pub fn with_bin_setup(mut self, setup: impl FnOnce(&mut Bin<I>) + Send + 'static) -> PackJob<I> {
+        self.setup = Box::new(setup);
+        self
+    }
+}
+
+/// The result of a [`PackJob`].
+#[derive(Debug,Clone)]
+pub struct JobResult<I:Clone> {
+    /// True if all items were placed
+    pub all_fit: bool,
+    /// The placed items
+    pub solution: Vec<PlacedItem<I>>,
+    /// The items which could not be placed, see `Bin::rejections`
+    pub rejections: Vec<(I, RejectReason)>,
+    /// Statistics about the packing. 'cancelled' is set if the job was cancelled.
+    pub stats: PackingStats,
+}
+
+/// A handle to a job submitted to a [`PackerPool`].
+pub struct JobHandle<I:Clone> {
+    cancelled: Arc<AtomicBool>,
+    result: Receiver<JobResult<I>>,
+}
+
+impl<I:Clone> JobHandle<I> {
+    /// Ask the job to stop. If it has not started yet, it finishes immediately when it is
+    /// picked up. Its result is still delivered, with the items placed before cancellation.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// The result, if the job has finished. Returns None while it is still queued or running,
+    /// if the job panicked, and after the result has already been returned once.
+    pub fn poll(&self) -> Option<JobResult<I>> {
+        self.result.try_recv().ok()
+    }
+
+    /// Block until the job has finished, and return its result.
+    /// Returns None if the job panicked, for instance in a callback set using 'with_bin_setup'.
+    pub fn wait(self) -> Option<JobResult<I>> {
+        self.result.recv().ok()
+    }
+}
+
+struct Job<I:Clone> {
+    job: PackJob<I>,
+    cancelled: Arc<AtomicBool>,
+    result: Sender<JobResult<I>>,
+}
+
+/// Runs packing jobs on a fixed number of worker threads.
+///
+/// Jobs are started in the order they are submitted. Dropping the pool waits for all
+/// submitted jobs to finish.
+pub struct PackerPool<I:Clone+Send+'static> {
+    queue: Option<Sender<Job<I>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<I:Clone+Send+'static> PackerPool<I> {
+    /// Create a pool with 'threads' worker threads. Panics if 'threads' is 0.
+    pub fn new(threads: usize) -> PackerPool<I> {
+        if threads == 0 {
+            panic!("A pool needs at least one thread");
+        }
+        let (queue, jobs) = mpsc::channel::<Job<I>>();
+        let jobs = Arc::new(Mutex::new(jobs));
+        let workers = (0..threads).map(|_| {
+            let jobs = jobs.clone();
+            thread::spawn(move || loop {
+                let job = match jobs.lock() {
+                    Ok(jobs) => jobs.recv(),
+                    Err(_) => return,
+                };
+                let Ok(Job { job, cancelled, result }) = job else {
+                    return; //The pool has been dropped
+                };
+                // If the job panics, the result is dropped, and the worker carries on with the next job
+                if let Ok(outcome) = panic::catch_unwind(AssertUnwindSafe(||run(job, cancelled))) {
+                    // The handle may have been dropped, in which case nobody wants the result
+                    let _ = result.send(outcome);
+                }
+            })
+        }).collect();
+        PackerPool {
+            queue: Some(queue),
+            workers,
+        }
+    }
+
+    /// Queue a job, returning a handle to cancel it or get its result
+    pub fn submit(&self, job: PackJob<I>) -> JobHandle<I> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (result, receiver) = mpsc::channel();
+        let queue = self.queue.as_ref().expect("queue is only taken when dropping");
+        queue.send(Job { job, cancelled: cancelled.clone(), result }).expect("workers only stop when the pool is dropped");
+        JobHandle {
+            cancelled,
+            result: receiver,
+        }
+    }
+}
+
+impl<I:Clone+Send+'static> Drop for PackerPool<I> {
+    fn drop(&mut self) {
+        self.queue = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run<I:Clone>(job: PackJob<I>, cancelled: Arc<AtomicBool>) -> JobResult<I> {
+    let mut bin = Bin::with_backend(job.width, job.height, job.backend);
+    (job.setup)(&mut bin);
+    let options = (job.options)().with_cancel(move || cancelled.load(Ordering::Relaxed));
+    let all_fit = bin.place_all(job.items.into_iter(), options);
+    JobResult {
+        all_fit,
+        rejections: bin.rejections().to_vec(),
+        stats: bin.stats().clone(),
+        solution: bin.take_solution(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool() {
+        let pool = PackerPool::new(2);
+        let handles: Vec<_> = (1..4).map(|n| {
+            let items = (0..n).map(|id| Item::new(5, 5, id)).collect();
+            pool.submit(PackJob::new(10, 10, items).with_options(|| PlaceOptions::default().with_spacing(0)))
+        }).collect();
+        let placed: Vec<_> = handles.into_iter().map(|x|x.wait().unwrap().solution.len()).collect();
+        assert_eq!(placed, vec![1, 2, 3]);
+
+        // Keep one worker busy until the second job has been cancelled
+        let pool = PackerPool::new(1);
+        let (release, blocked) = mpsc::channel::<()>();
+        let first = pool.submit(PackJob::new(10, 10, vec![Item::new(5, 5, 0)]).with_bin_setup(move |_| { let _ = blocked.recv(); }));
+        let second = pool.submit(PackJob::new(10, 10, vec![Item::new(5, 5, 0)]));
+        second.cancel();
+        assert!(second.poll().is_none());
+        release.send(()).unwrap();
+        assert!(first.wait().unwrap().all_fit);
+        let result = second.wait().unwrap();
+        assert!(result.stats.cancelled);
+        assert!(!result.all_fit);
+
+        // A panicking job doesn't stop its worker
+        let failing = pool.submit(PackJob::new(10, 10, vec![Item::new(5, 5, 0)]).with_bin_setup(|_|panic!("setup failed")));
+        assert!(failing.wait().is_none());
+        let job = PackJob::new(1_000_000, 1_000_000, vec![Item::new(5, 5, 0)]).with_backend(Backend::FreeRects);
+        assert!(pool.submit(job).wait().unwrap().all_fit);
+    }
+}
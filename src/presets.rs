@@ -0,0 +1,121 @@
+use crate::{Bin, PlaceOptions, Reservation};
+
+/// A stock size together with the constraints of the machine cutting it, so that they don't
+/// have to be repeated as magic numbers. Sizes are in millimeters. Presets are plain data:
+/// the built in ones (see [`Preset::builtins`]) can be adjusted, and new ones created at runtime.
+///
+/// ```
+/// use bin_packing_2d::{Bin, Item, Preset};
+/// let plywood = Preset::builtin("plywood-2440x1220").unwrap().with_kerf(3);
+/// assert_eq!((plywood.width, plywood.height), (2440, 1220));
+/// let mut presets = Preset::builtins();
+/// presets.push(Preset::new("offcut", 60, 40).with_kerf(2).with_margin(5));
+/// let offcut = Preset::find(&presets, "offcut").unwrap();
+/// let mut bin = Bin::from_preset(offcut);
+/// assert!(bin.place_all([Item::new(24, 20, 'A'), Item::new(24, 20, 'B')].into_iter(), offcut.options()));
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct Preset {
+    /// The name of the preset, for looking it up using 'find'
+    pub name: String,
+    /// The width of the stock
+    pub width: usize,
+    /// The height of the stock
+    pub height: usize,
+    /// The width of the cut, kept free between items
+    pub kerf: usize,
+    /// The trim along each edge of the stock, which is not used. Since trimming is a cut as
+    /// well, the kerf is also kept free next to it.
+    pub margin: usize,
+    /// Whether items may be rotated by default. False for materials with a grain direction.
+    pub allow_rotate: bool,
+}
+
+impl Preset {
+    /// A preset for stock of the given size, without kerf or margin, allowing rotation
+    pub fn new(name: impl Into<String>, width: usize, height: usize) -> Preset {
+        Preset { name: name.into(), width, height, kerf: 0, margin: 0, allow_rotate: true }
+    }
+
+    /// Set the width of the cut
+    pub fn with_kerf(mut self, kerf: usize) -> Preset {
+        self.kerf = kerf;
+        self
+    }
+
+    /// Set the trim along each edge
+    pub fn with_margin(mut self, margin: usize) -> Preset {
+        self.margin = margin;
+        self
+    }
+
+    /// Set whether items may be rotated by default
+    pub fn with_rotation(mut self, allow_rotate: bool) -> Preset {
+        self.allow_rotate = allow_rotate;
+        self
+    }
+
+    /// Common stock sizes, with typical kerfs and margins
+    pub fn builtins() -> Vec<Preset> {
+        vec![
+            Preset::new("plywood-2440x1220", 2440, 1220).with_kerf(4).with_margin(5).with_rotation(false),
+            Preset::new("plywood-2500x1250", 2500, 1250).with_kerf(4).with_margin(5).with_rotation(false),
+            Preset::new("mdf-2800x2070", 2800, 2070).with_kerf(4).with_margin(10),
+            Preset::new("glass-3210x2250", 3210, 2250).with_margin(20),
+            Preset::new("steel-3000x1500", 3000, 1500).with_kerf(1).with_margin(10),
+            Preset::new("paper-a4", 210, 297),
+        ]
+    }
+
+    /// The built in preset with the given name
+    pub fn builtin(name: &str) -> Option<Preset> {
+        Preset::find(&Preset::builtins(), name).cloned()
+    }
+
+    /// The preset with the given name in 'presets', for instance the built in ones extended
+    /// with those of the application
+    pub fn find<'a>(presets: &'a [Preset], name: &str) -> Option<&'a Preset> {
+        presets.iter().find(|x|x.name == name)
+    }
+
+    /// Options for packing a bin created using `Bin::from_preset`, with the kerf as spacing
+    pub fn options(&self) -> PlaceOptions<'static> {
+        PlaceOptions::default().with_spacing(self.kerf)
+    }
+}
+
+/// See [`Bin::from_preset`]
+pub(crate) fn bin<I:Clone>(preset: &Preset) -> Bin<I> {
+    let (width, height, margin) = (preset.width, preset.height, preset.margin);
+    let mut bin = Bin::new(width, height);
+    bin.set_default_allow_rotate(preset.allow_rotate);
+    if margin > 0 {
+        bin.reserve(Reservation::Rows(0..margin.min(height)));
+        bin.reserve(Reservation::Rows(height.saturating_sub(margin)..height));
+        bin.reserve(Reservation::Cols(0..margin.min(width)));
+        bin.reserve(Reservation::Cols(width.saturating_sub(margin)..width));
+        bin.update_holes(false);
+    }
+    bin
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Item;
+
+    #[test]
+    fn test_presets() {
+        let mut presets = Preset::builtins();
+        presets.push(Preset::new("offcut", 100, 50).with_kerf(2).with_margin(5).with_rotation(false));
+        let preset = Preset::find(&presets, "offcut").unwrap();
+        assert_eq!(Preset::builtin("offcut"), None);
+        let mut bin = Bin::from_preset(preset);
+        assert_eq!(bin.get_largest_hole().width, 90);
+        // The kerf is kept free between the items, and next to the trimmed margins, so two items
+        // of 42 fit, but not of 43, and not rotated
+        assert!(bin.clone().place_all([Item::new(42, 36, 'A'), Item::new(42, 36, 'B')].into_iter(), preset.options()));
+        assert!(!bin.clone().place_all([Item::new(43, 36, 'A'), Item::new(42, 36, 'B')].into_iter(), preset.options()));
+        assert!(!bin.place_all([Item::new(40, 60, 'A')].into_iter(), preset.options()));
+    }
+}
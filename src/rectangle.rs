@@ -0,0 +1,60 @@
+use crate::Hole;
+
+/// A rectangular area of a bin. It covers
+/// the columns `x0..x1` and the rows `y0..y1`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Rectangle {
+    /// The leftmost column of the rectangle
+    pub x0: usize,
+    /// The top row of the rectangle
+    pub y0: usize,
+    /// One past the rightmost column of the rectangle
+    pub x1: usize,
+    /// One past the bottom row of the rectangle
+    pub y1: usize,
+}
+
+impl Rectangle {
+    /// Width of the rectangle
+    pub fn width(&self) -> usize {
+        self.x1 - self.x0
+    }
+    /// Height of the rectangle
+    pub fn height(&self) -> usize {
+        self.y1 - self.y0
+    }
+    /// Area of the rectangle, saturating at 'usize::MAX'
+    pub fn area(&self) -> usize {
+        self.width().saturating_mul(self.height())
+    }
+    /// The size of the rectangle, without position
+    pub fn hole(&self) -> Hole {
+        Hole {
+            width: self.width(),
+            height: self.height(),
+        }
+    }
+    /// True if the two rectangles have any cell in common
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        self.x0 < other.x1 && other.x0 < self.x1 && self.y0 < other.y1 && other.y0 < self.y1
+    }
+    /// True if every cell of 'other' is also in this rectangle
+    pub fn contains(&self, other: &Rectangle) -> bool {
+        self.x0 <= other.x0 && self.y0 <= other.y0 && other.x1 <= self.x1 && other.y1 <= self.y1
+    }
+    /// The smallest rectangle containing both rectangles
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        Rectangle {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+    /// The number of cells the two rectangles have in common, saturating at 'usize::MAX'
+    pub fn intersection_area(&self, other: &Rectangle) -> usize {
+        let w = self.x1.min(other.x1).saturating_sub(self.x0.max(other.x0));
+        let h = self.y1.min(other.y1).saturating_sub(self.y0.max(other.y0));
+        w.saturating_mul(h)
+    }
+}
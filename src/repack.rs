@@ -0,0 +1,174 @@
+use std::time::Instant;
+
+use crate::stepwise::sort_items;
+use crate::{Bin, Candidate, Item, PackingStats, PlaceOptions, PlacedItem, Rectangle, Strategy};
+
+/// See [`Bin::repack_region`]
+pub(crate) fn repack_region<I:Clone+PartialEq>(bin: &mut Bin<I>, region: Rectangle, input: Vec<Item<I>>, mut options: PlaceOptions) -> Vec<Item<I>> {
+    let start = Instant::now();
+    let region = Rectangle { x0: region.x0, y0: region.y0, x1: region.x1.min(bin.width()), y1: region.y1.min(bin.height()) };
+    // Match each item to a placed item in the region with its id, if any, which is freed
+    let mut freed = vec![false; bin.items.len()];
+    let mut replaces = vec![false; input.len()];
+    for (item, replaces) in input.iter().zip(&mut replaces) {
+        if let Some(index) = (0..bin.items.len()).find(|&i|!freed[i] && bin.items[i].id == item.id && bin.items[i].rect().intersects(&region)) {
+            freed[index] = true;
+            *replaces = true;
+        }
+    }
+    // Sort like 'place_all' would, keeping track of the index of each item
+    let mut order: Vec<Item<usize>> = input.iter().enumerate().map(|(index, x)|x.with_id(index)).collect();
+    let compare = bin.item_order.clone().map(|compare|{ let input = &input; move |a: &Item<usize>, b: &Item<usize>|compare(&input[a.id], &input[b.id]) });
+    sort_items(&mut order, &mut options, compare.as_ref().map(|x|x as _));
+    // The bin is only changed if all freed items fit again. Otherwise, only the new items are placed.
+    let autosave = bin.autosave.take();
+    let mut attempt = bin.clone();
+    let mut left = repack(&mut attempt, &region, &input, &order, &freed, &mut options);
+    if left.iter().all(|&index|!replaces[index]) {
+        *bin = attempt;
+    } else {
+        order.retain(|x|!replaces[x.id]);
+        left = repack(bin, &region, &input, &order, &vec![false; freed.len()], &mut options);
+    }
+    bin.autosave = autosave;
+    bin.update_holes(false);
+    bin.stats.passes = 1;
+    bin.stats.cancelled = options.was_cancelled();
+    bin.stats.zone_utilization = bin.zone_utilization();
+    bin.stats.weighted_utilization = bin.weighted_utilization();
+    bin.stats.kerf_area = bin.kerf_area(options.spacing());
+    bin.stats.shared_cut_length = bin.shared_cut_length(options.spacing());
+    bin.stats.duration = start.elapsed();
+    bin.run_autosave();
+    let mut input: Vec<Option<Item<I>>> = input.into_iter().map(Some).collect();
+    left.into_iter().filter_map(|index|input[index].take()).collect()
+}
+
+/// Remove the placed items marked in 'freed', and place the items of 'input' in 'order'
+/// within 'region'. Returns the indices of the items which did not fit.
+fn repack<I:Clone>(bin: &mut Bin<I>, region: &Rectangle, input: &[Item<I>], order: &[Item<usize>], freed: &[bool], options: &mut PlaceOptions) -> Vec<usize> {
+    let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut bin.items).into_iter().zip(freed).partition(|(_, &freed)|freed);
+    bin.items = kept.into_iter().map(|(x, _)|x).collect();
+    free(bin, &removed.into_iter().map(|(x, _)|x).collect::<Vec<_>>());
+    bin.stats = PackingStats::default();
+    bin.rejections.clear();
+    let mut left = vec![];
+    for index in order.iter().map(|x|x.id) {
+        let item = &input[index];
+        if !options.is_cancelled() && place_in(bin, item, region, options) {
+            bin.stats.items_placed += 1;
+        } else {
+            bin.reject(item);
+            left.push(index);
+        }
+    }
+    left
+}
+
+/// See [`Bin::remove_item`]
+pub(crate) fn remove_item<I:Clone+PartialEq>(bin: &mut Bin<I>, id: &I) -> Option<PlacedItem<I>> {
+    let index = bin.items.iter().position(|x|x.id == *id)?;
+    let removed = bin.items.remove(index);
+    free(bin, std::slice::from_ref(&removed));
+    bin.update_holes(false);
+    bin.run_autosave();
+    Some(removed)
+}
+
+/// Clear the cells of 'freed', which have been removed from the items of 'bin'. The bitmap is
+/// patched instead of rebuilt: the parts of the freed items which are also covered by the
+/// watermark, reserved areas or overlapping items are filled again.
+fn free<I:Clone>(bin: &mut Bin<I>, freed: &[PlacedItem<I>]) {
+    bin.items_changed();
+    for placed in freed {
+        bin.bitmap.fill(placed.x0, placed.y0, placed.width(), placed.height(), false);
+    }
+    let watermark = Rectangle { x0: 0, y0: 0, x1: bin.width(), y1: bin.watermark };
+    let occupied: Vec<Rectangle> = bin.reserved_areas().into_iter().chain(bin.items.iter().map(|x|x.rect())).chain([watermark]).collect();
+    for placed in freed {
+        for rect in &occupied {
+            let (x0, y0) = (rect.x0.max(placed.x0), rect.y0.max(placed.y0));
+            let (x1, y1) = (rect.x1.min(placed.x1), rect.y1.min(placed.y1));
+            if x0 < x1 && y0 < y1 {
+                bin.bitmap.fill(x0, y0, x1 - x0, y1 - y0, true);
+            }
+        }
+    }
+}
+
+/// Place 'item' at the best position within 'region', evaluating only the positions there,
+/// in scan order. Returns false if it does not fit.
+fn place_in<I:Clone>(bin: &mut Bin<I>, item: &Item<I>, region: &Rectangle, options: &PlaceOptions) -> bool {
+    if bin.is_too_large(item) {
+        return false;
+    }
+    let (width, height) = (bin.width(), bin.height());
+    let scan = options.scan_order();
+    let mut positions = vec![];
+    for (w, h, rotated) in bin.orientations(item, Strategy::RotateIfSuitable, options) {
+        for y0 in region.y0..(region.y1 + 1).saturating_sub(h) {
+            for x0 in region.x0..(region.x1 + 1).saturating_sub(w) {
+                positions.push((scan.position_key(x0, y0, w, h, width, height), x0, y0, w, h, rotated));
+            }
+        }
+    }
+    positions.sort_unstable();
+    let partners = bin.placed_partners(&item.id);
+    let (tie_break, bounding_box) = (options.tie_break(), bin.bounding_box());
+    let mut best_fit: Option<(Candidate, usize)> = None;
+    for (scan_index, &(_, x0, y0, w, h, rotated)) in positions.iter().enumerate() {
+        let Some(candidate) = bin.candidate_at(item, &partners, (x0, y0, w, h, rotated), scan_index, options) else {
+            continue;
+        };
+        let key = Bin::tie_key(tie_break, bounding_box, item, &candidate);
+        if best_fit.is_none_or(|(best, best_key)|(candidate.rank(), key) < (best.rank(), best_key)) {
+            best_fit = Some((candidate, key));
+        }
+    }
+    bin.record_evaluations(positions.len() as u64);
+    let Some((best, _)) = best_fit else {
+        return false;
+    };
+    bin.place(best.x0, best.y0, item, best.rotated, options.overlap_tolerance().allowed_cells(item.w.saturating_mul(item.h)));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repack_region() {
+        let mut bin = Bin::new(10, 10);
+        bin.set_default_allow_rotate(false);
+        let banded = Item::new(3, 3, 'D').with_y_band(4..5);
+        assert!(bin.pack([Item::new(10, 4, 'A'), Item::new(4, 6, 'B'), Item::new(3, 3, 'C'), banded.clone()]));
+        let kept = |bin: &Bin<char>|bin.solution().iter().filter(|x|x.id == 'A' || x.id == 'B').map(|x|x.rect()).collect::<Vec<_>>();
+        let before = kept(&bin);
+        let d = bin.placement_of(&'D').map(|x|x.rect());
+        // The operator deletes 'C', and a wider part is added near where it was
+        assert_eq!(bin.remove_item(&'C').map(|x|x.id), Some('C'));
+        assert!(bin.remove_item(&'C').is_none());
+        let region = Rectangle { x0: 4, y0: 4, x1: 10, y1: 10 };
+        // 'D' keeps its band, so it can't be repacked after 'E', and stays where it was
+        let left = bin.repack_region(region, [banded, Item::new(6, 2, 'E')], PlaceOptions::default());
+        assert!(left.is_empty());
+        assert_eq!(bin.placement_of(&'D').map(|x|x.rect()), d);
+        assert!(region.contains(&bin.placement_of(&'E').unwrap().rect()));
+        assert_eq!((bin.solution().len(), bin.stats().items_placed), (4, 1));
+        // Without the band, both are packed within the region, and 'A' and 'B' stay
+        let left = bin.repack_region(region, [Item::new(3, 3, 'D'), Item::new(6, 2, 'E')], PlaceOptions::default());
+        assert!(left.is_empty());
+        assert_eq!(kept(&bin), before);
+        assert!(bin.solution().iter().filter(|x|x.id == 'D' || x.id == 'E').all(|x|region.contains(&x.rect())));
+        assert_eq!((bin.solution().len(), bin.stats().items_placed), (4, 2));
+        // The patched bitmap matches one built from scratch
+        let rebuilt = Bin::from_solution(10, 10, bin.solution().to_vec()).unwrap();
+        assert_eq!(bin.bitmap.count_occupied(0, 0, 10, 10), rebuilt.bitmap.count_occupied(0, 0, 10, 10));
+        assert_eq!(bin.get_largest_hole().width * bin.get_largest_hole().height, rebuilt.get_largest_hole().width * rebuilt.get_largest_hole().height);
+        // Items which no longer fit are returned
+        let left = bin.repack_region(region, [Item::new(6, 6, 'F')], PlaceOptions::default());
+        assert_eq!(left.iter().map(|x|x.id).collect::<Vec<_>>(), vec!['F']);
+        assert_eq!((bin.solution().len(), bin.rejections().len()), (4, 1));
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io::{self, Write};
+
+use crate::{Bin, ColorMap, Tags};
+
+/// Write a standalone HTML page showing the layout of 'bin' as an SVG image, followed by
+/// a table of packing statistics and a list of the items which could not be placed.
+/// Hovering over an item shows its id, size and whether it was rotated.
+/// Items are drawn in one group ('layer') per set of tags, with the bits of the tags in
+/// the 'data-tags' attribute. Untagged items are drawn first, so tagged items are on top.
+///
+/// Items are colored using 'colors', so that the same colors can be used in other
+/// renderings of the layout. Items without a color are assigned one.
+///
+/// The page has no external dependencies, so it can be attached to an email as is.
+pub fn write_html_report<I:Clone+Display+Eq+Hash>(mut writer: impl Write, bin: &Bin<I>, colors: &mut ColorMap<I>) -> io::Result<()> {
+    let (width, height) = (bin.width(), bin.height());
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html><head><meta charset=\"utf-8\"><title>Packing report</title><style>")?;
+    writeln!(writer, "body {{ font-family: sans-serif; }} svg {{ max-width: 100%; max-height: 80vh; border: 1px solid #444; }}")?;
+    writeln!(writer, "rect.item:hover {{ stroke: #000; stroke-width: 2; }} td, th {{ padding: 2px 12px; text-align: left; }}")?;
+    writeln!(writer, "</style></head><body>")?;
+    writeln!(writer, "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" preserveAspectRatio=\"xMinYMin meet\">", width, height)?;
+    writeln!(writer, "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"#f4f4f4\"/>", width, height)?;
+    if bin.watermark() > 0 {
+        writeln!(writer, "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"#bbb\"><title>Above watermark</title></rect>", width, bin.watermark())?;
+    }
+    for rect in bin.reserved_areas() {
+        writeln!(writer, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#bbb\"><title>Reserved</title></rect>", rect.x0, rect.y0, rect.width(), rect.height())?;
+    }
+    let mut layers: Vec<Tags> = bin.solution().iter().map(|x|x.tags).collect();
+    layers.sort();
+    layers.dedup();
+    for tags in layers {
+        writeln!(writer, "<g class=\"layer\" data-tags=\"{}\">", tags.0)?;
+        for item in bin.solution().iter().filter(|x|x.tags == tags) {
+            let (w, h) = (item.width(), item.height());
+            writeln!(writer, "<rect class=\"item\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" vector-effect=\"non-scaling-stroke\"><title>{}&#10;{} x {}{}</title></rect>",
+                item.x0, item.y0, w, h, colors.color(&item.id), escape(&item.id.to_string()), w, h, if item.rotated() { ", rotated" } else { "" })?;
+        }
+        writeln!(writer, "</g>")?;
+    }
+    writeln!(writer, "</svg>")?;
+    let stats = bin.stats();
+    writeln!(writer, "<h2>Statistics</h2><table>")?;
+    let rows = [
+        ("Bin size", format!("{} x {}", width, height)),
+        ("Items placed", bin.solution().len().to_string()),
+        ("Items rejected", bin.rejections().len().to_string()),
+        ("Utilization", format!("{:.1}%", bin.utilization() * 100.0)),
+        ("Passes", stats.passes.to_string()),
+        ("Duration", format!("{:?}", stats.duration)),
+    ];
+    for (name, value) in rows {
+        writeln!(writer, "<tr><th>{}</th><td>{}</td></tr>", name, value)?;
+    }
+    writeln!(writer, "</table>")?;
+    if !bin.rejections().is_empty() {
+        writeln!(writer, "<h2>Rejected items</h2><ul>")?;
+        for (id, reason) in bin.rejections() {
+            writeln!(writer, "<li>{}: {:?}</li>", escape(&id.to_string()), reason)?;
+        }
+        writeln!(writer, "</ul>")?;
+    }
+    writeln!(writer, "</body></html>")?;
+    writer.flush()
+}
+
+/// Escape text for use in HTML content and attribute values
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Item;
+
+    #[test]
+    fn test_html_report() {
+        let mut bin = Bin::new(10, 10);
+        assert!(!bin.pack([Item::new(4, 4, "<a>").with_tags(Tags::tag(2)), Item::new(20, 1, "long")]));
+        let mut buffer = vec![];
+        write_html_report(&mut buffer, &bin, &mut ColorMap::new()).unwrap();
+        let html = String::from_utf8(buffer).unwrap();
+        assert!(html.contains("<title>&lt;a&gt;&#10;4 x 4</title>"));
+        assert!(html.contains("<li>long: TooLarge</li>"));
+        assert_eq!(html.matches("class=\"item\"").count(), 1);
+        assert!(html.contains("<g class=\"layer\" data-tags=\"4\">"));
+    }
+}
@@ -0,0 +1,169 @@
+/// A corner of a bin.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Corner {
+    /// The corner at x = 0, y = 0
+    TopLeft,
+    /// The corner at x = width - 1, y = 0
+    TopRight,
+    /// The corner at x = 0, y = height - 1
+    BottomLeft,
+    /// The corner at x = width - 1, y = height - 1
+    BottomRight,
+}
+
+impl Corner {
+    fn is_right(self) -> bool {
+        matches!(self, Corner::TopRight | Corner::BottomRight)
+    }
+    fn is_bottom(self) -> bool {
+        matches!(self, Corner::BottomLeft | Corner::BottomRight)
+    }
+}
+
+/// The order in which candidate positions are searched.
+///
+/// Positions found early in the scan win ties when scoring, and packing
+/// therefore tends to start in the corner (or center) where the scan begins.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum ScanOrder {
+    /// Scan row by row, starting in the given corner.
+    /// Items are aligned to the corner, i.e. with `Corner::BottomRight`,
+    /// the bottom right corner of the item is placed at the scanned position.
+    Rows(Corner),
+    /// Scan column by column, starting in the given corner.
+    Columns(Corner),
+    /// Scan positions in square rings of increasing size, around the center of the bin.
+    /// Items are centered on the scanned position.
+    SpiralFromCenter,
+}
+
+impl Default for ScanOrder {
+    fn default() -> Self {
+        ScanOrder::Rows(Corner::TopLeft)
+    }
+}
+
+impl ScanOrder {
+    /// The number of lines (rows, columns or rings) to scan, for an item which is at least
+    /// 'min_size' (width and height) in every orientation tried.
+    pub(crate) fn line_count(&self, width: usize, height: usize, min_size: (usize, usize)) -> usize {
+        match self {
+            ScanOrder::Rows(_) => height.saturating_sub(min_size.1 - 1),
+            ScanOrder::Columns(_) => width.saturating_sub(min_size.0 - 1),
+            ScanOrder::SpiralFromCenter => width.max(height) / 2 + 1,
+        }
+    }
+
+    /// Put the positions of line number 'line' into 'out'. Positions where an item of
+    /// 'min_size' would extend past the edge of the bin are left out, apart from in spirals.
+    pub(crate) fn line_cells(&self, line: usize, width: usize, height: usize, min_size: (usize, usize), out: &mut Vec<(usize, usize)>) {
+        out.clear();
+        let along = |index: usize, extent: usize, reverse: bool| {
+            if reverse { extent - 1 - index } else { index }
+        };
+        match *self {
+            ScanOrder::Rows(corner) => {
+                let y = along(line, height, corner.is_bottom());
+                for i in 0..width.saturating_sub(min_size.0 - 1) {
+                    out.push((along(i, width, corner.is_right()), y));
+                }
+            }
+            ScanOrder::Columns(corner) => {
+                let x = along(line, width, corner.is_right());
+                for i in 0..height.saturating_sub(min_size.1 - 1) {
+                    out.push((x, along(i, height, corner.is_bottom())));
+                }
+            }
+            ScanOrder::SpiralFromCenter => {
+                let cx = (width / 2) as isize;
+                let cy = (height / 2) as isize;
+                let r = line as isize;
+                let mut push = |x: isize, y: isize| {
+                    if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+                        out.push((x as usize, y as usize));
+                    }
+                };
+                if r == 0 {
+                    push(cx, cy);
+                    return;
+                }
+                for dx in -r..=r {
+                    push(cx + dx, cy - r);
+                }
+                for dy in -r + 1..=r {
+                    push(cx + r, cy + dy);
+                }
+                for dx in (-r..r).rev() {
+                    push(cx + dx, cy + r);
+                }
+                for dy in (-r + 1..r).rev() {
+                    push(cx - r, cy + dy);
+                }
+            }
+        }
+    }
+
+    /// A key which orders item positions like this scan order would find them, when
+    /// positions are generated other than by scanning cell by cell.
+    pub(crate) fn position_key(&self, x0: usize, y0: usize, w: usize, h: usize, width: usize, height: usize) -> (usize, usize) {
+        let from_corner = |corner: Corner| {
+            let x = if corner.is_right() { width - (x0 + w) } else { x0 };
+            let y = if corner.is_bottom() { height - (y0 + h) } else { y0 };
+            (x, y)
+        };
+        match *self {
+            ScanOrder::Rows(corner) => {
+                let (x, y) = from_corner(corner);
+                (y, x)
+            }
+            ScanOrder::Columns(corner) => from_corner(corner),
+            ScanOrder::SpiralFromCenter => {
+                let (dx, dy) = ((x0 + w / 2).abs_diff(width / 2), (y0 + h / 2).abs_diff(height / 2));
+                (dx.max(dy), dy)
+            }
+        }
+    }
+
+    /// The top left coordinate of an item of size 'w' x 'h', when placed at the scanned
+    /// position 'x', 'y'. Returns None if the item would extend past the top or left edge of the bin.
+    pub(crate) fn anchor(&self, x: usize, y: usize, w: usize, h: usize) -> Option<(usize, usize)> {
+        match *self {
+            ScanOrder::Rows(corner) | ScanOrder::Columns(corner) => {
+                let x0 = if corner.is_right() { (x + 1).checked_sub(w)? } else { x };
+                let y0 = if corner.is_bottom() { (y + 1).checked_sub(h)? } else { y };
+                Some((x0, y0))
+            }
+            ScanOrder::SpiralFromCenter => Some((x.checked_sub(w / 2)?, y.checked_sub(h / 2)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spiral_covers_bin() {
+        let mut cells = vec![];
+        let mut line = vec![];
+        let order = ScanOrder::SpiralFromCenter;
+        for i in 0..order.line_count(5, 3, (1, 1)) {
+            order.line_cells(i, 5, 3, (1, 1), &mut line);
+            cells.extend_from_slice(&line);
+        }
+        assert_eq!(cells[0], (2, 1));
+        cells.sort();
+        cells.dedup();
+        assert_eq!(cells.len(), 15);
+    }
+
+    #[test]
+    fn test_full_width_line() {
+        let mut line = vec![];
+        let order = ScanOrder::Rows(Corner::BottomRight);
+        assert_eq!(order.line_count(10, 6, (10, 2)), 5);
+        order.line_cells(0, 10, 6, (10, 2), &mut line);
+        assert_eq!(line, vec![(9, 5)]);
+        assert_eq!(order.anchor(9, 5, 10, 2), Some((0, 4)));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{Bin, Item, PlaceOptions, PlacedItem, SolutionError};
+
+/// See [`Bin::place_all_seeded`]
+pub(crate) fn place_all_seeded<'o, I:Clone+PartialEq>(bin: &mut Bin<I>, input: Vec<Item<I>>, seed: Vec<PlacedItem<I>>, options: &dyn Fn() -> PlaceOptions<'o>) -> Result<bool, SolutionError> {
+    // Match each seed item to a different input item, with the same id and size, which may be
+    // placed where it is
+    let mut remaining: Vec<Option<Item<I>>> = input.iter().cloned().map(Some).collect();
+    for (index, placed) in seed.iter().enumerate() {
+        let matching = remaining.iter().position(|x|x.as_ref().is_some_and(|item|
+            item.id == placed.id && (item.w, item.h) == (placed.original_w(), placed.original_h()) &&
+            (!placed.rotated() || bin.allows_rotation(item)) && item.bands_allow(placed.x0, placed.y0)));
+        let Some(matching) = matching else {
+            return Err(SolutionError::Unknown(index));
+        };
+        remaining[matching] = None;
+    }
+    let mut seeded = bin.clone();
+    seeded.autosave = None;
+    let initial_count = seeded.items.len();
+    seeded.load_solution(seed)?;
+    if let Some(index) = seeded.items[initial_count..].iter().position(|x|!seeded.regions_allow(&x.id, &x.rect())) {
+        return Err(SolutionError::Region(index));
+    }
+    // Only the items not in the seed are packed, which is usually much faster
+    let mut all_fit = seeded.place_all(remaining.into_iter().flatten(), options());
+    if !all_fit {
+        // The seed may have been a poor start, so also try packing from scratch
+        let mut fresh = bin.clone();
+        fresh.autosave = None;
+        let fresh_fit = fresh.place_all(input.into_iter(), options());
+        if seeded.keep_best(fresh) {
+            all_fit = fresh_fit;
+        }
+    }
+    seeded.autosave = bin.autosave.take();
+    *bin = seeded;
+    bin.run_autosave();
+    Ok(all_fit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rotation, Tags};
+
+    #[test]
+    fn test_place_all_seeded() {
+        let items = || vec![Item::new(6, 4, 'A'), Item::new(4, 4, 'B'), Item::new(10, 6, 'C')];
+        let placed = |x0, y0, w, h, id| PlacedItem { x0, y0, x1: x0 + w, y1: y0 + h, rotation: Rotation::None, overlap: 0, margins: [0; 4], tags: Tags::NONE, id };
+        // A known layout for two of the items, the third is packed into the space left
+        let seed = vec![placed(0, 6, 6, 4, 'A'), placed(6, 6, 4, 4, 'B')];
+        let mut bin = Bin::new(10, 10);
+        assert_eq!(bin.place_all_seeded(items(), seed.clone(), PlaceOptions::default), Ok(true));
+        assert_eq!(bin.placement_of(&'A').map(|x|(x.x0, x.y0)), Some((0, 6)));
+        assert_eq!(bin.placement_of(&'C').map(|x|(x.x0, x.y0)), Some((0, 0)));
+
+        // A seed which leaves no room for C is replaced by packing from scratch
+        let poor = vec![placed(2, 3, 6, 4, 'A')];
+        let mut bin = Bin::new(10, 10);
+        assert_eq!(bin.place_all_seeded(items(), poor, PlaceOptions::default), Ok(true));
+        assert_eq!(bin.solution().len(), 3);
+
+        let mut bin = Bin::new(10, 10);
+        assert_eq!(bin.place_all_seeded(items(), vec![placed(0, 0, 4, 6, 'A')], PlaceOptions::default), Err(SolutionError::Unknown(0)));
+        assert_eq!(bin.place_all_seeded(items(), vec![seed[0].clone(), seed[0].clone()], PlaceOptions::default), Err(SolutionError::Unknown(1)));
+        assert_eq!(bin.place_all_seeded(items(), vec![seed[0].clone(), placed(4, 6, 4, 4, 'B')], PlaceOptions::default), Err(SolutionError::Overlap(1)));
+        assert!(bin.solution().is_empty());
+    }
+}
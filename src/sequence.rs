@@ -0,0 +1,41 @@
+use crate::Rectangle;
+
+/// An edge of a bin.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Edge {
+    /// The edge at x = 0
+    Left,
+    /// The edge at x = width
+    Right,
+    /// The edge at y = 0
+    Top,
+    /// The edge at y = height
+    Bottom,
+}
+
+/// True if 'blocker' lies between 'item' and 'edge', so that 'item' can't be
+/// taken out of the bin towards 'edge' without first removing 'blocker'.
+pub(crate) fn blocks(edge: Edge, blocker: &Rectangle, item: &Rectangle) -> bool {
+    let corridor = match edge {
+        Edge::Left => Rectangle { x0: 0, x1: item.x0, ..*item },
+        Edge::Right => Rectangle { x0: item.x1, x1: usize::MAX, ..*item },
+        Edge::Top => Rectangle { y0: 0, y1: item.y0, ..*item },
+        Edge::Bottom => Rectangle { y0: item.y1, y1: usize::MAX, ..*item },
+    };
+    corridor.intersects(blocker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks() {
+        let item = Rectangle { x0: 2, y0: 2, x1: 4, y1: 4 };
+        let below = Rectangle { x0: 3, y0: 6, x1: 8, y1: 7 };
+        assert!(blocks(Edge::Bottom, &below, &item));
+        assert!(!blocks(Edge::Top, &below, &item));
+        assert!(!blocks(Edge::Right, &below, &item));
+        assert!(!blocks(Edge::Bottom, &item, &below));
+    }
+}
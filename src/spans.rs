@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+/// The free intervals of the rows of a bitmap, kept up to date as cells are filled, so that
+/// repeated queries don't need to scan the row. A row is only added when it is first
+/// queried, so that bins which are never queried, or are very tall, don't pay for it.
+#[derive(Clone,Default)]
+pub(crate) struct FreeSpans {
+    // Sorted, disjoint and non-adjacent intervals `x0..x1` of free columns, by row
+    rows: BTreeMap<usize, Vec<(usize, usize)>>,
+}
+
+impl FreeSpans {
+    /// The free intervals of row 'y', computed using 'build' if the row is not known yet
+    pub(crate) fn row(&mut self, y: usize, build: impl FnOnce() -> Vec<(usize, usize)>) -> &[(usize, usize)] {
+        self.rows.entry(y).or_insert_with(build)
+    }
+
+    /// Update the known rows after the given cells have been set to 'value'
+    pub(crate) fn fill(&mut self, x0: usize, y0: usize, w: usize, h: usize, value: bool) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        for (_, spans) in self.rows.range_mut(y0..y0 + h) {
+            if value {
+                occupy(spans, x0, x0 + w);
+            } else {
+                release(spans, x0, x0 + w);
+            }
+        }
+    }
+}
+
+/// Remove the columns `x0..x1` from 'spans'
+fn occupy(spans: &mut Vec<(usize, usize)>, x0: usize, x1: usize) {
+    let mut result = Vec::with_capacity(spans.len() + 1);
+    for &(a, b) in spans.iter() {
+        if b <= x0 || a >= x1 {
+            result.push((a, b));
+            continue;
+        }
+        if a < x0 {
+            result.push((a, x0));
+        }
+        if b > x1 {
+            result.push((x1, b));
+        }
+    }
+    *spans = result;
+}
+
+/// Add the columns `x0..x1` to 'spans', merging the intervals it touches
+fn release(spans: &mut Vec<(usize, usize)>, x0: usize, x1: usize) {
+    let (mut start, mut end) = (x0, x1);
+    let mut result = Vec::with_capacity(spans.len() + 1);
+    let mut inserted = false;
+    for &(a, b) in spans.iter() {
+        if b < start {
+            result.push((a, b));
+        } else if a > end {
+            if !inserted {
+                result.push((start, end));
+                inserted = true;
+            }
+            result.push((a, b));
+        } else {
+            (start, end) = (start.min(a), end.max(b));
+        }
+    }
+    if !inserted {
+        result.push((start, end));
+    }
+    *spans = result;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, Bin, Item, PlaceOptions, Run};
+
+    #[test]
+    fn test_widest_free_span() {
+        for backend in [Backend::Bitmap, Backend::Tiled, Backend::FreeRects] {
+            let mut bin = Bin::with_backend(20, 5, backend);
+            assert_eq!(bin.widest_free_span(0), Some(Run { x0: 0, x1: 20, occupied: false }));
+            assert_eq!(bin.widest_free_span(5), None);
+            bin.place(6, 0, &Item::new(4, 2, 'A'), false, 0);
+            // The row is updated as items are placed
+            assert_eq!(bin.widest_free_span(0), Some(Run { x0: 10, x1: 20, occupied: false }));
+            bin.place(10, 0, &Item::new(10, 5, 'B'), false, 0);
+            assert_eq!(bin.widest_free_span(0), Some(Run { x0: 0, x1: 6, occupied: false }));
+            assert_eq!(bin.widest_free_span(3), Some(Run { x0: 0, x1: 10, occupied: false }));
+            bin.set_watermark(1);
+            assert_eq!(bin.widest_free_span(0), None);
+            // Rows are rebuilt when the contents of the bin are replaced
+            assert!(bin.place_all([Item::new(6, 4, 'C')].into_iter(), PlaceOptions::default()));
+            assert_eq!(bin.widest_free_span(1), None);
+            for y in 1..5 {
+                let widest = bin.occupancy_rle()[y].iter().filter(|x|!x.occupied).max_by_key(|x|x.x1 - x.x0).copied();
+                assert_eq!(bin.widest_free_span(y).map(|x|x.x1 - x.x0), widest.map(|x|x.x1 - x.x0));
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+/// Statistics about the most recent call to [`crate::Bin::place_all`].
+#[derive(Debug,Clone,Default,PartialEq)]
+#[non_exhaustive]
+pub struct PackingStats {
+    /// The number of passes that were started
+    pub passes: usize,
+    /// The number of items placed by the last pass
+    pub items_placed: usize,
+    /// The number of items which the last pass failed to place
+    pub items_rejected: usize,
+    /// The total number of candidate positions evaluated, over all passes
+    pub evaluations: u64,
+    /// The largest number of candidate positions evaluated for a single item
+    pub peak_evaluations: u64,
+    /// The number of items in the last pass which would have fit, but only by leaving
+    /// an offcut smaller than the minimum set using `PlaceOptions::with_min_offcut`.
+    pub offcut_rejections: usize,
+    /// The fraction of each zone (see `Bin::add_zone`) which is occupied after packing
+    pub zone_utilization: Vec<f64>,
+    /// The weighted utilization after packing (see `Bin::set_weight_map`), or None if no
+    /// weight map is set
+    pub weighted_utilization: Option<f64>,
+    /// The area of the kerf (the spacing set using `PlaceOptions::with_spacing`) attributed
+    /// to the items in the bin after packing, see `Bin::material_usage`
+    pub kerf_area: f64,
+    /// The total length of the cuts shared by adjacent items after packing, see
+    /// `Bin::shared_cut_length`
+    pub shared_cut_length: usize,
+    /// True if packing was aborted, because of the cancel callback or the deadline
+    /// set in `PlaceOptions`
+    pub cancelled: bool,
+    /// True if passes or the final search were skipped because the soft deadline set using
+    /// `PlaceOptions::with_soft_deadline` had passed
+    pub degraded: bool,
+    /// The wall-clock time spent
+    pub duration: Duration,
+}
+
+/// An estimate of the resources needed to pack an instance.
+/// See [`crate::Bin::estimate_cost`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct CostEstimate {
+    /// Approximate peak memory use in bytes, not counting heap memory owned by item ids.
+    pub memory_bytes: usize,
+    /// Upper bound on the number of candidate positions evaluated,
+    /// using the default options. Each evaluation takes time proportional to the
+    /// area of the item, so the actual time is roughly this number multiplied by the
+    /// average item area, in the worst case.
+    pub evaluations: u64,
+}
+
+/// How the candidate positions evaluated for an item turned out, see
+/// [`crate::Bin::candidate_counts`]. Positions which were not rejected fit the item.
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq,Hash)]
+pub struct CandidateCounts {
+    /// The number of positions evaluated
+    pub evaluated: u64,
+    /// Positions where the item would extend outside the bin
+    pub out_of_bounds: u64,
+    /// Positions where the item, or the spacing or margins around it, would cover occupied cells
+    pub occupied: u64,
+    /// Positions ruled out by a constraint: the watermark, bands, extent limit, margins of
+    /// placed items, regions or unloading order
+    pub constrained: u64,
+}
+
+/// The result of [`crate::Bin::estimate_feasibility`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Feasibility {
+    /// None of the quick checks ruled out placing all items. Packing may still fail.
+    Possible,
+    /// The items can certainly not all be placed, for the given reason
+    Impossible(Infeasibility),
+}
+
+/// Why a set of items can not all be placed in a bin. See [`Feasibility`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Infeasibility {
+    /// The item with this index in the input does not fit in the bin, even if it was empty
+    TooLarge(usize),
+    /// The total area of the items exceeds the free area of the bin
+    Area {
+        /// The total area of the items
+        required: usize,
+        /// The number of free cells in the bin
+        available: usize,
+    },
+    /// No two items wider than half the bin can be placed side by side, so they must be
+    /// stacked, but their total height exceeds the height of the bin
+    WideItems {
+        /// The total height of the items
+        required: usize,
+        /// The height of the bin, below the watermark
+        available: usize,
+    },
+    /// No two items taller than half the bin can be placed above each other, so they must be
+    /// placed side by side, but their total width exceeds the width of the bin
+    TallItems {
+        /// The total width of the items
+        required: usize,
+        /// The width of the bin
+        available: usize,
+    },
+}
+
+/// The result of [`crate::Bin::place_all_with_outcome`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum PackOutcome {
+    /// All items were placed
+    AllFit,
+    /// Some items could not be placed
+    Partial {
+        /// The approximate size `(width, height)` of a bin with the same aspect ratio in
+        /// which all items would fit. None if packing was cancelled, or if even a bin 16
+        /// times as large in each dimension was not enough or its size would overflow.
+        suggested_bin: Option<(usize, usize)>,
+    },
+}
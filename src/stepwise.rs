@@ -0,0 +1,473 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::time::Instant;
+
+use crate::bitmap::Bitmap2d;
+use crate::{Bin, CandidateCounts, Compare, Item, ItemError, PackingStats, PlaceOptions, PlacedItem, SliverPolicy, SpacingPolicy, Strategy, Tags};
+
+/// One step of a [`PackIterator`].
+#[derive(Debug,Clone)]
+pub enum Step<I:Clone> {
+    /// The item was placed
+    Placed(PlacedItem<I>),
+    /// The item with this id could not be placed in the current pass, for the given reason
+    Rejected(I, RejectReason),
+    /// Not all items could be placed in the previous pass, so the bin has been reset to its
+    /// state before packing started, and a new pass has started with the given strategy.
+    Restarted(Strategy),
+}
+
+/// Why an item could not be placed.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum RejectReason {
+    /// The item is larger than the free part of the bin, in every allowed orientation, so
+    /// it could not be placed even in an empty bin. A larger bin is needed.
+    TooLarge,
+    /// There was not enough free space left, or the free space was too fragmented
+    NoSpace,
+    /// The item failed validation, see `PlaceOptions::with_validation`
+    Invalid(ItemError),
+}
+
+/// Items which are placed the same way: the size, whether the item may be rotated, the margins,
+/// the bands, and the tags if they decide which items may share edges
+type ItemClass = (usize, usize, bool, [usize; 4], Option<Range<usize>>, Option<Range<usize>>, Tags);
+
+/// Packs items one at a time. Created by [`Bin::pack_stepwise`].
+///
+/// Each call to `next` places (or fails to place) a single item, which makes it
+/// possible to interleave packing with other work, such as rendering the partial solution,
+/// without using threads.
+pub struct PackIterator<'b, 'o, I:Clone> {
+    bin: &'b mut Bin<I>,
+    options: PlaceOptions<'o>,
+    items: Vec<Item<I>>,
+    strategies: Vec<Strategy>,
+    any_rotatable: bool,
+    initial_items: Vec<PlacedItem<I>>,
+    initial_bitmap: Bitmap2d,
+    pass: usize,
+    next_item: usize,
+    all_fit: bool,
+    // Whether an item which is not optional was rejected in the current pass
+    mandatory_rejected: bool,
+    // Classes of items which have failed to fit. Since the occupied area only
+    // grows during a pass, such items will never fit later in the pass either.
+    failed_classes: HashSet<ItemClass>,
+    // Whether each item processed in the current pass was placed
+    placed: Vec<bool>,
+    // The best layout of a finished pass, restored if packing is cancelled during a worse pass
+    best: Option<Snapshot<I>>,
+    result: Option<bool>,
+}
+
+/// The state of the bin after a finished pass
+struct Snapshot<I:Clone> {
+    quality: (bool, (usize, usize)),
+    items: Vec<PlacedItem<I>>,
+    bitmap: Bitmap2d,
+    rejections: Vec<(I, RejectReason)>,
+    candidate_counts: Vec<(I, CandidateCounts)>,
+    placed: Vec<bool>,
+    items_placed: usize,
+    items_rejected: usize,
+    offcut_rejections: usize,
+}
+
+impl<'b, 'o, I:Clone> PackIterator<'b, 'o, I> {
+    /// If 'sort' is false, the items are placed in the given order
+    pub(crate) fn new(bin: &'b mut Bin<I>, input: impl IntoIterator<Item=Item<I>>, mut options: PlaceOptions<'o>, sort: bool) -> Self {
+        let start = Instant::now();
+        let mut items: Vec<Item<I>> = input.into_iter().collect();
+        if sort {
+            sort_items(&mut items, &mut options, bin.item_order.as_deref().map(|x|x as _));
+        }
+        bin.stats = PackingStats::default();
+        bin.rejections.clear();
+        bin.candidate_counts.clear();
+        let mut iter = PackIterator {
+            any_rotatable: items.iter().any(|x|bin.allows_rotation(x)),
+            strategies: options.strategies().to_vec(),
+            // Each pass starts over from the state of the bin before packing
+            initial_items: bin.items.clone(),
+            initial_bitmap: bin.bitmap.clone(),
+            bin,
+            options,
+            items,
+            pass: 0,
+            next_item: 0,
+            all_fit: true,
+            mandatory_rejected: false,
+            failed_classes: HashSet::new(),
+            placed: vec![],
+            best: None,
+            result: None,
+        };
+        if iter.strategies.is_empty() {
+            iter.finish(false);
+        } else {
+            iter.start_pass();
+        }
+        iter.bin.stats.duration += start.elapsed();
+        #[cfg(feature = "metrics")]
+        if iter.result.is_some() {
+            iter.record_metrics();
+        }
+        iter
+    }
+
+    /// The items placed so far. Note that this may shrink again, if a new pass is started.
+    pub fn solution(&self) -> &[PlacedItem<I>] {
+        self.bin.solution()
+    }
+
+    /// The bin being packed
+    pub fn bin(&self) -> &Bin<I> {
+        self.bin
+    }
+
+    /// None while packing is still in progress. When finished, Some(true) if
+    /// all items which are not 'Item::optional' were placed, and Some(false) otherwise.
+    pub fn result(&self) -> Option<bool> {
+        self.result
+    }
+
+    /// True if the current pass is the last one, and it has placed at least as much as the best
+    /// earlier pass, so that items placed in it are never removed again
+    pub(crate) fn placements_are_final(&self) -> bool {
+        let is_final_pass = self.pass + 1 >= self.strategies.len() || !self.any_rotatable;
+        is_final_pass && self.best.as_ref().is_none_or(|best|self.quality() >= best.quality)
+    }
+
+    /// How the layout of the current pass compares to others: whether all mandatory items
+    /// were placed, then the number and area of the placed items
+    fn quality(&self) -> (bool, (usize, usize)) {
+        (!self.mandatory_rejected, self.bin.placed_quality())
+    }
+
+    /// Whether each item was placed, in packing order, in the layout currently in the bin.
+    /// Items which were not tried because packing was cancelled are missing at the end.
+    pub(crate) fn placed(&self) -> &[bool] {
+        &self.placed
+    }
+
+    /// The options and items, in the order they were packed
+    pub(crate) fn into_parts(self) -> (PlaceOptions<'o>, Vec<Item<I>>) {
+        (self.options, self.items)
+    }
+
+    fn start_pass(&mut self) {
+        #[cfg(feature = "log")]
+        log::debug!("Starting pass {} with strategy {:?}, {} items", self.pass + 1, self.strategies[self.pass], self.items.len());
+        self.bin.stats.passes += 1;
+        self.bin.stats.items_placed = 0;
+        self.bin.stats.items_rejected = 0;
+        self.bin.stats.offcut_rejections = 0;
+        self.bin.rejections.clear();
+        self.bin.candidate_counts.clear();
+        self.all_fit = true;
+        self.mandatory_rejected = false;
+        self.next_item = 0;
+        self.failed_classes.clear();
+        self.placed.clear();
+    }
+
+    fn place_next_item(&mut self) -> Step<I> {
+        let item = &self.items[self.next_item];
+        self.next_item += 1;
+        let strategy = self.strategies[self.pass];
+        // Items which are too large are rejected as usual below
+        if let Some(Err(error @ (ItemError::Empty | ItemError::Overflow))) = self.options.validate().then(||item.validate(self.bin)) {
+            let reason = RejectReason::Invalid(error);
+            self.all_fit = false;
+            self.mandatory_rejected |= !item.optional;
+            self.placed.push(false);
+            self.bin.stats.items_rejected += 1;
+            self.bin.rejections.push((item.id.clone(), reason));
+            return Step::Rejected(item.id.clone(), reason);
+        }
+        let tags = if let SpacingPolicy::SharedEdgeTagged(_) = self.options.spacing_policy() { item.tags } else { Tags::NONE };
+        let class = (item.w, item.h, self.bin.allows_rotation(item), item.margins, item.x_band.clone(), item.y_band.clone(), tags);
+        let options = &mut self.options;
+        // With a minimum offcut size or forbidden slivers, an item which failed may fit later, if the
+        // offcut or sliver it would have created has been filled. With an unloading order or regions,
+        // items of the same size may have different constraints.
+        let known_failure = options.deduplicate() && options.min_offcut().is_none() &&
+            !matches!(options.sliver_policy(), SliverPolicy::Forbid { .. }) && self.bin.unloading.is_none() &&
+            self.bin.regions.is_empty() && self.bin.forbidden.is_empty() && self.failed_classes.contains(&class);
+        self.bin.candidate_counter.set(CandidateCounts::default());
+        let placed = !known_failure && self.bin.add_to_best_fit(item, strategy, options);
+        if options.candidate_stats() {
+            self.bin.candidate_counts.push((item.id.clone(), self.bin.candidate_counter.get()));
+        }
+        if placed {
+            self.bin.stats.items_placed += 1;
+            self.placed.push(true);
+            return Step::Placed(self.bin.items.last().expect("item was just placed").clone());
+        }
+        self.all_fit = false;
+        self.mandatory_rejected |= !item.optional;
+        self.placed.push(false);
+        self.failed_classes.insert(class);
+        let reason = self.bin.reject(item);
+        #[cfg(feature = "log")]
+        log::trace!("Rejected {}x{} item: {:?}", item.w, item.h, reason);
+        Step::Rejected(item.id.clone(), reason)
+    }
+
+    fn finish(&mut self, result: bool) {
+        self.result = Some(result);
+        self.bin.stats.cancelled = self.options.was_cancelled();
+        if self.bin.stats.cancelled {
+            self.restore_best();
+        }
+        self.bin.stats.zone_utilization = self.bin.zone_utilization();
+        self.bin.stats.weighted_utilization = self.bin.weighted_utilization();
+        self.bin.stats.kerf_area = self.bin.kerf_area(self.options.spacing());
+        self.bin.stats.shared_cut_length = self.bin.shared_cut_length(self.options.spacing());
+        #[cfg(feature = "log")]
+        {
+            let stats = &self.bin.stats;
+            log::debug!("Packing finished after {} passes in {:?}: {} items placed, {} rejected, {:.1}% utilization{}",
+                stats.passes, stats.duration, stats.items_placed, stats.items_rejected, self.bin.utilization() * 100.0,
+                if stats.cancelled { " (cancelled)" } else { "" });
+        }
+    }
+
+    /// Emit the outcome of a finished packing through the 'metrics' facade.
+    /// Called once, after the duration of the last step has been added.
+    #[cfg(feature = "metrics")]
+    fn record_metrics(&self) {
+        let stats = &self.bin.stats;
+        let outcome = match self.result {
+            Some(true) => "all_fit",
+            _ if stats.cancelled => "cancelled",
+            _ => "partial",
+        };
+        metrics::counter!("bin_packing_2d_packings_total", "outcome" => outcome).increment(1);
+        metrics::counter!("bin_packing_2d_items_placed_total").increment(stats.items_placed as u64);
+        metrics::counter!("bin_packing_2d_items_rejected_total").increment(stats.items_rejected as u64);
+        metrics::histogram!("bin_packing_2d_pack_duration_seconds").record(stats.duration.as_secs_f64());
+        metrics::histogram!("bin_packing_2d_utilization_ratio").record(self.bin.utilization());
+    }
+
+    /// True if only optional items were rejected in the pass, and the utilization target is reached,
+    /// so that no further passes are needed
+    fn target_reached(&self) -> bool {
+        !self.mandatory_rejected && self.options.utilization_target().is_some_and(|target|self.bin.utilization() >= target)
+    }
+
+    /// Remember the layout of the finished pass, if it is better than that of all earlier passes
+    fn save_if_best(&mut self) {
+        let quality = self.quality();
+        if self.best.as_ref().is_some_and(|best|best.quality >= quality) {
+            return;
+        }
+        let stats = &self.bin.stats;
+        self.best = Some(Snapshot {
+            quality,
+            items: self.bin.items.clone(),
+            bitmap: self.bin.bitmap.clone(),
+            rejections: self.bin.rejections.clone(),
+            candidate_counts: self.bin.candidate_counts.clone(),
+            placed: self.placed.clone(),
+            items_placed: stats.items_placed,
+            items_rejected: stats.items_rejected,
+            offcut_rejections: stats.offcut_rejections,
+        });
+    }
+
+    /// Go back to the best layout of a finished pass, if the current, possibly
+    /// unfinished, pass has placed less
+    fn restore_best(&mut self) {
+        let quality = self.quality();
+        let Some(best) = self.best.take_if(|best|best.quality > quality) else {
+            return;
+        };
+        #[cfg(feature = "log")]
+        log::debug!("Restoring the layout of an earlier pass, with {} items placed", best.items_placed);
+        self.bin.items = best.items;
+        self.bin.bitmap = best.bitmap;
+        self.bin.rejections = best.rejections;
+        self.bin.candidate_counts = best.candidate_counts;
+        self.placed = best.placed;
+        self.mandatory_rejected = !best.quality.0;
+        self.bin.stats.items_placed = best.items_placed;
+        self.bin.stats.items_rejected = best.items_rejected;
+        self.bin.stats.offcut_rejections = best.offcut_rejections;
+        self.bin.items_changed();
+        self.bin.update_holes(false);
+    }
+
+    /// Called when all items of a pass have been processed. Returns the strategy
+    /// of the next pass, if one was started.
+    fn finish_pass(&mut self) -> Option<Strategy> {
+        self.bin.update_holes(!(self.all_fit || self.pass == 0));
+        #[cfg(feature = "log")]
+        log::debug!("Pass {} finished: {} items placed, {} rejected", self.pass + 1, self.bin.stats.items_placed, self.bin.stats.items_rejected);
+        if self.all_fit || self.target_reached() {
+            self.finish(true);
+            return None;
+        }
+        //No point in trying passes where rotation is allowed, if none of the items allow rotation
+        let mut last_pass = self.pass + 1 == self.strategies.len() || !self.any_rotatable || self.options.is_cancelled();
+        if !last_pass && self.options.should_degrade() {
+            #[cfg(feature = "log")]
+            log::debug!("Soft deadline passed, skipping the remaining passes");
+            self.bin.stats.degraded = true;
+            last_pass = true;
+        }
+        if last_pass {
+            // Keep an earlier pass which placed all mandatory items, rather than this one
+            if self.mandatory_rejected && self.best.as_ref().is_some_and(|best|best.quality.0) {
+                self.restore_best();
+            }
+            self.finish(!self.mandatory_rejected && !self.options.was_cancelled());
+            return None;
+        }
+        self.save_if_best();
+        self.pass += 1;
+        self.bin.items.clone_from(&self.initial_items);
+        self.bin.items_changed();
+        self.bin.bitmap.clone_from(&self.initial_bitmap);
+        self.start_pass();
+        Some(self.strategies[self.pass])
+    }
+}
+
+/// Put items in the order they are packed: mandatory items before optional ones, each
+/// largest first, or in the order given by 'compare' if set. The items are shuffled beforehand
+/// if a seed or random number generator is set. Sorting is stable, so that equal items keep
+/// their order.
+pub(crate) fn sort_items<I>(items: &mut [Item<I>], options: &mut PlaceOptions, compare: Option<&Compare<'_, I>>) {
+    options.shuffle(items);
+    match compare {
+        Some(compare) => items.sort_by(compare),
+        None => items.sort_by_key(|x|(x.optional, Reverse(x.size()))),
+    }
+}
+
+impl<I:Clone> Iterator for PackIterator<'_, '_, I> {
+    type Item = Step<I>;
+
+    fn next(&mut self) -> Option<Step<I>> {
+        if self.result.is_some() {
+            return None;
+        }
+        let start = Instant::now();
+        let step = if self.next_item < self.items.len() {
+            let step = self.place_next_item();
+            if self.options.is_cancelled() {
+                self.finish(false);
+            }
+            Some(step)
+        } else {
+            self.finish_pass().map(Step::Restarted)
+        };
+        self.bin.stats.duration += start.elapsed();
+        #[cfg(feature = "metrics")]
+        if self.result.is_some() {
+            self.record_metrics();
+        }
+        step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Quality;
+
+    #[test]
+    fn test_restart() {
+        let mut bin = Bin::new(10, 4);
+        let item = Item::rotatable(4, 10, 'A');
+        let steps: Vec<_> = bin.pack_stepwise([item], PlaceOptions::default()).map(|x|format!("{:?}", x)).collect();
+        assert_eq!(steps, vec![
+            "Rejected('A', NoSpace)".to_string(),
+            "Restarted(Rotate)".to_string(),
+            "Placed(PlacedItem { x0: 0, y0: 0, x1: 10, y1: 4, rotation: Cw90, overlap: 0, margins: [0, 0, 0, 0], tags: Tags(0), id: 'A' })".to_string(),
+        ]);
+        assert_eq!(bin.stats().passes, 2);
+    }
+
+    #[test]
+    fn test_quality() {
+        let items = || [Item::rotatable(4, 10, 'A'), Item::new(20, 1, 'B')];
+        let mut bin = Bin::new(10, 4);
+        bin.place_all(items().into_iter(), PlaceOptions::default().with_quality(Quality::Balanced));
+        assert_eq!(bin.stats().passes, 3);
+        let mut bin = Bin::new(10, 4);
+        bin.place_all(items().into_iter(), PlaceOptions::default().with_quality(Quality::Fast));
+        assert_eq!(bin.stats().passes, 1);
+        assert!(!bin.stats().degraded);
+        // A soft deadline which has passed lowers the quality, but still gives a result
+        let mut bin = Bin::new(10, 4);
+        let options = PlaceOptions::default().with_quality(Quality::Best).with_soft_deadline(Instant::now());
+        bin.place_all(items().into_iter(), options);
+        assert_eq!(bin.stats().passes, 1);
+        assert!(bin.stats().degraded);
+        assert!(!bin.stats().cancelled);
+        assert_eq!(bin.solution().len(), 0);
+    }
+
+    #[test]
+    fn test_cancel_keeps_best_pass() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        let mut bin = Bin::new(10, 4);
+        let items = [
+            Item::rotatable(4, 10, 'C'),
+            Item::new(3, 3, 'A').with_allow_rotate(false),
+            Item::new(2, 2, 'B').with_allow_rotate(false),
+        ];
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = cancelled.clone();
+        let mut packer = bin.pack_stepwise(items, PlaceOptions::default().with_cancel(move || flag.load(Ordering::Relaxed)));
+        for step in packer.by_ref() {
+            if let Step::Restarted(_) = step {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+        // The second pass placed only 'C' before being cancelled, the first pass placed 'A' and 'B'
+        assert_eq!(packer.placed(), &[false, true, true]);
+        let ids: Vec<_> = bin.solution().iter().map(|x|x.id).collect();
+        assert_eq!(ids, vec!['A', 'B']);
+        assert!(bin.stats().cancelled);
+        assert_eq!(bin.stats().items_placed, 2);
+        assert_eq!(bin.rejections().len(), 1);
+        assert!((bin.utilization() - 13.0 / 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_utilization_target() {
+        let items = |optional| [Item::rotatable(10, 6, 'A'), Item::rotatable(10, 5, 'B').with_optional(optional)];
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all(items(true).into_iter(), PlaceOptions::default().with_utilization_target(0.6)));
+        assert_eq!(bin.stats().passes, 1);
+        assert_eq!(bin.rejections().len(), 1);
+        // Below the target, the other passes are tried in case they fit the optional item
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all(items(true).into_iter(), PlaceOptions::default().with_utilization_target(0.7)));
+        assert!(bin.stats().passes > 1);
+        let mut bin = Bin::new(10, 10);
+        assert!(!bin.place_all(items(false).into_iter(), PlaceOptions::default().with_utilization_target(0.6)));
+    }
+
+    #[test]
+    fn test_optional_items() {
+        // The filler is the largest item, but is only packed after the mandatory ones
+        let items = [Item::new(10, 6, 'F').with_optional(true), Item::new(10, 5, 'A'), Item::new(10, 5, 'B')];
+        let mut bin = Bin::new(10, 10);
+        assert!(bin.place_all(items.clone().into_iter(), PlaceOptions::default()));
+        let placed: Vec<_> = bin.solution().iter().map(|x|x.id).collect();
+        assert_eq!(placed, vec!['A', 'B']);
+        assert_eq!(bin.rejections(), &[('F', RejectReason::NoSpace)]);
+        let mut bin = Bin::new(10, 12);
+        assert!(bin.place_all(items.into_iter().rev(), PlaceOptions::default()));
+        assert_eq!(bin.solution().len(), 2);
+        let mut bin = Bin::new(10, 9);
+        assert!(!bin.place_all([Item::new(10, 5, 'A'), Item::new(10, 5, 'B')].into_iter(), PlaceOptions::default()));
+    }
+}
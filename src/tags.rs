@@ -0,0 +1,46 @@
+use std::ops::BitOr;
+
+/// A set of up to 64 application defined tags, such as "rush order", which are attached
+/// to an item and copied to the placed item. Tags don't affect packing, but can be used
+/// to filter the solution (see `Bin::filter_by_tag`), or to render items in layers.
+///
+/// ```
+/// use bin_packing_2d::Tags;
+/// const RUSH_ORDER: Tags = Tags::tag(0);
+/// const FRAGILE: Tags = Tags::tag(1);
+/// let tags = RUSH_ORDER | FRAGILE;
+/// assert!(tags.contains(RUSH_ORDER));
+/// assert!(!Tags::NONE.contains(FRAGILE));
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash,Default)]
+pub struct Tags(pub u64);
+
+impl Tags {
+    /// No tags
+    pub const NONE: Tags = Tags(0);
+
+    /// The set containing only tag number 'index'. Panics if 'index' is 64 or more.
+    pub const fn tag(index: u32) -> Tags {
+        if index >= 64 {
+            panic!("Tag index must be below 64");
+        }
+        Tags(1 << index)
+    }
+
+    /// True if every tag in 'other' is also in this set
+    pub fn contains(&self, other: Tags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// True if no tags are set
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Tags {
+    type Output = Tags;
+    fn bitor(self, other: Tags) -> Tags {
+        Tags(self.0 | other.0)
+    }
+}
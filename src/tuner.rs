@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::{Bin, Corner, Heuristic, Item, PlaceOptions, ScanOrder, TieBreak};
+
+/// A combination of settings which a [`Tuner`] chooses between.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct TunerConfig {
+    /// How candidate positions are scored, see `PlaceOptions::with_heuristic`
+    pub heuristic: Heuristic,
+    /// The order positions are searched in, see `PlaceOptions::with_scan_order`
+    pub scan_order: ScanOrder,
+    /// How ties are broken, see `PlaceOptions::with_tie_break`
+    pub tie_break: TieBreak,
+}
+
+/// How often a configuration took part in a race, and how often it won
+#[derive(Debug,Clone,Copy,Default)]
+struct ArmStats {
+    plays: u64,
+    wins: u64,
+}
+
+/// Learns which configuration works best for each class of instances, such as the
+/// orders of a particular customer, and biases future packings toward it.
+///
+/// Each call to 'place_all' races a few configurations against each other, and keeps the
+/// best result. The configurations are chosen using the UCB1 bandit algorithm: mostly those
+/// which have won most often for the class, but also ones which have been tried too rarely
+/// to tell. This makes it possible to race only a few configurations per instance, instead
+/// of all of them. The statistics can be saved and loaded, so that they survive restarts.
+#[derive(Debug,Clone)]
+pub struct Tuner<K> {
+    configurations: Vec<TunerConfig>,
+    race_size: usize,
+    classes: HashMap<K, Vec<ArmStats>>,
+}
+
+impl<K:Clone+Eq+Hash> Tuner<K> {
+    /// Create a tuner racing 'race_size' configurations per packing, chosen from the
+    /// combinations of the two built-in heuristics, row and column scan orders, and both
+    /// tie-break policies. Panics if 'race_size' is 0.
+    pub fn new(race_size: usize) -> Tuner<K> {
+        let mut configurations = vec![];
+        for heuristic in [Heuristic::MaxContact, Heuristic::FirstFit] {
+            for scan_order in [ScanOrder::Rows(Corner::TopLeft), ScanOrder::Columns(Corner::TopLeft)] {
+                for tie_break in [TieBreak::ScanOrder, TieBreak::SmallestBoundingBox] {
+                    configurations.push(TunerConfig { heuristic, scan_order, tie_break });
+                }
+            }
+        }
+        Tuner::with_configurations(configurations, race_size)
+    }
+
+    /// Create a tuner choosing between the given configurations.
+    /// Panics if 'configurations' is empty or 'race_size' is 0.
+    pub fn with_configurations(configurations: Vec<TunerConfig>, race_size: usize) -> Tuner<K> {
+        if configurations.is_empty() || race_size == 0 {
+            panic!("A tuner needs at least one configuration, and must race at least one");
+        }
+        Tuner {
+            configurations,
+            race_size,
+            classes: HashMap::new(),
+        }
+    }
+
+    /// The configurations chosen between
+    pub fn configurations(&self) -> &[TunerConfig] {
+        &self.configurations
+    }
+
+    /// Pack 'input' into 'bin' with each of the configurations chosen for 'class', using
+    /// options created by 'options' with the heuristic, scan order and tie-break replaced.
+    /// The best result (as defined by `Bin::keep_best`) is kept in 'bin', and the configuration
+    /// which produced it is recorded as the winner. Returns true if all items were placed.
+    pub fn place_all<'o, I:Clone>(&mut self, class: &K, bin: &mut Bin<I>, input: impl Iterator<Item=Item<I>>, options: impl Fn() -> PlaceOptions<'o>) -> bool {
+        let items: Vec<Item<I>> = input.collect();
+        let chosen = self.choose(class);
+        let mut best: Option<(usize, bool, Bin<I>)> = None;
+        for arm in chosen.iter().copied() {
+            let config = self.configurations[arm];
+            let mut attempt = bin.clone();
+            attempt.autosave = None;
+            let all_fit = attempt.place_all(items.iter().cloned(), options()
+                .with_heuristic(config.heuristic)
+                .with_scan_order(config.scan_order)
+                .with_tie_break(config.tie_break));
+            let cancelled = attempt.stats().cancelled;
+            match &mut best {
+                None => best = Some((arm, all_fit, attempt)),
+                Some((winner, winner_fit, winner_bin)) => {
+                    if winner_bin.keep_best(attempt) {
+                        (*winner, *winner_fit) = (arm, all_fit);
+                    }
+                }
+            }
+            if cancelled {
+                break;
+            }
+        }
+        let (winner, all_fit, mut winner_bin) = best.expect("at least one configuration is raced");
+        // Cancelled races don't tell which configuration is best
+        if !winner_bin.stats().cancelled {
+            let stats = self.classes.get_mut(class).expect("created by 'choose'");
+            for arm in chosen {
+                stats[arm].plays += 1;
+            }
+            stats[winner].wins += 1;
+        }
+        winner_bin.autosave = bin.autosave.clone();
+        *bin = winner_bin;
+        all_fit
+    }
+
+    /// The configuration which has won most often for 'class', relative to how often it
+    /// was raced. None if no packing has been done for the class.
+    pub fn best_configuration(&self, class: &K) -> Option<TunerConfig> {
+        let stats = self.classes.get(class)?;
+        let rate = |x: &ArmStats| x.wins as f64 / x.plays.max(1) as f64;
+        let (arm, _) = stats.iter().enumerate().filter(|(_, x)|x.plays > 0)
+            .max_by(|(_, a), (_, b)|rate(a).total_cmp(&rate(b)))?;
+        Some(self.configurations[arm])
+    }
+
+    /// The indices of the configurations to race for 'class', in order of preference
+    fn choose(&mut self, class: &K) -> Vec<usize> {
+        let stats = self.classes.entry(class.clone()).or_insert_with(|| vec![ArmStats::default(); self.configurations.len()]);
+        let total: u64 = stats.iter().map(|x|x.plays).sum();
+        let upper_bound = |x: &ArmStats| {
+            if x.plays == 0 {
+                return f64::INFINITY;
+            }
+            x.wins as f64 / x.plays as f64 + (2.0 * (total as f64).ln() / x.plays as f64).sqrt()
+        };
+        let mut arms: Vec<usize> = (0..stats.len()).collect();
+        // Stable, so untried configurations are tried in order
+        arms.sort_by(|&a, &b|upper_bound(&stats[b]).total_cmp(&upper_bound(&stats[a])));
+        arms.truncate(self.race_size);
+        arms
+    }
+
+    /// Write the statistics learned so far, one line per class and configuration, as the
+    /// tab-separated fields `configuration plays wins class`, where 'configuration' is an index
+    /// into 'configurations'. The `Display` representation of the class must not contain line breaks.
+    pub fn write_stats(&self, mut writer: impl Write) -> io::Result<()> where K: Display {
+        for (class, stats) in &self.classes {
+            for (arm, x) in stats.iter().enumerate() {
+                writeln!(writer, "{}\t{}\t{}\t{}", arm, x.plays, x.wins, class)?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Read statistics written by 'write_stats', replacing the statistics of the classes read.
+    /// The tuner must use the same configurations as the one that wrote them.
+    pub fn read_stats(&mut self, reader: impl BufRead) -> io::Result<()> where K: FromStr {
+        let invalid = |line: usize| io::Error::new(io::ErrorKind::InvalidData, format!("invalid tuner statistics on line {}", line + 1));
+        let mut classes: HashMap<K, Vec<ArmStats>> = HashMap::new();
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.splitn(4, '\t');
+            let mut number = || -> io::Result<u64> {
+                fields.next().and_then(|x|x.parse().ok()).ok_or_else(|| invalid(line_number))
+            };
+            let (arm, plays, wins) = (number()? as usize, number()?, number()?);
+            let class = fields.next().and_then(|x|x.parse().ok()).ok_or_else(|| invalid(line_number))?;
+            if arm >= self.configurations.len() || wins > plays {
+                return Err(invalid(line_number));
+            }
+            classes.entry(class).or_insert_with(|| vec![ArmStats::default(); self.configurations.len()])[arm] = ArmStats { plays, wins };
+        }
+        self.classes.extend(classes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuner() {
+        let mut tuner = Tuner::new(2);
+        let items = || (0..6).map(|id| Item::rotatable(3 + id % 3, 4, id));
+        for _ in 0..10 {
+            let mut bin = Bin::new(12, 12);
+            assert!(tuner.place_all(&"boxes", &mut bin, items(), PlaceOptions::default));
+            assert_eq!(bin.solution().len(), 6);
+        }
+        let best = tuner.best_configuration(&"boxes");
+        assert!(best.is_some());
+        assert!(tuner.best_configuration(&"other").is_none());
+
+        let mut saved = vec![];
+        tuner.write_stats(&mut saved).unwrap();
+        let mut loaded: Tuner<String> = Tuner::new(2);
+        loaded.read_stats(&saved[..]).unwrap();
+        assert_eq!(loaded.best_configuration(&"boxes".to_string()), best);
+        assert!(loaded.read_stats(&b"99\t1\t0\tboxes\n"[..]).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use crate::{Bin, Item};
+
+/// Why an item can not be placed in a bin. See [`Item::validate`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum ItemError {
+    /// The item has zero width or height
+    Empty,
+    /// The area of the item, its size including margins, or its far edge when placed
+    /// somewhere in the bin, does not fit in a 'usize'
+    Overflow,
+    /// The item does not fit in the empty bin in any allowed orientation
+    TooLarge,
+}
+
+impl Display for ItemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemError::Empty => write!(f, "item has zero size"),
+            ItemError::Overflow => write!(f, "item size overflows"),
+            ItemError::TooLarge => write!(f, "item is larger than the bin"),
+        }
+    }
+}
+
+impl Error for ItemError {}
+
+pub(crate) fn validate<I:Clone>(item: &Item<I>, bin: &Bin<I>) -> Result<(), ItemError> {
+    if item.w == 0 || item.h == 0 {
+        return Err(ItemError::Empty);
+    }
+    // The item may be rotated, so each of its sides is checked against the longest side of the bin
+    let limit = bin.width().max(bin.height());
+    let [left, right, top, bottom] = item.margins;
+    let extent = |size: usize, before: usize, after: usize| size.checked_add(before)?.checked_add(after)?.checked_add(limit);
+    if item.w.checked_mul(item.h).is_none() || extent(item.w, left, right).is_none() || extent(item.h, top, bottom).is_none() {
+        return Err(ItemError::Overflow);
+    }
+    if bin.is_too_large(item) {
+        return Err(ItemError::TooLarge);
+    }
+    Ok(())
+}
+
+/// Validate each of 'items' against 'bin', see [`Item::validate`]. Returns the index of the
+/// first invalid item, with the reason.
+pub fn validate_all<I:Clone>(items: &[Item<I>], bin: &Bin<I>) -> Result<(), (usize, ItemError)> {
+    for (index, item) in items.iter().enumerate() {
+        validate(item, bin).map_err(|error|(index, error))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PlaceOptions, RejectReason};
+
+    #[test]
+    fn test_validate() {
+        let bin = Bin::new(10, 10);
+        assert_eq!(Item::new(10, 4, 'A').validate(&bin), Ok(()));
+        assert_eq!(Item::new(0, 4, 'B').validate(&bin), Err(ItemError::Empty));
+        assert_eq!(Item::new(usize::MAX, 4, 'C').validate(&bin), Err(ItemError::Overflow));
+        assert_eq!(Item::new(11, 4, 'D').validate(&bin), Err(ItemError::TooLarge));
+        let mut margins = Item::new(2, 2, 'E');
+        margins.margins = [usize::MAX - 1, 1, 0, 0];
+        assert_eq!(margins.validate(&bin), Err(ItemError::Overflow));
+        let items = [Item::new(3, 3, 'F'), Item::new(3, 0, 'G'), Item::new(0, 3, 'H')];
+        assert_eq!(validate_all(&items, &bin), Err((1, ItemError::Empty)));
+
+        // With validation, invalid items are rejected instead of panicking
+        let mut bin = Bin::new(10, 10);
+        assert!(!bin.place_all(items.into_iter(), PlaceOptions::default().with_validation(true)));
+        assert_eq!(bin.solution().len(), 1);
+        assert_eq!(bin.rejections(), &[('G', RejectReason::Invalid(ItemError::Empty)), ('H', RejectReason::Invalid(ItemError::Empty))]);
+    }
+}
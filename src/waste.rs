@@ -0,0 +1,48 @@
+use crate::bitmap::Bitmap2d;
+use crate::free_rects::maximal_free_rects;
+use crate::{Bin, Rectangle};
+
+/// See [`Bin::wasted_neighborhood`]
+pub(crate) fn wasted_neighborhood<I:Clone>(bin: &Bin<I>, halo: usize) -> Vec<usize> {
+    let (width, height) = (bin.width(), bin.height());
+    let Some(smallest) = bin.items.iter().min_by_key(|x|x.rect().area()) else {
+        return vec![];
+    };
+    let (w, h) = (smallest.width(), smallest.height());
+    // Free cells are usable if they are in a free rectangle which the smallest item fits in
+    let mut usable = Bitmap2d::with_backend(width, height, bin.bitmap.backend());
+    for rect in maximal_free_rects(&bin.bitmap) {
+        let (rw, rh) = (rect.width(), rect.height());
+        if (rw >= w && rh >= h) || (rw >= h && rh >= w) {
+            usable.fill(rect.x0, rect.y0, rw, rh, true);
+        }
+    }
+    bin.items.iter().map(|item| {
+        let halo = Rectangle {
+            x0: item.x0.saturating_sub(halo),
+            y0: item.y0.saturating_sub(halo),
+            x1: item.x1.saturating_add(halo).min(width),
+            y1: item.y1.saturating_add(halo).min(height),
+        };
+        let (hw, hh) = (halo.width(), halo.height());
+        let free = halo.area() - bin.bitmap.count_occupied(halo.x0, halo.y0, hw, hh);
+        free - usable.count_occupied(halo.x0, halo.y0, hw, hh)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Bin, Item};
+
+    #[test]
+    fn test_wasted_neighborhood() {
+        let mut bin = Bin::new(10, 6);
+        bin.set_default_allow_rotate(false);
+        // 'B' leaves a 1 wide gap to the right of it, which is too narrow for 'C'
+        assert!(bin.pack([Item::new(5, 4, 'A'), Item::new(4, 4, 'B'), Item::new(2, 2, 'C')]));
+        let waste = bin.wasted_neighborhood(1);
+        assert_eq!(waste, vec![0, 4, 0]);
+        assert_eq!(bin.wasted_neighborhood(0), vec![0; 3]);
+        assert!(Bin::<char>::new(5, 5).wasted_neighborhood(1).is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{Bin, Rectangle};
+
+/// A weight for each cell of a bin, for instance to mark premium regions of a sheet which are
+/// clear of knots. Used for the weighted utilization, see [`Bin::set_weight_map`]. Cells outside
+/// the map (for instance after a bin has grown) have weight 1.
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct WeightMap {
+    width: usize,
+    height: usize,
+    // Row by row
+    weights: Vec<u32>,
+}
+
+impl WeightMap {
+    /// A map of 'width' x 'height' cells, each with the given weight
+    pub fn new(width: usize, height: usize, weight: u32) -> WeightMap {
+        WeightMap { width, height, weights: vec![weight; width * height] }
+    }
+
+    /// The width of the map
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the map
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The weight of the cell at 'x', 'y'
+    pub fn get(&self, x: usize, y: usize) -> u32 {
+        if x < self.width && y < self.height { self.weights[y * self.width + x] } else { 1 }
+    }
+
+    /// Set the weight of the cells of 'rect' which lie within the map
+    pub fn fill(&mut self, rect: Rectangle, weight: u32) {
+        for y in rect.y0..rect.y1.min(self.height) {
+            let row = y * self.width;
+            for cell in &mut self.weights[row + rect.x0.min(self.width)..row + rect.x1.min(self.width)] {
+                *cell = weight;
+            }
+        }
+    }
+
+    /// The total weight of the cells `x0..x1` of row 'y'
+    fn row_weight(&self, y: usize, x0: usize, x1: usize) -> u64 {
+        if y >= self.height {
+            return (x1 - x0) as u64;
+        }
+        let (inside0, inside1) = (x0.min(self.width), x1.min(self.width));
+        let row = y * self.width;
+        let inside: u64 = self.weights[row + inside0..row + inside1].iter().map(|&x|x as u64).sum();
+        inside + (x1 - x0 - (inside1 - inside0)) as u64
+    }
+}
+
+/// See [`Bin::weighted_utilization`]
+pub(crate) fn weighted_utilization<I:Clone>(bin: &Bin<I>) -> Option<f64> {
+    let weights = bin.weights.as_ref()?;
+    let (mut occupied, mut total) = (0u64, 0u64);
+    for y in 0..bin.height() {
+        for run in bin.bitmap.row_runs(y) {
+            let weight = weights.row_weight(y, run.x0, run.x1);
+            total += weight;
+            if run.occupied {
+                occupied += weight;
+            }
+        }
+    }
+    Some(if total == 0 { 0.0 } else { occupied as f64 / total as f64 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Item, PlaceOptions};
+
+    #[test]
+    fn test_weighted_utilization() {
+        let mut bin = Bin::new(10, 4);
+        assert_eq!(bin.weighted_utilization(), None);
+        // The left half of the sheet counts three times as much
+        let mut weights = WeightMap::new(10, 4, 1);
+        weights.fill(Rectangle { x0: 0, y0: 0, x1: 5, y1: 20 }, 3);
+        assert_eq!((weights.get(4, 3), weights.get(5, 3), weights.get(0, 4)), (3, 1, 1));
+        bin.set_weight_map(weights);
+        assert!(bin.place_all([Item::new(5, 4, 'A')].into_iter(), PlaceOptions::default()));
+        assert_eq!(bin.utilization(), 0.5);
+        assert_eq!(bin.weighted_utilization(), Some(0.75));
+        assert_eq!(bin.stats().weighted_utilization, Some(0.75));
+        // Cells outside the map have weight 1
+        bin.set_weight_map(WeightMap::new(5, 4, 2));
+        assert_eq!(bin.weighted_utilization(), Some(40.0 / 60.0));
+    }
+}